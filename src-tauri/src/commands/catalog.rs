@@ -0,0 +1,54 @@
+use crate::core::catalog::{ThreadCatalog, ThreadQuery};
+use crate::error::CommandResult;
+use crate::state::ThreadCatalogState;
+use crate::utils::path::{custom_thread_catalogs_dir, thread_catalogs_dir};
+
+/// Returns the Borsh-encoded list of the available thread catalogs (`CatalogInfo`).
+#[tauri::command]
+pub fn get_thread_catalogs(catalog: tauri::State<ThreadCatalogState>) -> CommandResult<tauri::ipc::Response> {
+  let catalogs = catalog.read().unwrap().catalogs();
+  Ok(tauri::ipc::Response::new(borsh::to_vec(&catalogs)?))
+}
+
+/// Returns the Borsh-encoded threads of the catalog.
+#[tauri::command]
+pub fn get_catalog(name: String, catalog: tauri::State<ThreadCatalogState>) -> CommandResult<tauri::ipc::Response> {
+  let catalog = catalog.read().unwrap();
+  let threads = catalog
+    .get(&name)
+    .ok_or_else(|| anyhow::anyhow!("Unknown thread catalog {name}"))?;
+  Ok(tauri::ipc::Response::new(borsh::to_vec(threads)?))
+}
+
+/// Searches the threads by the Borsh-encoded `ThreadQuery`.
+/// Returns the Borsh-encoded found threads.
+#[tauri::command]
+pub fn search_threads(
+  request: tauri::ipc::Request<'_>,
+  catalog: tauri::State<ThreadCatalogState>,
+) -> CommandResult<tauri::ipc::Response> {
+  if let tauri::ipc::InvokeBody::Raw(data) = request.body() {
+    let query: ThreadQuery = borsh::from_slice(data)?;
+    let threads = catalog.read().unwrap().search(&query);
+    Ok(tauri::ipc::Response::new(borsh::to_vec(&threads)?))
+  } else {
+    Err(anyhow::anyhow!("Invalid request body").into())
+  }
+}
+
+/// Reloads the bundled and custom thread catalogs, e.g., after the user has added or edited a custom one.
+/// Returns the Borsh-encoded list of the available thread catalogs (`CatalogInfo`).
+#[tauri::command]
+pub fn reload_thread_catalogs<R: tauri::Runtime>(
+  app_handle: tauri::AppHandle<R>,
+  catalog: tauri::State<ThreadCatalogState>,
+) -> CommandResult<tauri::ipc::Response> {
+  // The files are read before locking the state, so the searches aren't blocked meanwhile.
+  let reloaded = ThreadCatalog::load(
+    thread_catalogs_dir(&app_handle)?,
+    custom_thread_catalogs_dir(&app_handle)?,
+  )?;
+  let catalogs = reloaded.catalogs();
+  *catalog.write().unwrap() = reloaded;
+  Ok(tauri::ipc::Response::new(borsh::to_vec(&catalogs)?))
+}
@@ -0,0 +1,63 @@
+use crate::core::convert::{ImageConversion, convert_image};
+use crate::core::fonts::StitchFont;
+use crate::core::parser::PatternFormat;
+use crate::core::pattern::display::DisplaySettings;
+use crate::core::pattern::{PaletteItem, unix_timestamp};
+use crate::core::symbols::symbol_candidates;
+use crate::error::CommandResult;
+use crate::recovery;
+use crate::state::{HistoryState, PatternKey, PatternsState, RecoveryState, ThreadCatalogState};
+use crate::utils::path::app_document_dir;
+
+/// Converts the image into a new pattern and opens it as `create_pattern` does.
+///
+/// Accepts the Borsh-encoded `ImageConversion` and returns the pattern key along with the pattern.
+#[tauri::command]
+pub fn import_image<R: tauri::Runtime>(
+  request: tauri::ipc::Request<'_>,
+  window: tauri::WebviewWindow<R>,
+  app_handle: tauri::AppHandle<R>,
+  patterns: tauri::State<PatternsState>,
+  history: tauri::State<HistoryState<R>>,
+  recovery: tauri::State<RecoveryState>,
+  thread_catalog: tauri::State<ThreadCatalogState>,
+) -> CommandResult<Vec<u8>> {
+  if let tauri::ipc::InvokeBody::Raw(data) = request.body() {
+    log::trace!("Converting image into pattern");
+
+    let conversion: ImageConversion = borsh::from_slice(data)?;
+    let catalog = thread_catalog
+      .read()
+      .unwrap()
+      .get(&conversion.catalog)
+      .ok_or_else(|| anyhow::anyhow!("Unknown thread catalog {}", conversion.catalog))?
+      .iter()
+      .cloned()
+      .map(PaletteItem::from)
+      .collect::<Vec<_>>();
+
+    // The symbols are taken from the stitch font the new pattern uses by default.
+    let font = StitchFont::resolve(&app_handle, &DisplaySettings::default().default_stitch_font)?;
+    let candidates = symbol_candidates(&font)?;
+
+    let mut patproj = convert_image(&conversion.image, &catalog, &conversion.options, &candidates)?;
+
+    patproj.pattern.info.created_at = Some(unix_timestamp());
+    patproj.file_path =
+      app_document_dir(&app_handle)?.join(format!("{}.{}", patproj.pattern.info.title, PatternFormat::default()));
+
+    let pattern_key = PatternKey::from(&patproj.file_path);
+    recovery::mark_persisted(&recovery, &pattern_key, &patproj)?;
+    let result = borsh::to_vec(&(&pattern_key, &patproj))?;
+
+    let mut patterns = patterns.write().unwrap();
+    let mut history = history.write().unwrap();
+    history.get_mut(&pattern_key).notify(&window, &pattern_key)?;
+    patterns.insert(pattern_key, patproj);
+
+    log::trace!("Image has been converted into pattern");
+    Ok(result)
+  } else {
+    Err(anyhow::anyhow!("Invalid request body").into())
+  }
+}
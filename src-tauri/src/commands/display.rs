@@ -1,5 +1,5 @@
-use crate::core::actions::{Action, SetDisplayModeAction, ShowSymbolsAction};
-use crate::display::DisplayMode;
+use crate::core::actions::{Action, SetDisplayModeAction, ShowSymbolsAction, UpdateDisplaySettingsAction};
+use crate::display::{DisplayMode, DisplaySetting};
 use crate::error::CommandResult;
 use crate::state::{HistoryState, PatternKey, PatternsState};
 
@@ -19,7 +19,9 @@ pub fn set_display_mode<R: tauri::Runtime>(
   action.perform(&window, patproj)?;
 
   let mut history = history.write().unwrap();
-  history.get_mut(&pattern_key).push(Box::new(action));
+  let history = history.get_mut(&pattern_key);
+  history.push(Box::new(action));
+  history.notify(&window, &pattern_key)?;
 
   Ok(())
 }
@@ -39,7 +41,36 @@ pub fn show_symbols<R: tauri::Runtime>(
   action.perform(&window, patproj)?;
 
   let mut history = history.write().unwrap();
-  history.get_mut(&pattern_key).push(Box::new(action));
+  let history = history.get_mut(&pattern_key);
+  history.push(Box::new(action));
+  history.notify(&window, &pattern_key)?;
 
   Ok(())
 }
+
+/// Updates any single display setting: a toggle, the zoom or one of the settings sub-structures.
+#[tauri::command]
+pub fn update_display_setting<R: tauri::Runtime>(
+  request: tauri::ipc::Request<'_>,
+  window: tauri::WebviewWindow<R>,
+  history: tauri::State<HistoryState<R>>,
+  patterns: tauri::State<PatternsState>,
+) -> CommandResult<()> {
+  if let tauri::ipc::InvokeBody::Raw(data) = request.body() {
+    let pattern_key = request.headers().get("patternKey").unwrap().to_str().unwrap().into();
+    let setting: DisplaySetting = borsh::from_slice(data)?;
+
+    let mut patterns = patterns.write().unwrap();
+    let action = UpdateDisplaySettingsAction::new(setting);
+    action.perform(&window, patterns.get_mut(&pattern_key).unwrap())?;
+
+    let mut history = history.write().unwrap();
+    let history = history.get_mut(&pattern_key);
+    history.push(Box::new(action));
+    history.notify(&window, &pattern_key)?;
+
+    Ok(())
+  } else {
+    Err(anyhow::anyhow!("Invalid request body").into())
+  }
+}
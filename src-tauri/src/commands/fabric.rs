@@ -1,4 +1,5 @@
-use crate::core::actions::{Action, UpdateFabricPropertiesAction};
+use crate::core::actions::{Action, CropCanvasAction, ResizeCanvasAction, UpdateFabricPropertiesAction};
+use crate::core::pattern::{CanvasCrop, CanvasResize};
 use crate::error::CommandResult;
 use crate::state::{HistoryState, PatternsState};
 
@@ -18,7 +19,77 @@ pub fn update_fabric<R: tauri::Runtime>(
     action.perform(&window, patterns.get_mut(&pattern_key).unwrap())?;
 
     let mut history = history.write().unwrap();
-    history.get_mut(&pattern_key).push(Box::new(action));
+    let history = history.get_mut(&pattern_key);
+    history.push(Box::new(action));
+    history.notify(&window, &pattern_key)?;
+
+    Ok(())
+  } else {
+    Err(anyhow::anyhow!("Invalid request body").into())
+  }
+}
+
+/// Resizes the canvas, placing the pattern content according to the anchor or the explicit offset.
+#[tauri::command]
+pub fn resize_canvas<R: tauri::Runtime>(
+  request: tauri::ipc::Request<'_>,
+  window: tauri::WebviewWindow<R>,
+  history: tauri::State<HistoryState<R>>,
+  patterns: tauri::State<PatternsState>,
+) -> CommandResult<()> {
+  if let tauri::ipc::InvokeBody::Raw(data) = request.body() {
+    let pattern_key = request.headers().get("patternKey").unwrap().to_str().unwrap().into();
+    let CanvasResize { width, height, placement } = borsh::from_slice(data)?;
+    if width == 0 || height == 0 {
+      return Err(anyhow::anyhow!("The canvas size must not be zero").into());
+    }
+
+    let mut patterns = patterns.write().unwrap();
+    let patproj = patterns.get_mut(&pattern_key).unwrap();
+    let fabric = &patproj.pattern.fabric;
+    let offset = placement.offset((fabric.width, fabric.height), (width, height));
+    let action = ResizeCanvasAction::new(width, height, offset);
+    action.perform(&window, patproj)?;
+
+    let mut history = history.write().unwrap();
+    let history = history.get_mut(&pattern_key);
+    history.push(Box::new(action));
+    history.notify(&window, &pattern_key)?;
+
+    Ok(())
+  } else {
+    Err(anyhow::anyhow!("Invalid request body").into())
+  }
+}
+
+/// Crops the canvas to the given area or, if it isn't specified, to the pattern content.
+/// Does nothing if there is no area and the pattern is empty.
+#[tauri::command]
+pub fn crop_canvas<R: tauri::Runtime>(
+  request: tauri::ipc::Request<'_>,
+  window: tauri::WebviewWindow<R>,
+  history: tauri::State<HistoryState<R>>,
+  patterns: tauri::State<PatternsState>,
+) -> CommandResult<()> {
+  if let tauri::ipc::InvokeBody::Raw(data) = request.body() {
+    let pattern_key = request.headers().get("patternKey").unwrap().to_str().unwrap().into();
+    let CanvasCrop { area, margin } = borsh::from_slice(data)?;
+
+    let mut patterns = patterns.write().unwrap();
+    let patproj = patterns.get_mut(&pattern_key).unwrap();
+    let Some(area) = area.or_else(|| patproj.pattern.content_bounds()) else {
+      return Ok(());
+    };
+    if area.width == 0 || area.height == 0 {
+      return Err(anyhow::anyhow!("The crop area must not be empty").into());
+    }
+    let action = CropCanvasAction::new(area, margin);
+    action.perform(&window, patproj)?;
+
+    let mut history = history.write().unwrap();
+    let history = history.get_mut(&pattern_key);
+    history.push(Box::new(action));
+    history.notify(&window, &pattern_key)?;
 
     Ok(())
   } else {
@@ -1,7 +1,6 @@
-use convert_case::{Case, Casing};
-use tauri::Manager;
-
+use crate::core::fonts::StitchFont;
 use crate::error::CommandResult;
+use crate::utils::path::stitch_font_path;
 
 #[tauri::command]
 pub fn get_all_text_font_families() -> CommandResult<Vec<String>> {
@@ -14,11 +13,23 @@ pub fn load_stitch_font<R: tauri::Runtime>(
   font_family: String,
   app_handle: tauri::AppHandle<R>,
 ) -> CommandResult<tauri::ipc::Response> {
-  let font_family = font_family.to_case(Case::Snake);
-  let font_path = app_handle.path().resolve(
-    format!("resources/fonts/{font_family}.ttf"),
-    tauri::path::BaseDirectory::Resource,
-  )?;
+  let font_path = stitch_font_path(&app_handle, &font_family)?;
   let content = std::fs::read(font_path)?;
   Ok(tauri::ipc::Response::new(content))
 }
+
+/// Describes the glyphs of the stitch font which can be used as symbols.
+/// The bundled stitch fonts take precedence over the system fonts of the same family.
+///
+/// Returns the Borsh-encoded `FontDescription`.
+/// If `preview_size` is specified, each glyph includes its grayscale preview of that size (in pixels).
+#[tauri::command]
+pub fn get_stitch_font_glyphs<R: tauri::Runtime>(
+  font_family: String,
+  preview_size: Option<u32>,
+  app_handle: tauri::AppHandle<R>,
+) -> CommandResult<tauri::ipc::Response> {
+  let font = StitchFont::resolve(&app_handle, &font_family)?;
+  let description = font.describe(preview_size)?;
+  Ok(tauri::ipc::Response::new(borsh::to_vec(&description)?))
+}
@@ -18,7 +18,9 @@ pub fn update_grid<R: tauri::Runtime>(
     action.perform(&window, patterns.get_mut(&pattern_key).unwrap())?;
 
     let mut history = history.write().unwrap();
-    history.get_mut(&pattern_key).push(Box::new(action));
+    let history = history.get_mut(&pattern_key);
+    history.push(Box::new(action));
+    history.notify(&window, &pattern_key)?;
 
     Ok(())
   } else {
@@ -1,5 +1,7 @@
 use tauri::WebviewWindow;
 
+use crate::core::actions::{emit_pattern_changed, with_muted_events};
+use crate::core::history::{HistoryLimits, HistoryStep};
 use crate::error::CommandResult;
 use crate::state::{HistoryState, PatternKey, PatternsState};
 
@@ -12,9 +14,11 @@ pub fn undo<R: tauri::Runtime>(
 ) -> CommandResult<()> {
   let mut history = history.write().unwrap();
   let mut patterns = patterns.write().unwrap();
-  if let Some(action) = history.get_mut(&pattern_key).undo() {
+  let history = history.get_mut(&pattern_key);
+  if let Some(action) = history.undo() {
     action.revoke(&window, patterns.get_mut(&pattern_key).unwrap())?;
   }
+  history.notify(&window, &pattern_key)?;
   Ok(())
 }
 
@@ -27,8 +31,89 @@ pub fn redo<R: tauri::Runtime>(
 ) -> CommandResult<()> {
   let mut history = history.write().unwrap();
   let mut patterns = patterns.write().unwrap();
-  if let Some(action) = history.get_mut(&pattern_key).redo() {
+  let history = history.get_mut(&pattern_key);
+  if let Some(action) = history.redo() {
     action.perform(&window, patterns.get_mut(&pattern_key).unwrap())?;
   }
+  history.notify(&window, &pattern_key)?;
   Ok(())
 }
+
+/// Returns the timeline of the actions in the history.
+#[tauri::command]
+pub fn get_history<R: tauri::Runtime>(
+  pattern_key: PatternKey,
+  history: tauri::State<HistoryState<R>>,
+) -> CommandResult<Vec<u8>> {
+  let history = history.read().unwrap();
+  let description = history
+    .get(&pattern_key)
+    .map(|history| history.describe())
+    .unwrap_or_default();
+  Ok(borsh::to_vec(&description)?)
+}
+
+/// Undoes or redoes as many actions as needed to reach the given position in the history timeline.
+///
+/// The events of the individual actions are muted.
+/// Instead, the `pattern:changed` event is emitted once, so the frontend reloads the resulting pattern.
+/// If an action fails, the history stops at the last successfully applied action.
+#[tauri::command]
+pub fn jump_to_history<R: tauri::Runtime>(
+  pattern_key: PatternKey,
+  index: usize,
+  window: WebviewWindow<R>,
+  history: tauri::State<HistoryState<R>>,
+  patterns: tauri::State<PatternsState>,
+) -> CommandResult<()> {
+  let mut history = history.write().unwrap();
+  let mut patterns = patterns.write().unwrap();
+  let history = history.get_mut(&pattern_key);
+  let patproj = patterns.get_mut(&pattern_key).unwrap();
+
+  let result = with_muted_events(|| {
+    history.jump_to(index, |step| match step {
+      HistoryStep::Undo(action) => action.revoke(&window, patproj),
+      HistoryStep::Redo(action) => action.perform(&window, patproj),
+    })
+  });
+
+  // Even if an action fails, the preceding ones are already applied, so the frontend is updated anyway.
+  emit_pattern_changed(&window)?;
+  history.notify(&window, &pattern_key)?;
+  result?;
+  Ok(())
+}
+
+/// Starts grouping the subsequent actions into a single undo step.
+#[tauri::command]
+pub fn begin_transaction<R: tauri::Runtime>(pattern_key: PatternKey, history: tauri::State<HistoryState<R>>) {
+  history.write().unwrap().get_mut(&pattern_key).begin_transaction();
+}
+
+/// Finishes grouping the actions started by the `begin_transaction` command.
+#[tauri::command]
+pub fn commit_transaction<R: tauri::Runtime>(
+  pattern_key: PatternKey,
+  window: WebviewWindow<R>,
+  history: tauri::State<HistoryState<R>>,
+) -> CommandResult<()> {
+  let mut history = history.write().unwrap();
+  let history = history.get_mut(&pattern_key);
+  history.commit_transaction();
+  history.notify(&window, &pattern_key)?;
+  Ok(())
+}
+
+/// Updates the depth and the memory budget (in bytes) of the undo history of all the patterns.
+#[tauri::command]
+pub fn set_history_limits<R: tauri::Runtime>(
+  max_depth: usize,
+  max_memory: usize,
+  history: tauri::State<HistoryState<R>>,
+) {
+  history
+    .write()
+    .unwrap()
+    .set_limits(HistoryLimits { max_depth, max_memory });
+}
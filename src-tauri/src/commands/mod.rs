@@ -1,3 +1,5 @@
+pub mod catalog;
+pub mod convert;
 pub mod display;
 pub mod fabric;
 pub mod fonts;
@@ -6,4 +8,6 @@ pub mod history;
 pub mod palette;
 pub mod path;
 pub mod pattern;
+pub mod print;
+pub mod recovery;
 pub mod stitches;
@@ -1,6 +1,9 @@
 use crate::core::actions::{
-  Action, AddPaletteItemAction, RemovePaletteItemsAction, UpdatePaletteDisplaySettingsAction,
+  Action, AddPaletteItemAction, CompositeAction, PaletteItemFormats, PaletteItemSymbols, RemovePaletteItemsAction,
+  UpdatePaletteDisplaySettingsAction, UpdatePaletteItemFormatsAction, UpdatePaletteItemSymbolsAction,
 };
+use crate::core::fonts::StitchFont;
+use crate::core::symbols::{assign_symbols, symbol_candidates};
 use crate::error::CommandResult;
 use crate::state::{HistoryState, PatternsState};
 
@@ -22,7 +25,9 @@ pub fn add_palette_item<R: tauri::Runtime>(
       action.perform(&window, patproj)?;
 
       let mut history = history.write().unwrap();
-      history.get_mut(&pattern_key).push(Box::new(action));
+      let history = history.get_mut(&pattern_key);
+      history.push(Box::new(action));
+      history.notify(&window, &pattern_key)?;
     }
 
     Ok(())
@@ -46,7 +51,9 @@ pub fn remove_palette_items<R: tauri::Runtime>(
   action.perform(&window, patterns.get_mut(&pattern_key).unwrap())?;
 
   let mut history = history.write().unwrap();
-  history.get_mut(&pattern_key).push(Box::new(action));
+  let history = history.get_mut(&pattern_key);
+  history.push(Box::new(action));
+  history.notify(&window, &pattern_key)?;
 
   Ok(())
 }
@@ -67,10 +74,128 @@ pub fn update_palette_display_settings<R: tauri::Runtime>(
     action.perform(&window, patterns.get_mut(&pattern_key).unwrap())?;
 
     let mut history = history.write().unwrap();
-    history.get_mut(&pattern_key).push(Box::new(action));
+    let history = history.get_mut(&pattern_key);
+    history.push(Box::new(action));
+    history.notify(&window, &pattern_key)?;
 
     Ok(())
   } else {
     Err(anyhow::anyhow!("Invalid request body").into())
   }
 }
+
+#[tauri::command]
+pub fn update_palette_item_symbols<R: tauri::Runtime>(
+  request: tauri::ipc::Request<'_>,
+  window: tauri::WebviewWindow<R>,
+  history: tauri::State<HistoryState<R>>,
+  patterns: tauri::State<PatternsState>,
+) -> CommandResult<()> {
+  if let tauri::ipc::InvokeBody::Raw(data) = request.body() {
+    let pattern_key = request.headers().get("patternKey").unwrap().to_str().unwrap().into();
+    let PaletteItemSymbols { palindex, symbols } = borsh::from_slice(data)?;
+
+    let mut patterns = patterns.write().unwrap();
+    let action = UpdatePaletteItemSymbolsAction::new(palindex, symbols);
+    action.perform(&window, patterns.get_mut(&pattern_key).unwrap())?;
+
+    let mut history = history.write().unwrap();
+    let history = history.get_mut(&pattern_key);
+    history.push(Box::new(action));
+    history.notify(&window, &pattern_key)?;
+
+    Ok(())
+  } else {
+    Err(anyhow::anyhow!("Invalid request body").into())
+  }
+}
+
+#[tauri::command]
+pub fn update_palette_item_formats<R: tauri::Runtime>(
+  request: tauri::ipc::Request<'_>,
+  window: tauri::WebviewWindow<R>,
+  history: tauri::State<HistoryState<R>>,
+  patterns: tauri::State<PatternsState>,
+) -> CommandResult<()> {
+  if let tauri::ipc::InvokeBody::Raw(data) = request.body() {
+    let pattern_key = request.headers().get("patternKey").unwrap().to_str().unwrap().into();
+    let PaletteItemFormats { palindex, formats } = borsh::from_slice(data)?;
+
+    let mut patterns = patterns.write().unwrap();
+    let action = UpdatePaletteItemFormatsAction::new(palindex, formats);
+    action.perform(&window, patterns.get_mut(&pattern_key).unwrap())?;
+
+    let mut history = history.write().unwrap();
+    let history = history.get_mut(&pattern_key);
+    history.push(Box::new(action));
+    history.notify(&window, &pattern_key)?;
+
+    Ok(())
+  } else {
+    Err(anyhow::anyhow!("Invalid request body").into())
+  }
+}
+
+/// Assigns distinct symbols from the stitch font to the given palette items, or to the whole palette if none are given.
+#[tauri::command]
+pub fn auto_assign_symbols<R: tauri::Runtime>(
+  palette_item_indexes: Option<Vec<u8>>,
+  request: tauri::ipc::Request<'_>,
+  app_handle: tauri::AppHandle<R>,
+  window: tauri::WebviewWindow<R>,
+  history: tauri::State<HistoryState<R>>,
+  patterns: tauri::State<PatternsState>,
+) -> CommandResult<()> {
+  let pattern_key = request.headers().get("patternKey").unwrap().to_str().unwrap().into();
+
+  // Scanning the font glyphs takes a while, so the patterns are not locked meanwhile.
+  let font_family = patterns
+    .read()
+    .unwrap()
+    .get(&pattern_key)
+    .unwrap()
+    .display_settings
+    .default_stitch_font
+    .clone();
+  let font = StitchFont::resolve(&app_handle, &font_family)?;
+  let candidates = symbol_candidates(&font)?;
+
+  let mut patterns = patterns.write().unwrap();
+  let patproj = patterns.get_mut(&pattern_key).unwrap();
+
+  let palette_item_indexes = palette_item_indexes.unwrap_or_else(|| {
+    (0..patproj.pattern.palette.len())
+      .map(|palindex| palindex as u8)
+      .collect()
+  });
+  let palette_size = patproj.pattern.palette.len();
+  if let Some(palindex) = palette_item_indexes
+    .iter()
+    .find(|&&palindex| palindex as usize >= palette_size)
+  {
+    return Err(anyhow::anyhow!("There is no palette item with the index {palindex}").into());
+  }
+  let mut actions = assign_symbols(
+    &candidates,
+    &patproj.display_settings.symbols,
+    &patproj.pattern.stitch_counts(),
+    &palette_item_indexes,
+  )
+  .into_iter()
+  .map(|(palindex, symbols)| Box::new(UpdatePaletteItemSymbolsAction::new(palindex, symbols)) as Box<dyn Action<R>>)
+  .collect::<Vec<_>>();
+
+  let action = match actions.len() {
+    0 => return Ok(()),
+    1 => actions.pop().unwrap(),
+    _ => Box::new(CompositeAction::new(actions)),
+  };
+  action.perform(&window, patproj)?;
+
+  let mut history = history.write().unwrap();
+  let history = history.get_mut(&pattern_key);
+  history.push(action);
+  history.notify(&window, &pattern_key)?;
+
+  Ok(())
+}
@@ -1,35 +1,80 @@
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use tauri::Emitter;
+
+use crate::core::actions::{Action, ActionRegistry, UpdatePatternInfoAction};
+use crate::core::history::History;
 use crate::core::parser::{self, PatternFormat};
 use crate::core::pattern::display::DisplaySettings;
 use crate::core::pattern::print::PrintSettings;
-use crate::core::pattern::{Fabric, Pattern, PatternProject};
+use crate::core::pattern::{Fabric, Pattern, PatternProject, unix_timestamp};
 use crate::error::CommandResult;
-use crate::state::{PatternKey, PatternsState};
-use crate::utils::path::app_document_dir;
+use crate::recovery;
+use crate::state::{DrawingModesState, HistoryState, PatternKey, PatternsState, RecoveryState};
+use crate::utils::path::{app_document_dir, app_recovery_dir};
 
+/// Loads the pattern and returns it along with its key.
+/// If the `lazy` header is set to `true`, the pattern is returned without stitches.
+/// They are meant to be requested by regions via `get_stitches_in_region` then.
 #[tauri::command]
-pub fn load_pattern(request: tauri::ipc::Request<'_>, patterns: tauri::State<PatternsState>) -> CommandResult<Vec<u8>> {
+pub fn load_pattern<R: tauri::Runtime>(
+  request: tauri::ipc::Request<'_>,
+  window: tauri::WebviewWindow<R>,
+  patterns: tauri::State<PatternsState>,
+  history: tauri::State<HistoryState<R>>,
+  recovery: tauri::State<RecoveryState>,
+) -> CommandResult<Vec<u8>> {
   log::trace!("Loading pattern");
   let file_path: std::path::PathBuf = request.headers().get("filePath").unwrap().to_str().unwrap().into();
+  let lazy = request
+    .headers()
+    .get("lazy")
+    .and_then(|value| value.to_str().ok())
+    .is_some_and(|value| value == "true");
+  let serialize = |pattern_key: &PatternKey, patproj: &PatternProject| {
+    if lazy {
+      borsh::to_vec(&(pattern_key, &patproj.without_stitches()))
+    } else {
+      borsh::to_vec(&(pattern_key, patproj))
+    }
+  };
 
   let mut patterns = patterns.write().unwrap();
   let pattern_key = PatternKey::from(&file_path);
   if let Some(pattern) = patterns.get(&pattern_key) {
     log::trace!("Pattern loaded");
-    return Ok(borsh::to_vec(&(pattern_key, pattern))?);
+    return Ok(serialize(&pattern_key, pattern)?);
   }
 
   // Change the original file path with the path to `.embproj` file.
   let mut new_file_path = file_path.clone();
   new_file_path.set_extension(PatternFormat::default().to_string());
 
-  let mut pattern = match PatternFormat::try_from(file_path.extension())? {
-    PatternFormat::Xsd => parser::xsd::parse_pattern(file_path)?,
-    PatternFormat::Oxs => parser::oxs::parse_pattern(file_path)?,
-    PatternFormat::EmbProj => parser::embproj::parse_pattern(file_path)?,
+  let (mut pattern, persisted_history) = match PatternFormat::try_from(file_path.extension())? {
+    PatternFormat::Xsd => (parser::xsd::parse_pattern(file_path)?, None),
+    PatternFormat::Oxs => (parser::oxs::parse_pattern(file_path)?, None),
+    PatternFormat::EmbProj => {
+      // The history is not essential, so the pattern is opened even if its history is broken.
+      let persisted_history = parser::embproj::parse_history(&file_path)
+        .inspect_err(|err| log::warn!("Failed to parse the pattern history: {err:?}"))
+        .ok()
+        .flatten();
+      (parser::embproj::parse_pattern(file_path)?, persisted_history)
+    }
   };
   pattern.file_path = new_file_path;
+  recovery::mark_persisted(&recovery, &pattern_key, &pattern)?;
 
-  let result = borsh::to_vec(&(&pattern_key, &pattern))?;
+  let mut history = history.write().unwrap();
+  if let Some(persisted_history) = persisted_history {
+    match History::restore(&persisted_history, &ActionRegistry::default(), history.limits()) {
+      Ok(restored) => history.insert(pattern_key.clone(), restored),
+      Err(err) => log::warn!("Failed to restore the pattern history: {err:?}"),
+    }
+  }
+  history.get_mut(&pattern_key).notify(&window, &pattern_key)?;
+
+  let result = serialize(&pattern_key, &pattern)?;
   patterns.insert(pattern_key, pattern);
 
   log::trace!("Pattern loaded");
@@ -39,8 +84,11 @@ pub fn load_pattern(request: tauri::ipc::Request<'_>, patterns: tauri::State<Pat
 #[tauri::command]
 pub fn create_pattern<R: tauri::Runtime>(
   request: tauri::ipc::Request<'_>,
+  window: tauri::WebviewWindow<R>,
   app_handle: tauri::AppHandle<R>,
   patterns: tauri::State<PatternsState>,
+  history: tauri::State<HistoryState<R>>,
+  recovery: tauri::State<RecoveryState>,
 ) -> CommandResult<Vec<u8>> {
   // println!("Name {}", app_handle.package_info().name);
   // println!("Version {}", app_handle.package_info().version.to_string());
@@ -48,7 +96,8 @@ pub fn create_pattern<R: tauri::Runtime>(
     log::trace!("Creating new pattern");
 
     let fabric: Fabric = borsh::from_slice(data)?;
-    let pattern = Pattern::new(fabric);
+    let mut pattern = Pattern::new(fabric);
+    pattern.info.created_at = Some(unix_timestamp());
     let patproj = PatternProject {
       file_path: app_document_dir(&app_handle)?.join(format!("{}.{}", pattern.info.title, PatternFormat::default())),
       pattern,
@@ -57,9 +106,12 @@ pub fn create_pattern<R: tauri::Runtime>(
     };
 
     let pattern_key = PatternKey::from(&patproj.file_path);
+    recovery::mark_persisted(&recovery, &pattern_key, &patproj)?;
     let result = borsh::to_vec(&(&pattern_key, &patproj))?;
 
     let mut patterns = patterns.write().unwrap();
+    let mut history = history.write().unwrap();
+    history.get_mut(&pattern_key).notify(&window, &pattern_key)?;
     patterns.insert(pattern_key, patproj);
 
     log::trace!("Pattern has been created");
@@ -72,33 +124,143 @@ pub fn create_pattern<R: tauri::Runtime>(
 #[tauri::command]
 pub fn save_pattern<R: tauri::Runtime>(
   request: tauri::ipc::Request<'_>,
+  window: tauri::WebviewWindow<R>,
   app_handle: tauri::AppHandle<R>,
   patterns: tauri::State<PatternsState>,
+  history: tauri::State<HistoryState<R>>,
+  recovery: tauri::State<RecoveryState>,
 ) -> CommandResult<()> {
   log::trace!("Saving pattern");
 
   let pattern_key = request.headers().get("patternKey").unwrap().to_str().unwrap().into();
   let file_path = request.headers().get("filePath").unwrap().to_str().unwrap().into();
+  // Whether to store the undo history in the pattern file (only supported by EMBPROJ).
+  let save_history = request
+    .headers()
+    .get("saveHistory")
+    .and_then(|value| value.to_str().ok())
+    .is_none_or(|value| value != "false");
+  // The number of previous versions of the file to keep as backups.
+  let backups = request
+    .headers()
+    .get("backupsNumber")
+    .and_then(|value| value.to_str().ok())
+    .and_then(|value| value.parse().ok())
+    .unwrap_or(0);
 
   let mut patterns = patterns.write().unwrap();
+  let mut history = history.write().unwrap();
+  let history = history.get_mut(&pattern_key);
+
   let patproj = patterns.get_mut(&pattern_key).unwrap();
-  patproj.file_path = file_path;
-  match PatternFormat::try_from(patproj.file_path.extension())? {
+  // The modification date is a part of the file itself, not a change to be undone.
+  // The pattern is stamped (and moved to the new path) only if it has been saved successfully.
+  let mut stamped = patproj.clone();
+  stamped.file_path = file_path;
+  stamped.pattern.info.modified_at = Some(unix_timestamp());
+  match PatternFormat::try_from(stamped.file_path.extension())? {
     PatternFormat::Xsd => Err(anyhow::anyhow!("The XSD format is not supported for saving.")),
-    PatternFormat::Oxs => parser::oxs::save_pattern(patproj, app_handle.package_info()),
-    PatternFormat::EmbProj => parser::embproj::save_pattern(patproj, app_handle.package_info()),
+    PatternFormat::Oxs => parser::oxs::save_pattern(&stamped, app_handle.package_info(), backups),
+    PatternFormat::EmbProj => {
+      // The pattern itself is more important than its history, so we save it even if the history can't be stored.
+      let persisted_history = if save_history {
+        history
+          .persist()
+          .inspect_err(|err| log::warn!("Failed to persist the pattern history: {err:?}"))
+          .ok()
+      } else {
+        None
+      };
+      parser::embproj::save_pattern(&stamped, persisted_history.as_ref(), app_handle.package_info(), backups)
+    }
   }?;
 
+  patproj.file_path = stamped.file_path;
+  patproj.pattern.info.modified_at = stamped.pattern.info.modified_at;
+  window.emit(
+    "pattern:update_info",
+    STANDARD.encode(borsh::to_vec(&patproj.pattern.info)?),
+  )?;
+
+  // The pattern is safely stored on the disk, so its snapshot is not needed anymore.
+  recovery::mark_persisted(&recovery, &pattern_key, patproj)?;
+  recovery::remove_snapshot(&app_recovery_dir(&app_handle)?, &pattern_key)?;
+
+  history.mark_saved();
+  history.notify(&window, &pattern_key)?;
+
   log::trace!("Pattern saved");
   Ok(())
 }
 
 #[tauri::command]
-pub fn close_pattern(request: tauri::ipc::Request<'_>, patterns: tauri::State<PatternsState>) {
+pub fn update_pattern_info<R: tauri::Runtime>(
+  request: tauri::ipc::Request<'_>,
+  window: tauri::WebviewWindow<R>,
+  history: tauri::State<HistoryState<R>>,
+  patterns: tauri::State<PatternsState>,
+) -> CommandResult<()> {
+  if let tauri::ipc::InvokeBody::Raw(data) = request.body() {
+    let pattern_key = request.headers().get("patternKey").unwrap().to_str().unwrap().into();
+    let info = borsh::from_slice(data)?;
+
+    let mut patterns = patterns.write().unwrap();
+    let action = UpdatePatternInfoAction::new(info);
+    action.perform(&window, patterns.get_mut(&pattern_key).unwrap())?;
+
+    let mut history = history.write().unwrap();
+    let history = history.get_mut(&pattern_key);
+    history.push(Box::new(action));
+    history.notify(&window, &pattern_key)?;
+
+    Ok(())
+  } else {
+    Err(anyhow::anyhow!("Invalid request body").into())
+  }
+}
+
+/// Closes the pattern.
+/// Returns `false` without closing the pattern if it has unsaved changes, unless the `force` header is set to `true`.
+#[tauri::command]
+pub fn close_pattern<R: tauri::Runtime>(
+  request: tauri::ipc::Request<'_>,
+  window: tauri::WebviewWindow<R>,
+  app_handle: tauri::AppHandle<R>,
+  patterns: tauri::State<PatternsState>,
+  history: tauri::State<HistoryState<R>>,
+  recovery: tauri::State<RecoveryState>,
+  drawing_modes: tauri::State<DrawingModesState>,
+) -> CommandResult<bool> {
   log::trace!("Closing pattern");
   let pattern_key = request.headers().get("patternKey").unwrap().to_str().unwrap().into();
-  patterns.write().unwrap().remove(&pattern_key);
+  let force = request
+    .headers()
+    .get("force")
+    .and_then(|value| value.to_str().ok())
+    .is_some_and(|value| value == "true");
+
+  let mut patterns = patterns.write().unwrap();
+  let mut history = history.write().unwrap();
+  if !force && history.get(&pattern_key).is_some_and(|history| history.is_dirty()) {
+    log::trace!("Pattern has unsaved changes");
+    return Ok(false);
+  }
+
+  patterns.remove(&pattern_key);
+  history.remove(&pattern_key);
+  drawing_modes.write().unwrap().remove(&pattern_key);
+  History::notify_closed(&window, &pattern_key)?;
+  // The pattern is closed intentionally, so there is nothing to recover.
+  recovery::forget(&app_handle, &recovery, &pattern_key)?;
+
   log::trace!("Pattern closed");
+  Ok(true)
+}
+
+#[tauri::command]
+pub fn is_pattern_dirty<R: tauri::Runtime>(pattern_key: PatternKey, history: tauri::State<HistoryState<R>>) -> bool {
+  let history = history.read().unwrap();
+  history.get(&pattern_key).is_some_and(|history| history.is_dirty())
 }
 
 #[tauri::command]
@@ -0,0 +1,65 @@
+use crate::core::history::History;
+use crate::error::CommandResult;
+use crate::recovery;
+use crate::state::{HistoryState, PatternKey, PatternsState, RecoveryState};
+use crate::utils::path::app_recovery_dir;
+
+#[cfg(test)]
+#[path = "recovery.test.rs"]
+mod tests;
+
+#[tauri::command]
+pub fn list_recoverable_patterns<R: tauri::Runtime>(
+  app_handle: tauri::AppHandle<R>,
+  patterns: tauri::State<PatternsState>,
+) -> CommandResult<Vec<u8>> {
+  let patterns = patterns.read().unwrap();
+  let snapshots = recovery::list_snapshots(&app_recovery_dir(&app_handle)?)?
+    .into_iter()
+    // The snapshots of the currently open patterns are not recoverable, they are just autosaves.
+    .filter(|snapshot| !patterns.contains_key(&snapshot.pattern_key))
+    .collect::<Vec<_>>();
+  Ok(borsh::to_vec(&snapshots)?)
+}
+
+#[tauri::command]
+pub fn restore_pattern<R: tauri::Runtime>(
+  pattern_key: PatternKey,
+  window: tauri::WebviewWindow<R>,
+  app_handle: tauri::AppHandle<R>,
+  patterns: tauri::State<PatternsState>,
+  history: tauri::State<HistoryState<R>>,
+  recovery: tauri::State<RecoveryState>,
+) -> CommandResult<Vec<u8>> {
+  log::trace!("Restoring pattern");
+
+  let mut patterns = patterns.write().unwrap();
+  if patterns.contains_key(&pattern_key) {
+    return Err(anyhow::anyhow!("The pattern is already open").into());
+  }
+
+  let patproj = recovery::read_snapshot(&app_recovery_dir(&app_handle)?, &pattern_key)?;
+  // The snapshot is kept until the restored pattern is saved.
+  // The pattern is not marked as persisted, so the autosave keeps refreshing the snapshot meanwhile.
+  recovery::mark_restored(&recovery, &pattern_key);
+
+  let result = borsh::to_vec(&(&pattern_key, &patproj))?;
+  let mut history = history.write().unwrap();
+  // The restored pattern differs from its file, so it must not be closed without a prompt.
+  let limits = history.limits();
+  history.insert(pattern_key.clone(), History::unsaved(limits));
+  history.get_mut(&pattern_key).notify(&window, &pattern_key)?;
+  patterns.insert(pattern_key, patproj);
+
+  log::trace!("Pattern restored");
+  Ok(result)
+}
+
+#[tauri::command]
+pub fn discard_recoverable_pattern<R: tauri::Runtime>(
+  pattern_key: PatternKey,
+  app_handle: tauri::AppHandle<R>,
+) -> CommandResult<()> {
+  recovery::remove_snapshot(&app_recovery_dir(&app_handle)?, &pattern_key)?;
+  Ok(())
+}
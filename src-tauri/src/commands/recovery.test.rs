@@ -0,0 +1,127 @@
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use tauri::ipc::{CallbackFn, InvokeBody};
+use tauri::test::{INVOKE_KEY, MockRuntime, get_ipc_response, mock_builder};
+use tauri::webview::InvokeRequest;
+use tauri::{Manager, WebviewUrl, WebviewWindow, WebviewWindowBuilder, generate_context};
+
+use crate::core::parser::oxs;
+use crate::core::pattern::{DrawingMode, PatternProject};
+use crate::recovery;
+use crate::state::{HistoryStateInner, PatternKey, RecoveryState};
+use crate::utils::path::app_recovery_dir;
+
+fn setup_app() -> (tauri::App<MockRuntime>, WebviewWindow<MockRuntime>) {
+  let app = mock_builder()
+    .manage(RwLock::new(HashMap::<PatternKey, PatternProject>::new()))
+    .manage(RwLock::new(HistoryStateInner::<MockRuntime>::default()))
+    .manage(RwLock::new(HashMap::<PatternKey, Option<u64>>::new()))
+    .manage(RwLock::new(HashMap::<PatternKey, DrawingMode>::new()))
+    .invoke_handler(tauri::generate_handler![
+      super::restore_pattern,
+      crate::commands::pattern::close_pattern,
+    ])
+    .build(generate_context!())
+    .unwrap();
+  let window = WebviewWindowBuilder::new(&app, "main", WebviewUrl::default())
+    .build()
+    .unwrap();
+  (app, window)
+}
+
+fn invoke(
+  window: &WebviewWindow<MockRuntime>,
+  cmd: &str,
+  body: serde_json::Value,
+  headers: &[(&'static str, &str)],
+) -> tauri::ipc::InvokeResponseBody {
+  let mut header_map = tauri::http::HeaderMap::new();
+  for &(name, value) in headers {
+    header_map.insert(name, value.parse().unwrap());
+  }
+  get_ipc_response(
+    window,
+    InvokeRequest {
+      cmd: cmd.to_string(),
+      callback: CallbackFn(0),
+      error: CallbackFn(1),
+      url: "http://tauri.localhost".parse().unwrap(),
+      body: InvokeBody::Json(body),
+      headers: header_map,
+      invoke_key: INVOKE_KEY.to_string(),
+    },
+  )
+  .unwrap()
+}
+
+fn has_snapshot(recovery_dir: &std::path::Path, pattern_key: &PatternKey) -> bool {
+  recovery::list_snapshots(recovery_dir)
+    .unwrap()
+    .iter()
+    .any(|snapshot| &snapshot.pattern_key == pattern_key)
+}
+
+#[test]
+fn restored_pattern_is_not_closed_silently() {
+  let (app, window) = setup_app();
+  let recovery_dir = app_recovery_dir(app.handle()).unwrap();
+
+  let file_path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("testdata/patterns/rainbow.oxs");
+  let patproj = oxs::parse_pattern(file_path).unwrap();
+  let pattern_key = PatternKey::from("restored_pattern_is_not_closed_silently");
+  recovery::write_snapshot(&recovery_dir, &pattern_key, &patproj, app.package_info()).unwrap();
+
+  invoke(
+    &window,
+    "restore_pattern",
+    serde_json::json!({ "patternKey": pattern_key }),
+    &[],
+  );
+
+  // The restored work only exists in the snapshot, so closing the pattern requires a confirmation.
+  let closed = invoke(
+    &window,
+    "close_pattern",
+    serde_json::json!({}),
+    &[("patternkey", pattern_key.as_ref())],
+  );
+  assert!(!closed.deserialize::<bool>().unwrap());
+  assert!(has_snapshot(&recovery_dir, &pattern_key));
+
+  // Once the user confirms, the pattern is closed and its snapshot is removed.
+  let closed = invoke(
+    &window,
+    "close_pattern",
+    serde_json::json!({}),
+    &[("patternkey", pattern_key.as_ref()), ("force", "true")],
+  );
+  assert!(closed.deserialize::<bool>().unwrap());
+  assert!(!has_snapshot(&recovery_dir, &pattern_key));
+}
+
+#[test]
+fn closed_pattern_is_not_autosaved() {
+  let (app, window) = setup_app();
+  let recovery_dir = app_recovery_dir(app.handle()).unwrap();
+
+  let file_path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("testdata/patterns/rainbow.oxs");
+  let patproj = oxs::parse_pattern(file_path).unwrap();
+  let pattern_key = PatternKey::from("closed_pattern_is_not_autosaved");
+  recovery::write_snapshot(&recovery_dir, &pattern_key, &patproj, app.package_info()).unwrap();
+
+  invoke(
+    &window,
+    "restore_pattern",
+    serde_json::json!({ "patternKey": pattern_key }),
+    &[],
+  );
+  recovery::autosave(app.handle()).unwrap();
+  assert!(has_snapshot(&recovery_dir, &pattern_key));
+
+  // The pattern may be closed while the autosave is running, after the pattern has been copied.
+  // To emulate this, the pattern is forgotten while it is still in the patterns state.
+  recovery::forget(app.handle(), &app.state::<RecoveryState>(), &pattern_key).unwrap();
+  recovery::autosave(app.handle()).unwrap();
+  assert!(!has_snapshot(&recovery_dir, &pattern_key));
+}
@@ -1,26 +1,72 @@
-use crate::core::actions::{Action, AddStitchAction, RemoveStitchAction};
+use crate::core::actions::{
+  Action, AddStitchAction, CleanupConfettiAction, CompositeAction, OptimizeLinesAction, RemoveStitchAction,
+  TransformPatternAction,
+};
+use crate::core::pattern::{ConfettiOptions, PatternTransform, Rect, Stitch};
 use crate::error::CommandResult;
-use crate::state::{HistoryState, PatternsState};
+use crate::state::{DrawingModesState, HistoryState, PatternKey, PatternsState};
 
+/// Groups the actions into a single one if there are several of them.
+fn group_actions<R: tauri::Runtime>(mut actions: Vec<Box<dyn Action<R>>>) -> Option<Box<dyn Action<R>>> {
+  match actions.len() {
+    0 => None,
+    1 => actions.pop(),
+    _ => Some(Box::new(CompositeAction::new(actions))),
+  }
+}
+
+/// Sets the drawing mode used to add and remove stitches of the pattern.
+#[tauri::command]
+pub fn set_drawing_mode(
+  request: tauri::ipc::Request<'_>,
+  drawing_modes: tauri::State<DrawingModesState>,
+) -> CommandResult<()> {
+  if let tauri::ipc::InvokeBody::Raw(data) = request.body() {
+    let pattern_key = request.headers().get("patternKey").unwrap().to_str().unwrap().into();
+    let mode = borsh::from_slice(data)?;
+    drawing_modes.write().unwrap().insert(pattern_key, mode);
+    Ok(())
+  } else {
+    Err(anyhow::anyhow!("Invalid request body").into())
+  }
+}
+
+/// Adds the stitch along with its counterparts produced by the current drawing mode.
+/// All of them are added as a single action.
 #[tauri::command]
 pub fn add_stitch<R: tauri::Runtime>(
   request: tauri::ipc::Request<'_>,
   window: tauri::WebviewWindow<R>,
   history: tauri::State<HistoryState<R>>,
   patterns: tauri::State<PatternsState>,
+  drawing_modes: tauri::State<DrawingModesState>,
 ) -> CommandResult<bool> {
   if let tauri::ipc::InvokeBody::Raw(data) = request.body() {
     let pattern_key = request.headers().get("patternKey").unwrap().to_str().unwrap().into();
-    let stitch = borsh::from_slice(data)?;
+    let stitch: Stitch = borsh::from_slice(data)?;
 
     let mut patterns = patterns.write().unwrap();
     let patproj = patterns.get_mut(&pattern_key).unwrap();
-    if !patproj.pattern.contains_stitch(&stitch) {
-      let action = AddStitchAction::new(stitch);
+    let mode = drawing_modes
+      .read()
+      .unwrap()
+      .get(&pattern_key)
+      .copied()
+      .unwrap_or_default();
+
+    let actions = mode
+      .expand(stitch, &patproj.pattern.fabric)
+      .into_iter()
+      .filter(|stitch| !patproj.pattern.contains_stitch(stitch))
+      .map(|stitch| Box::new(AddStitchAction::new(stitch)) as Box<dyn Action<R>>)
+      .collect();
+    if let Some(action) = group_actions(actions) {
       action.perform(&window, patproj)?;
 
       let mut history = history.write().unwrap();
-      history.get_mut(&pattern_key).push(Box::new(action));
+      let history = history.get_mut(&pattern_key);
+      history.push(action);
+      history.notify(&window, &pattern_key)?;
 
       Ok(true)
     } else {
@@ -31,28 +77,44 @@ pub fn add_stitch<R: tauri::Runtime>(
   }
 }
 
+/// Removes the stitch along with its counterparts produced by the current drawing mode.
+/// All of them are removed as a single action.
 #[tauri::command]
 pub fn remove_stitch<R: tauri::Runtime>(
   request: tauri::ipc::Request<'_>,
   window: tauri::WebviewWindow<R>,
   history: tauri::State<HistoryState<R>>,
   patterns: tauri::State<PatternsState>,
+  drawing_modes: tauri::State<DrawingModesState>,
 ) -> CommandResult<bool> {
   if let tauri::ipc::InvokeBody::Raw(data) = request.body() {
     let pattern_key = request.headers().get("patternKey").unwrap().to_str().unwrap().into();
-    let stitch = borsh::from_slice(data)?;
+    let stitch: Stitch = borsh::from_slice(data)?;
 
     let mut patterns = patterns.write().unwrap();
     let patproj = patterns.get_mut(&pattern_key).unwrap();
+    let mode = drawing_modes
+      .read()
+      .unwrap()
+      .get(&pattern_key)
+      .copied()
+      .unwrap_or_default();
 
     // This command may accept the stitches which doesn't contain all the properties of the stitch.
-    // So we need to get the actual stitch from the pattern.
-    if let Some(stitch) = patproj.pattern.get_stitch(&stitch) {
-      let action = RemoveStitchAction::new(stitch);
+    // So we need to get the actual stitches from the pattern.
+    let actions = mode
+      .expand(stitch, &patproj.pattern.fabric)
+      .into_iter()
+      .filter_map(|stitch| patproj.pattern.get_stitch(&stitch))
+      .map(|stitch| Box::new(RemoveStitchAction::new(stitch)) as Box<dyn Action<R>>)
+      .collect();
+    if let Some(action) = group_actions(actions) {
       action.perform(&window, patproj)?;
 
       let mut history = history.write().unwrap();
-      history.get_mut(&pattern_key).push(Box::new(action));
+      let history = history.get_mut(&pattern_key);
+      history.push(action);
+      history.notify(&window, &pattern_key)?;
 
       Ok(true)
     } else {
@@ -62,3 +124,122 @@ pub fn remove_stitch<R: tauri::Runtime>(
     Err(anyhow::anyhow!("Invalid request body").into())
   }
 }
+
+/// Rotates or mirrors the pattern area or the whole pattern.
+#[tauri::command]
+pub fn transform_pattern<R: tauri::Runtime>(
+  request: tauri::ipc::Request<'_>,
+  window: tauri::WebviewWindow<R>,
+  history: tauri::State<HistoryState<R>>,
+  patterns: tauri::State<PatternsState>,
+) -> CommandResult<()> {
+  if let tauri::ipc::InvokeBody::Raw(data) = request.body() {
+    let pattern_key = request.headers().get("patternKey").unwrap().to_str().unwrap().into();
+    let PatternTransform { transformation, area } = borsh::from_slice(data)?;
+
+    let mut patterns = patterns.write().unwrap();
+    let action = TransformPatternAction::new(transformation, area);
+    action.perform(&window, patterns.get_mut(&pattern_key).unwrap())?;
+
+    let mut history = history.write().unwrap();
+    let history = history.get_mut(&pattern_key);
+    history.push(Box::new(action));
+    history.notify(&window, &pattern_key)?;
+
+    Ok(())
+  } else {
+    Err(anyhow::anyhow!("Invalid request body").into())
+  }
+}
+
+/// Merges, deduplicates and splits the lines of the pattern.
+/// Returns the number of line segments eliminated.
+#[tauri::command]
+pub fn optimize_lines<R: tauri::Runtime>(
+  pattern_key: PatternKey,
+  window: tauri::WebviewWindow<R>,
+  history: tauri::State<HistoryState<R>>,
+  patterns: tauri::State<PatternsState>,
+) -> CommandResult<usize> {
+  let mut patterns = patterns.write().unwrap();
+  let action = OptimizeLinesAction::new();
+  action.perform(&window, patterns.get_mut(&pattern_key).unwrap())?;
+
+  // There is nothing to undo if the lines are already optimized.
+  if !action.is_noop() {
+    let mut history = history.write().unwrap();
+    let history = history.get_mut(&pattern_key);
+    history.push(Box::new(action.clone()));
+    history.notify(&window, &pattern_key)?;
+  }
+
+  Ok(action.merged())
+}
+
+/// Finds the isolated full stitches and the tiny islands of them.
+#[tauri::command]
+pub fn analyze_confetti(
+  request: tauri::ipc::Request<'_>,
+  patterns: tauri::State<PatternsState>,
+) -> CommandResult<tauri::ipc::Response> {
+  if let tauri::ipc::InvokeBody::Raw(data) = request.body() {
+    let pattern_key = request.headers().get("patternKey").unwrap().to_str().unwrap().into();
+    let options: ConfettiOptions = borsh::from_slice(data)?;
+
+    let patterns = patterns.read().unwrap();
+    let analysis = patterns.get(&pattern_key).unwrap().pattern.analyze_confetti(&options);
+    Ok(tauri::ipc::Response::new(borsh::to_vec(&analysis)?))
+  } else {
+    Err(anyhow::anyhow!("Invalid request body").into())
+  }
+}
+
+/// Recolors the confetti to the dominant palette items of their neighbours.
+/// Returns the number of recolored stitches.
+#[tauri::command]
+pub fn cleanup_confetti<R: tauri::Runtime>(
+  request: tauri::ipc::Request<'_>,
+  window: tauri::WebviewWindow<R>,
+  history: tauri::State<HistoryState<R>>,
+  patterns: tauri::State<PatternsState>,
+) -> CommandResult<usize> {
+  if let tauri::ipc::InvokeBody::Raw(data) = request.body() {
+    let pattern_key = request.headers().get("patternKey").unwrap().to_str().unwrap().into();
+    let options: ConfettiOptions = borsh::from_slice(data)?;
+
+    let mut patterns = patterns.write().unwrap();
+    let action = CleanupConfettiAction::new(options);
+    action.perform(&window, patterns.get_mut(&pattern_key).unwrap())?;
+
+    // There is nothing to undo if there is no confetti that can be recolored.
+    if action.recolored() > 0 {
+      let mut history = history.write().unwrap();
+      let history = history.get_mut(&pattern_key);
+      history.push(Box::new(action.clone()));
+      history.notify(&window, &pattern_key)?;
+    }
+
+    Ok(action.recolored())
+  } else {
+    Err(anyhow::anyhow!("Invalid request body").into())
+  }
+}
+
+/// Returns the stitches lying in the area of the pattern.
+/// It allows the frontend to load the stitches of big patterns by tiles, starting with the visible ones.
+#[tauri::command]
+pub fn get_stitches_in_region(
+  request: tauri::ipc::Request<'_>,
+  patterns: tauri::State<PatternsState>,
+) -> CommandResult<tauri::ipc::Response> {
+  if let tauri::ipc::InvokeBody::Raw(data) = request.body() {
+    let pattern_key = request.headers().get("patternKey").unwrap().to_str().unwrap().into();
+    let area: Rect = borsh::from_slice(data)?;
+
+    let patterns = patterns.read().unwrap();
+    let region = patterns.get(&pattern_key).unwrap().pattern.stitches_in_region(area);
+    Ok(tauri::ipc::Response::new(borsh::to_vec(&region)?))
+  } else {
+    Err(anyhow::anyhow!("Invalid request body").into())
+  }
+}
@@ -0,0 +1,90 @@
+use std::sync::OnceLock;
+
+use anyhow::Result;
+use borsh::{BorshDeserialize, BorshSerialize};
+use tauri::WebviewWindow;
+
+use super::{Action, ActionDescription, ActionKind, EncodedAction, emit_pattern_changed, once_lock};
+use crate::core::pattern::{PatternProject, StitchChanges};
+
+#[cfg(test)]
+#[path = "backstitch.test.rs"]
+mod tests;
+
+/// Merges, deduplicates and splits the lines of the pattern (see `Pattern::optimize_lines`).
+#[derive(Clone, BorshSerialize, BorshDeserialize)]
+pub struct OptimizeLinesAction {
+  #[borsh(serialize_with = "once_lock::serialize", deserialize_with = "once_lock::deserialize")]
+  changes: OnceLock<StitchChanges>,
+}
+
+impl OptimizeLinesAction {
+  #[allow(clippy::new_without_default)]
+  pub fn new() -> Self {
+    Self { changes: OnceLock::new() }
+  }
+
+  /// Returns the number of line segments eliminated by the optimization.
+  /// Returns `0` if the action hasn't been performed yet.
+  pub fn merged(&self) -> usize {
+    self
+      .changes
+      .get()
+      .map_or(0, |changes| changes.removed.len().saturating_sub(changes.added.len()))
+  }
+
+  /// Returns `true` if the action has been performed but hasn't changed anything.
+  pub fn is_noop(&self) -> bool {
+    self
+      .changes
+      .get()
+      .is_some_and(|changes| changes.removed.is_empty() && changes.added.is_empty())
+  }
+}
+
+impl ActionKind for OptimizeLinesAction {
+  const KIND: &str = "optimize_lines";
+}
+
+impl<R: tauri::Runtime> Action<R> for OptimizeLinesAction {
+  /// Optimizes the lines of the pattern.
+  ///
+  /// **Emits:**
+  /// - `pattern:changed`, since many lines may be changed.
+  fn perform(&self, window: &WebviewWindow<R>, patproj: &mut PatternProject) -> Result<()> {
+    let changes = patproj.pattern.optimize_lines();
+    emit_pattern_changed(window)?;
+
+    if self.changes.get().is_none() {
+      self.changes.set(changes).unwrap();
+    }
+
+    Ok(())
+  }
+
+  /// Restores the original lines.
+  ///
+  /// **Emits:**
+  /// - `pattern:changed`.
+  fn revoke(&self, window: &WebviewWindow<R>, patproj: &mut PatternProject) -> Result<()> {
+    patproj.pattern.revert_stitch_changes(self.changes.get().unwrap());
+    emit_pattern_changed(window)?;
+    Ok(())
+  }
+
+  fn size(&self) -> usize {
+    std::mem::size_of_val(self)
+      + self.changes.get().map_or(0, |changes| {
+        std::mem::size_of_val(changes.removed.as_slice()) + std::mem::size_of_val(changes.added.as_slice())
+      })
+  }
+
+  fn describe(&self) -> ActionDescription {
+    ActionDescription::new(Self::KIND, "Optimize backstitches")
+      .with_stitches(self.changes.get().map_or(0, |changes| changes.removed.len()))
+  }
+
+  fn encode(&self) -> Result<EncodedAction> {
+    EncodedAction::new(self)
+  }
+}
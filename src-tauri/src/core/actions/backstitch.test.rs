@@ -0,0 +1,69 @@
+use ordered_float::NotNan;
+use tauri::test::{MockRuntime, mock_builder};
+use tauri::{App, WebviewUrl, WebviewWindowBuilder, generate_context};
+
+use super::{Action, OptimizeLinesAction};
+use crate::core::pattern::*;
+
+fn setup_app() -> App<MockRuntime> {
+  mock_builder().build(generate_context!()).unwrap()
+}
+
+fn line(x: (f32, f32), y: (f32, f32)) -> Line {
+  Line {
+    x: (NotNan::new(x.0).unwrap(), NotNan::new(x.1).unwrap()),
+    y: (NotNan::new(y.0).unwrap(), NotNan::new(y.1).unwrap()),
+    palindex: 0,
+    kind: LineKind::Back,
+  }
+}
+
+#[test]
+fn test_optimize_lines() {
+  let app = setup_app();
+  let window = WebviewWindowBuilder::new(&app, "main", WebviewUrl::default())
+    .build()
+    .unwrap();
+
+  let mut patproj = PatternProject::default();
+  patproj.pattern.lines = Stitches::from_iter([
+    line((0.0, 1.0), (0.0, 0.0)),
+    line((1.0, 2.0), (0.0, 0.0)),
+    line((2.0, 3.0), (0.0, 0.0)),
+    line((5.0, 5.0), (5.0, 6.0)),
+  ]);
+  let original = patproj.pattern.lines.iter().copied().collect::<Vec<_>>();
+
+  let action = OptimizeLinesAction::new();
+  action.perform(&window, &mut patproj).unwrap();
+  assert_eq!(action.merged(), 2);
+  assert!(!action.is_noop());
+  assert_eq!(
+    patproj.pattern.lines.iter().copied().collect::<Vec<_>>(),
+    vec![line((0.0, 3.0), (0.0, 0.0)), line((5.0, 5.0), (5.0, 6.0))]
+  );
+
+  action.revoke(&window, &mut patproj).unwrap();
+  assert_eq!(patproj.pattern.lines.iter().copied().collect::<Vec<_>>(), original);
+
+  // Redoing the action gives the same result.
+  action.perform(&window, &mut patproj).unwrap();
+  assert_eq!(patproj.pattern.lines.iter().count(), 2);
+  assert_eq!(action.merged(), 2);
+}
+
+#[test]
+fn test_optimize_optimized_lines() {
+  let app = setup_app();
+  let window = WebviewWindowBuilder::new(&app, "main", WebviewUrl::default())
+    .build()
+    .unwrap();
+
+  let mut patproj = PatternProject::default();
+  patproj.pattern.lines = Stitches::from_iter([line((0.0, 3.0), (0.0, 0.0))]);
+
+  let action = OptimizeLinesAction::new();
+  action.perform(&window, &mut patproj).unwrap();
+  assert!(action.is_noop());
+  assert_eq!(action.merged(), 0);
+}
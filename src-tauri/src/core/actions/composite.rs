@@ -0,0 +1,106 @@
+use anyhow::Result;
+use tauri::WebviewWindow;
+
+use super::{Action, ActionDescription, ActionKind, ActionRegistry, EncodedAction};
+use crate::core::pattern::PatternProject;
+
+#[cfg(test)]
+#[path = "composite.test.rs"]
+mod tests;
+
+/// A group of actions that are performed and revoked as a single one.
+#[derive(Clone)]
+pub struct CompositeAction<R: tauri::Runtime> {
+  actions: Vec<Box<dyn Action<R>>>,
+}
+
+impl<R: tauri::Runtime> CompositeAction<R> {
+  pub fn new(actions: Vec<Box<dyn Action<R>>>) -> Self {
+    Self { actions }
+  }
+
+  pub fn len(&self) -> usize {
+    self.actions.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.actions.is_empty()
+  }
+
+  /// Rebuilds the composite action and all the grouped actions from their serialized form.
+  pub fn decode(registry: &ActionRegistry<R>, data: &[u8]) -> Result<Box<dyn Action<R>>> {
+    let actions = borsh::from_slice::<Vec<EncodedAction>>(data)?
+      .iter()
+      .map(|action| registry.decode(action))
+      .collect::<Result<Vec<_>>>()?;
+    Ok(Box::new(Self::new(actions)))
+  }
+}
+
+impl<R: tauri::Runtime> ActionKind for CompositeAction<R> {
+  const KIND: &str = "composite";
+}
+
+impl<R: tauri::Runtime> Action<R> for CompositeAction<R> {
+  /// Performs all the grouped actions in the order they were added.
+  /// If one of them fails, the already performed ones are revoked, so the group is applied all or nothing.
+  ///
+  /// **Emits:**
+  /// - The events of the grouped actions.
+  fn perform(&self, window: &WebviewWindow<R>, patproj: &mut PatternProject) -> Result<()> {
+    for (performed, action) in self.actions.iter().enumerate() {
+      if let Err(error) = action.perform(window, patproj) {
+        for action in self.actions[..performed].iter().rev() {
+          action.revoke(window, patproj)?;
+        }
+        return Err(error);
+      }
+    }
+    Ok(())
+  }
+
+  /// Revokes all the grouped actions in the reverse order.
+  ///
+  /// **Emits:**
+  /// - The events of the grouped actions.
+  fn revoke(&self, window: &WebviewWindow<R>, patproj: &mut PatternProject) -> Result<()> {
+    for action in self.actions.iter().rev() {
+      action.revoke(window, patproj)?;
+    }
+    Ok(())
+  }
+
+  /// Describes the group as a whole, summing up the counts of the grouped actions.
+  /// A group of a single action type is labeled after it.
+  fn describe(&self) -> ActionDescription {
+    let descriptions = self.actions.iter().map(|action| action.describe()).collect::<Vec<_>>();
+    let label = match descriptions.first() {
+      Some(first) if descriptions.iter().all(|description| description.kind == first.kind) => {
+        format!("{} ({})", first.label, descriptions.len())
+      }
+      _ => format!("{} actions", descriptions.len()),
+    };
+    let mut description = ActionDescription::new(Self::KIND, label);
+    for child in descriptions {
+      description.stitches += child.stitches;
+      description.palette_items += child.palette_items;
+    }
+    description
+  }
+
+  fn encode(&self) -> Result<EncodedAction> {
+    let actions = self
+      .actions
+      .iter()
+      .map(|action| action.encode())
+      .collect::<Result<Vec<_>>>()?;
+    Ok(EncodedAction {
+      kind: Self::KIND.to_string(),
+      data: borsh::to_vec(&actions)?,
+    })
+  }
+
+  fn size(&self) -> usize {
+    std::mem::size_of_val(self) + self.actions.iter().map(|action| action.size()).sum::<usize>()
+  }
+}
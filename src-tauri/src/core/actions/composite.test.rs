@@ -0,0 +1,69 @@
+use tauri::test::{MockRuntime, mock_builder};
+use tauri::{App, WebviewUrl, WebviewWindowBuilder, generate_context};
+
+use super::{Action, CompositeAction};
+use crate::core::actions::{UpdateGridPropertiesAction, UpdatePaletteItemSymbolsAction};
+use crate::core::pattern::PatternProject;
+use crate::core::pattern::display::{Grid, Symbols};
+
+fn setup_app() -> App<MockRuntime> {
+  mock_builder().build(generate_context!()).unwrap()
+}
+
+#[test]
+fn test_composite_action() {
+  let app = setup_app();
+  let window = WebviewWindowBuilder::new(&app, "main", WebviewUrl::default())
+    .build()
+    .unwrap();
+
+  let mut patproj = PatternProject::default();
+  let first_grid = Grid {
+    major_lines_interval: 5,
+    ..Grid::default()
+  };
+  let second_grid = Grid {
+    major_lines_interval: 15,
+    ..Grid::default()
+  };
+
+  let actions: Vec<Box<dyn Action<MockRuntime>>> = vec![
+    Box::new(UpdateGridPropertiesAction::new(first_grid)),
+    Box::new(UpdateGridPropertiesAction::new(second_grid.clone())),
+  ];
+  let action = CompositeAction::new(actions);
+
+  action.perform(&window, &mut patproj).unwrap();
+  assert_eq!(patproj.display_settings.grid, second_grid);
+
+  // The actions are revoked in the reverse order, so the initial state is restored.
+  action.revoke(&window, &mut patproj).unwrap();
+  assert_eq!(patproj.display_settings.grid, Grid::default());
+
+  action.perform(&window, &mut patproj).unwrap();
+  assert_eq!(patproj.display_settings.grid, second_grid);
+}
+
+#[test]
+fn test_composite_action_failure_revokes_performed_actions() {
+  let app = setup_app();
+  let window = WebviewWindowBuilder::new(&app, "main", WebviewUrl::default())
+    .build()
+    .unwrap();
+
+  let mut patproj = PatternProject::default();
+  let grid = Grid {
+    major_lines_interval: 5,
+    ..Grid::default()
+  };
+
+  let actions: Vec<Box<dyn Action<MockRuntime>>> = vec![
+    Box::new(UpdateGridPropertiesAction::new(grid)),
+    // The palette is empty, so this action fails.
+    Box::new(UpdatePaletteItemSymbolsAction::new(0, Symbols::default())),
+  ];
+  let action = CompositeAction::new(actions);
+
+  assert!(action.perform(&window, &mut patproj).is_err());
+  assert_eq!(patproj.display_settings.grid, Grid::default());
+}
@@ -0,0 +1,90 @@
+use std::sync::OnceLock;
+
+use anyhow::Result;
+use borsh::{BorshDeserialize, BorshSerialize};
+use tauri::WebviewWindow;
+
+use super::{Action, ActionDescription, ActionKind, EncodedAction, emit_pattern_changed, once_lock};
+use crate::core::pattern::{ConfettiOptions, FullStitch, PatternProject};
+
+#[cfg(test)]
+#[path = "confetti.test.rs"]
+mod tests;
+
+/// Recolors the confetti to the dominant palette items of their neighbours (see `Pattern::recolor_confetti`).
+#[derive(Clone, BorshSerialize, BorshDeserialize)]
+pub struct CleanupConfettiAction {
+  options: ConfettiOptions,
+  /// The recolored stitches with their original palette items.
+  #[borsh(serialize_with = "once_lock::serialize", deserialize_with = "once_lock::deserialize")]
+  originals: OnceLock<Vec<FullStitch>>,
+}
+
+impl CleanupConfettiAction {
+  pub fn new(options: ConfettiOptions) -> Self {
+    Self {
+      options,
+      originals: OnceLock::new(),
+    }
+  }
+
+  /// Returns the number of recolored stitches.
+  /// Returns `0` if the action hasn't been performed yet.
+  pub fn recolored(&self) -> usize {
+    self.originals.get().map_or(0, |originals| originals.len())
+  }
+}
+
+impl ActionKind for CleanupConfettiAction {
+  const KIND: &str = "cleanup_confetti";
+}
+
+impl<R: tauri::Runtime> Action<R> for CleanupConfettiAction {
+  /// Recolors the confetti.
+  ///
+  /// **Emits:**
+  /// - `pattern:changed`, since many stitches may be changed.
+  fn perform(&self, window: &WebviewWindow<R>, patproj: &mut PatternProject) -> Result<()> {
+    let originals = patproj
+      .pattern
+      .recolor_confetti(&self.options)
+      .into_iter()
+      .filter_map(|stitch| patproj.pattern.fullstitches.insert(stitch))
+      .collect();
+    emit_pattern_changed(window)?;
+
+    if self.originals.get().is_none() {
+      self.originals.set(originals).unwrap();
+    }
+
+    Ok(())
+  }
+
+  /// Restores the original palette items of the recolored stitches.
+  ///
+  /// **Emits:**
+  /// - `pattern:changed`.
+  fn revoke(&self, window: &WebviewWindow<R>, patproj: &mut PatternProject) -> Result<()> {
+    for &stitch in self.originals.get().unwrap() {
+      patproj.pattern.fullstitches.insert(stitch);
+    }
+    emit_pattern_changed(window)?;
+    Ok(())
+  }
+
+  fn size(&self) -> usize {
+    std::mem::size_of_val(self)
+      + self
+        .originals
+        .get()
+        .map_or(0, |originals| std::mem::size_of_val(originals.as_slice()))
+  }
+
+  fn describe(&self) -> ActionDescription {
+    ActionDescription::new(Self::KIND, "Clean up confetti").with_stitches(self.recolored())
+  }
+
+  fn encode(&self) -> Result<EncodedAction> {
+    EncodedAction::new(self)
+  }
+}
@@ -0,0 +1,51 @@
+use ordered_float::NotNan;
+use tauri::test::{MockRuntime, mock_builder};
+use tauri::{App, WebviewUrl, WebviewWindowBuilder, generate_context};
+
+use super::{Action, CleanupConfettiAction};
+use crate::core::pattern::*;
+
+fn setup_app() -> App<MockRuntime> {
+  mock_builder().build(generate_context!()).unwrap()
+}
+
+fn fullstitch(x: f32, y: f32, palindex: u8) -> FullStitch {
+  FullStitch {
+    x: NotNan::new(x).unwrap(),
+    y: NotNan::new(y).unwrap(),
+    palindex,
+    kind: FullStitchKind::Full,
+  }
+}
+
+#[test]
+fn test_cleanup_confetti() {
+  let app = setup_app();
+  let window = WebviewWindowBuilder::new(&app, "main", WebviewUrl::default())
+    .build()
+    .unwrap();
+
+  let mut patproj = PatternProject::default();
+  for y in 0..3 {
+    for x in 0..3 {
+      let palindex = if (x, y) == (1, 1) { 1 } else { 0 };
+      patproj
+        .pattern
+        .fullstitches
+        .insert(fullstitch(x as f32, y as f32, palindex));
+    }
+  }
+  let original = patproj.pattern.fullstitches.iter().copied().collect::<Vec<_>>();
+
+  let action = CleanupConfettiAction::new(ConfettiOptions::default());
+  action.perform(&window, &mut patproj).unwrap();
+  assert_eq!(action.recolored(), 1);
+  assert!(patproj.pattern.fullstitches.contains(&fullstitch(1.0, 1.0, 0)));
+  assert_eq!(patproj.pattern.fullstitches.count_by_palindex(1), 0);
+
+  action.revoke(&window, &mut patproj).unwrap();
+  assert_eq!(
+    patproj.pattern.fullstitches.iter().copied().collect::<Vec<_>>(),
+    original
+  );
+}
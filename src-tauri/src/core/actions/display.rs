@@ -1,19 +1,23 @@
 use std::sync::OnceLock;
 
 use anyhow::Result;
-use tauri::{Emitter, WebviewWindow};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use borsh::{BorshDeserialize, BorshSerialize};
+use tauri::WebviewWindow;
 
-use super::Action;
+use super::{Action, ActionDescription, ActionKind, EncodedAction, emit, once_lock};
 use crate::PatternProject;
-use crate::display::DisplayMode;
+use crate::display::{DisplayMode, DisplaySetting};
 
 #[cfg(test)]
 #[path = "display.test.rs"]
 mod tests;
 
-#[derive(Clone)]
+#[derive(Clone, BorshSerialize, BorshDeserialize)]
 pub struct SetDisplayModeAction {
   mode: DisplayMode,
+  #[borsh(serialize_with = "once_lock::serialize", deserialize_with = "once_lock::deserialize")]
   old_mode: OnceLock<DisplayMode>,
 }
 
@@ -23,13 +27,17 @@ impl SetDisplayModeAction {
   }
 }
 
+impl ActionKind for SetDisplayModeAction {
+  const KIND: &str = "set_display_mode";
+}
+
 impl<R: tauri::Runtime> Action<R> for SetDisplayModeAction {
   /// Updates the display mode.
   ///
   /// **Emits:**
   /// - `display:set_mode` with the updated display mode.
   fn perform(&self, window: &WebviewWindow<R>, patproj: &mut PatternProject) -> Result<()> {
-    window.emit("display:set_mode", self.mode.to_string())?;
+    emit(window, "display:set_mode", self.mode.to_string())?;
     let old_mode = std::mem::replace(&mut patproj.display_settings.display_mode, self.mode.clone());
     if self.old_mode.get().is_none() {
       self.old_mode.set(old_mode).unwrap();
@@ -43,41 +51,143 @@ impl<R: tauri::Runtime> Action<R> for SetDisplayModeAction {
   /// - `display:set_mode` with the previous display mode.
   fn revoke(&self, window: &WebviewWindow<R>, patproj: &mut PatternProject) -> Result<()> {
     let old_mode = self.old_mode.get().unwrap();
-    window.emit("display:set_mode", old_mode.to_string())?;
+    emit(window, "display:set_mode", old_mode.to_string())?;
     patproj.display_settings.display_mode = old_mode.clone();
     Ok(())
   }
+
+  fn describe(&self) -> ActionDescription {
+    ActionDescription::new(Self::KIND, "Change display mode")
+  }
+
+  fn encode(&self) -> Result<EncodedAction> {
+    EncodedAction::new(self)
+  }
 }
 
-#[derive(Clone)]
+#[derive(Clone, BorshSerialize, BorshDeserialize)]
 pub struct ShowSymbolsAction {
   value: bool,
+  #[borsh(serialize_with = "once_lock::serialize", deserialize_with = "once_lock::deserialize")]
+  old_value: OnceLock<bool>,
 }
 
 impl ShowSymbolsAction {
   pub fn new(value: bool) -> Self {
-    Self { value }
+    Self {
+      value,
+      old_value: OnceLock::new(),
+    }
   }
 }
 
+impl ActionKind for ShowSymbolsAction {
+  const KIND: &str = "show_symbols";
+}
+
 impl<R: tauri::Runtime> Action<R> for ShowSymbolsAction {
   /// Updates the display setting for showing symbols.
   ///
   /// **Emits:**
   /// - `display:show_symbols` with the new value.
   fn perform(&self, window: &WebviewWindow<R>, patproj: &mut PatternProject) -> Result<()> {
-    patproj.display_settings.show_symbols = self.value;
-    window.emit("display:show_symbols", self.value)?;
+    let old_value = std::mem::replace(&mut patproj.display_settings.show_symbols, self.value);
+    emit(window, "display:show_symbols", self.value)?;
+    if self.old_value.get().is_none() {
+      self.old_value.set(old_value).unwrap();
+    }
     Ok(())
   }
 
-  /// Toggles the display setting for showing symbols.
+  /// Restores the previous value of the display setting for showing symbols.
   ///
   /// **Emits:**
-  /// - `display:show_symbols` with the new value.
+  /// - `display:show_symbols` with the previous value.
+  fn revoke(&self, window: &WebviewWindow<R>, patproj: &mut PatternProject) -> Result<()> {
+    let old_value = *self.old_value.get().unwrap();
+    patproj.display_settings.show_symbols = old_value;
+    emit(window, "display:show_symbols", old_value)?;
+    Ok(())
+  }
+
+  fn describe(&self) -> ActionDescription {
+    ActionDescription::new(Self::KIND, if self.value { "Show symbols" } else { "Hide symbols" })
+  }
+
+  fn encode(&self) -> Result<EncodedAction> {
+    EncodedAction::new(self)
+  }
+}
+
+#[derive(Clone, BorshSerialize, BorshDeserialize)]
+pub struct UpdateDisplaySettingsAction {
+  setting: DisplaySetting,
+  #[borsh(serialize_with = "once_lock::serialize", deserialize_with = "once_lock::deserialize")]
+  old_setting: OnceLock<DisplaySetting>,
+}
+
+impl UpdateDisplaySettingsAction {
+  pub fn new(setting: DisplaySetting) -> Self {
+    Self {
+      setting,
+      old_setting: OnceLock::new(),
+    }
+  }
+}
+
+impl ActionKind for UpdateDisplaySettingsAction {
+  const KIND: &str = "update_display_settings";
+}
+
+impl<R: tauri::Runtime> Action<R> for UpdateDisplaySettingsAction {
+  /// Updates a single display setting.
+  ///
+  /// **Emits:**
+  /// - `display:update_setting` with the new value of the setting.
+  fn perform(&self, window: &WebviewWindow<R>, patproj: &mut PatternProject) -> Result<()> {
+    let old_setting = self.setting.apply(&mut patproj.display_settings);
+    emit(
+      window,
+      "display:update_setting",
+      STANDARD.encode(borsh::to_vec(&self.setting)?),
+    )?;
+    if self.old_setting.get().is_none() {
+      self.old_setting.set(old_setting).unwrap();
+    }
+    Ok(())
+  }
+
+  /// Restores the previous value of the display setting.
+  ///
+  /// **Emits:**
+  /// - `display:update_setting` with the previous value of the setting.
   fn revoke(&self, window: &WebviewWindow<R>, patproj: &mut PatternProject) -> Result<()> {
-    patproj.display_settings.show_symbols = !self.value;
-    window.emit("display:show_symbols", !self.value)?;
+    let old_setting = self.old_setting.get().unwrap();
+    old_setting.apply(&mut patproj.display_settings);
+    emit(
+      window,
+      "display:update_setting",
+      STANDARD.encode(borsh::to_vec(old_setting)?),
+    )?;
     Ok(())
   }
+
+  /// Absorbs the subsequent update of the same setting, e.g., while changing the zoom.
+  fn coalesce(&mut self, next: &dyn Action<R>) -> bool {
+    match next.as_any().downcast_ref::<Self>() {
+      Some(next) if next.setting.is_same_setting(&self.setting) => {
+        self.setting = next.setting.clone();
+        true
+      }
+      _ => false,
+    }
+  }
+
+  fn describe(&self) -> ActionDescription {
+    ActionDescription::new(Self::KIND, "Update display settings")
+  }
+
+  fn encode(&self) -> Result<EncodedAction> {
+    EncodedAction::new(self)
+  }
 }
@@ -1,9 +1,11 @@
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
 use tauri::test::{MockRuntime, mock_builder};
 use tauri::{App, Listener, WebviewUrl, WebviewWindowBuilder, generate_context};
 
-use super::{Action, SetDisplayModeAction, ShowSymbolsAction};
+use super::{Action, SetDisplayModeAction, ShowSymbolsAction, UpdateDisplaySettingsAction};
 use crate::PatternProject;
-use crate::display::DisplayMode;
+use crate::display::{DisplayMode, DisplaySetting, StitchOutline};
 
 fn setup_app() -> App<MockRuntime> {
   mock_builder().build(generate_context!()).unwrap()
@@ -86,3 +88,92 @@ fn test_show_symbols() {
     window.unlisten(event_id);
   }
 }
+
+#[test]
+fn test_show_symbols_restores_previous_value() {
+  let app = setup_app();
+  let window = WebviewWindowBuilder::new(&app, "main", WebviewUrl::default())
+    .build()
+    .unwrap();
+
+  let mut patproj = PatternProject::default();
+  let initial_value = patproj.display_settings.show_symbols;
+
+  // Setting the same value must not toggle it on revoking.
+  let action = ShowSymbolsAction::new(initial_value);
+  action.perform(&window, &mut patproj).unwrap();
+  action.revoke(&window, &mut patproj).unwrap();
+  assert_eq!(patproj.display_settings.show_symbols, initial_value);
+}
+
+#[test]
+fn test_update_display_settings() {
+  let app = setup_app();
+  let window = WebviewWindowBuilder::new(&app, "main", WebviewUrl::default())
+    .build()
+    .unwrap();
+
+  let mut patproj = PatternProject::default();
+  let old_outline = patproj.display_settings.stitch_outline.clone();
+  let outline = StitchOutline {
+    color: Some(String::from("FF0000")),
+    ..StitchOutline::default()
+  };
+  let action = UpdateDisplaySettingsAction::new(DisplaySetting::StitchOutline(outline.clone()));
+
+  // Test executing the command.
+  {
+    let expected = DisplaySetting::StitchOutline(outline.clone());
+    let event_id = window.listen("display:update_setting", move |e| {
+      let base64: &str = serde_json::from_str(e.payload()).unwrap();
+      let setting: DisplaySetting = borsh::from_slice(&STANDARD.decode(base64).unwrap()).unwrap();
+      assert_eq!(setting, expected);
+    });
+
+    action.perform(&window, &mut patproj).unwrap();
+    assert_eq!(patproj.display_settings.stitch_outline, outline);
+    window.unlisten(event_id);
+  }
+
+  // Test revoking the command.
+  {
+    let expected = DisplaySetting::StitchOutline(old_outline.clone());
+    let event_id = window.listen("display:update_setting", move |e| {
+      let base64: &str = serde_json::from_str(e.payload()).unwrap();
+      let setting: DisplaySetting = borsh::from_slice(&STANDARD.decode(base64).unwrap()).unwrap();
+      assert_eq!(setting, expected);
+    });
+
+    action.revoke(&window, &mut patproj).unwrap();
+    assert_eq!(patproj.display_settings.stitch_outline, old_outline);
+    window.unlisten(event_id);
+  }
+}
+
+#[test]
+fn test_coalesce_display_settings() {
+  let app = setup_app();
+  let window = WebviewWindowBuilder::new(&app, "main", WebviewUrl::default())
+    .build()
+    .unwrap();
+
+  let mut patproj = PatternProject::default();
+  let initial_zoom = patproj.display_settings.zoom;
+
+  let mut action = UpdateDisplaySettingsAction::new(DisplaySetting::Zoom(150));
+  action.perform(&window, &mut patproj).unwrap();
+
+  let next = UpdateDisplaySettingsAction::new(DisplaySetting::Zoom(200));
+  next.perform(&window, &mut patproj).unwrap();
+  assert!(Action::<MockRuntime>::coalesce(&mut action, &next));
+
+  // Updates of different settings are not coalesced.
+  let other = UpdateDisplaySettingsAction::new(DisplaySetting::ShowGrid(false));
+  assert!(!Action::<MockRuntime>::coalesce(&mut action, &other));
+
+  action.revoke(&window, &mut patproj).unwrap();
+  assert_eq!(patproj.display_settings.zoom, initial_zoom);
+
+  action.perform(&window, &mut patproj).unwrap();
+  assert_eq!(patproj.display_settings.zoom, 200);
+}
@@ -3,20 +3,23 @@ use std::sync::OnceLock;
 use anyhow::Result;
 use base64::Engine;
 use base64::engine::general_purpose::STANDARD;
-use tauri::{Emitter, WebviewWindow};
+use borsh::{BorshDeserialize, BorshSerialize};
+use tauri::WebviewWindow;
 
-use super::Action;
+use super::{Action, ActionDescription, ActionKind, EncodedAction, emit, emit_pattern_changed, once_lock};
 use crate::Stitch;
-use crate::core::pattern::{Fabric, PatternProject};
+use crate::core::pattern::{Fabric, PatternProject, Rect, SpecialStitch};
 
 #[cfg(test)]
 #[path = "fabric.test.rs"]
 mod tests;
 
-#[derive(Clone)]
+#[derive(Clone, BorshSerialize, BorshDeserialize)]
 pub struct UpdateFabricPropertiesAction {
   fabric: Fabric,
+  #[borsh(serialize_with = "once_lock::serialize", deserialize_with = "once_lock::deserialize")]
   old_fabric: OnceLock<Fabric>,
+  #[borsh(serialize_with = "once_lock::serialize", deserialize_with = "once_lock::deserialize")]
   extra_stitches: OnceLock<Vec<Stitch>>,
 }
 
@@ -30,6 +33,10 @@ impl UpdateFabricPropertiesAction {
   }
 }
 
+impl ActionKind for UpdateFabricPropertiesAction {
+  const KIND: &str = "update_fabric_properties";
+}
+
 impl<R: tauri::Runtime> Action<R> for UpdateFabricPropertiesAction {
   /// Updates the fabric properties.
   ///
@@ -38,13 +45,17 @@ impl<R: tauri::Runtime> Action<R> for UpdateFabricPropertiesAction {
   /// - `stitches:remove_many` with the stitches that are outside the new fabric bounds.
   fn perform(&self, window: &WebviewWindow<R>, patproj: &mut PatternProject) -> Result<()> {
     let old_fabric = std::mem::replace(&mut patproj.pattern.fabric, self.fabric.clone());
-    window.emit("fabric:update", STANDARD.encode(borsh::to_vec(&self.fabric)?))?;
+    emit(window, "fabric:update", STANDARD.encode(borsh::to_vec(&self.fabric)?))?;
 
     if self.fabric.width < old_fabric.width || self.fabric.height < old_fabric.height {
       let extra_stitches = patproj
         .pattern
         .remove_stitches_outside_bounds(0, 0, self.fabric.width, self.fabric.height);
-      window.emit("stitches:remove_many", STANDARD.encode(borsh::to_vec(&extra_stitches)?))?;
+      emit(
+        window,
+        "stitches:remove_many",
+        STANDARD.encode(borsh::to_vec(&extra_stitches)?),
+      )?;
       if self.extra_stitches.get().is_none() {
         self.extra_stitches.set(extra_stitches).unwrap();
       }
@@ -65,13 +76,189 @@ impl<R: tauri::Runtime> Action<R> for UpdateFabricPropertiesAction {
   fn revoke(&self, window: &WebviewWindow<R>, patproj: &mut PatternProject) -> Result<()> {
     let old_fabric = self.old_fabric.get().unwrap();
     patproj.pattern.fabric = old_fabric.clone();
-    window.emit("fabric:update", STANDARD.encode(borsh::to_vec(old_fabric)?))?;
+    emit(window, "fabric:update", STANDARD.encode(borsh::to_vec(old_fabric)?))?;
 
     if let Some(extra_stitches) = self.extra_stitches.get() {
       patproj.pattern.add_stitches(extra_stitches.clone());
-      window.emit("stitches:add_many", STANDARD.encode(borsh::to_vec(extra_stitches)?))?;
+      emit(
+        window,
+        "stitches:add_many",
+        STANDARD.encode(borsh::to_vec(extra_stitches)?),
+      )?;
+    }
+
+    Ok(())
+  }
+
+  fn size(&self) -> usize {
+    std::mem::size_of_val(self)
+      + self
+        .extra_stitches
+        .get()
+        .map_or(0, |stitches| std::mem::size_of_val(stitches.as_slice()))
+  }
+
+  fn describe(&self) -> ActionDescription {
+    ActionDescription::new(Self::KIND, "Update fabric")
+      .with_stitches(self.extra_stitches.get().map_or(0, |stitches| stitches.len()))
+  }
+
+  fn encode(&self) -> Result<EncodedAction> {
+    EncodedAction::new(self)
+  }
+}
+
+/// Resizes the canvas (fabric), moving the pattern content by the given offset.
+#[derive(Clone, BorshSerialize, BorshDeserialize)]
+pub struct ResizeCanvasAction {
+  width: u16,
+  height: u16,
+  offset: (i32, i32),
+  #[borsh(serialize_with = "once_lock::serialize", deserialize_with = "once_lock::deserialize")]
+  old_size: OnceLock<(u16, u16)>,
+  /// The stitches that are outside the new canvas, in the coordinates of the new canvas.
+  #[borsh(serialize_with = "once_lock::serialize", deserialize_with = "once_lock::deserialize")]
+  extra_stitches: OnceLock<(Vec<Stitch>, Vec<SpecialStitch>)>,
+}
+
+impl ResizeCanvasAction {
+  pub fn new(width: u16, height: u16, offset: (i32, i32)) -> Self {
+    Self {
+      width,
+      height,
+      offset,
+      old_size: OnceLock::new(),
+      extra_stitches: OnceLock::new(),
+    }
+  }
+}
+
+impl ActionKind for ResizeCanvasAction {
+  const KIND: &str = "resize_canvas";
+}
+
+impl<R: tauri::Runtime> Action<R> for ResizeCanvasAction {
+  /// Resizes the canvas and moves all the stitches by the offset.
+  /// The stitches that are outside the new canvas are removed.
+  ///
+  /// **Emits:**
+  /// - `pattern:changed`, since every stitch may be moved.
+  fn perform(&self, window: &WebviewWindow<R>, patproj: &mut PatternProject) -> Result<()> {
+    let pattern = &mut patproj.pattern;
+    let old_size = (pattern.fabric.width, pattern.fabric.height);
+
+    pattern.translate_stitches(self.offset.0, self.offset.1);
+    let extra_stitches = pattern.remove_stitches_outside_bounds(0, 0, self.width, self.height);
+    let extra_specialstitches = pattern
+      .specialstitches
+      .remove_stitches_outside_bounds(0, 0, self.width, self.height);
+    pattern.fabric.width = self.width;
+    pattern.fabric.height = self.height;
+
+    emit_pattern_changed(window)?;
+
+    if self.old_size.get().is_none() {
+      self.old_size.set(old_size).unwrap();
+    }
+    if self.extra_stitches.get().is_none() {
+      self
+        .extra_stitches
+        .set((extra_stitches, extra_specialstitches))
+        .unwrap();
+    }
+
+    Ok(())
+  }
+
+  /// Restores the previous canvas size and moves the stitches back.
+  ///
+  /// **Emits:**
+  /// - `pattern:changed`.
+  fn revoke(&self, window: &WebviewWindow<R>, patproj: &mut PatternProject) -> Result<()> {
+    let pattern = &mut patproj.pattern;
+    let &(width, height) = self.old_size.get().unwrap();
+    let (extra_stitches, extra_specialstitches) = self.extra_stitches.get().unwrap();
+
+    pattern.add_stitches(extra_stitches.clone());
+    for &specialstitch in extra_specialstitches {
+      pattern.specialstitches.insert(specialstitch);
     }
+    pattern.translate_stitches(-self.offset.0, -self.offset.1);
+    pattern.fabric.width = width;
+    pattern.fabric.height = height;
+
+    emit_pattern_changed(window)?;
 
     Ok(())
   }
+
+  fn size(&self) -> usize {
+    std::mem::size_of_val(self)
+      + self.extra_stitches.get().map_or(0, |(stitches, specialstitches)| {
+        std::mem::size_of_val(stitches.as_slice()) + std::mem::size_of_val(specialstitches.as_slice())
+      })
+  }
+
+  fn describe(&self) -> ActionDescription {
+    ActionDescription::new(Self::KIND, "Resize canvas").with_stitches(
+      self
+        .extra_stitches
+        .get()
+        .map_or(0, |(stitches, specialstitches)| stitches.len() + specialstitches.len()),
+    )
+  }
+
+  fn encode(&self) -> Result<EncodedAction> {
+    EncodedAction::new(self)
+  }
+}
+
+/// Crops the canvas to the given area, keeping the margin around it.
+/// It is a canvas resize that moves the area to the top-left corner, but it is described separately in the history.
+#[derive(Clone, BorshSerialize, BorshDeserialize)]
+pub struct CropCanvasAction(ResizeCanvasAction);
+
+impl CropCanvasAction {
+  pub fn new(area: Rect, margin: u16) -> Self {
+    Self(ResizeCanvasAction::new(
+      area.width.saturating_add(margin.saturating_mul(2)),
+      area.height.saturating_add(margin.saturating_mul(2)),
+      (margin as i32 - area.x as i32, margin as i32 - area.y as i32),
+    ))
+  }
+}
+
+impl ActionKind for CropCanvasAction {
+  const KIND: &str = "crop_canvas";
+}
+
+impl<R: tauri::Runtime> Action<R> for CropCanvasAction {
+  /// Crops the canvas, removing the stitches outside the area and its margin.
+  ///
+  /// **Emits:**
+  /// - `pattern:changed`.
+  fn perform(&self, window: &WebviewWindow<R>, patproj: &mut PatternProject) -> Result<()> {
+    Action::<R>::perform(&self.0, window, patproj)
+  }
+
+  /// Restores the previous canvas and the removed stitches.
+  ///
+  /// **Emits:**
+  /// - `pattern:changed`.
+  fn revoke(&self, window: &WebviewWindow<R>, patproj: &mut PatternProject) -> Result<()> {
+    Action::<R>::revoke(&self.0, window, patproj)
+  }
+
+  fn size(&self) -> usize {
+    Action::<R>::size(&self.0)
+  }
+
+  fn describe(&self) -> ActionDescription {
+    let ActionDescription { stitches, .. } = Action::<R>::describe(&self.0);
+    ActionDescription::new(Self::KIND, "Crop canvas").with_stitches(stitches as usize)
+  }
+
+  fn encode(&self) -> Result<EncodedAction> {
+    EncodedAction::new(self)
+  }
 }
@@ -1,10 +1,11 @@
 use base64::Engine;
 use base64::engine::general_purpose::STANDARD;
+use ordered_float::NotNan;
 use tauri::test::{MockRuntime, mock_builder};
 use tauri::{App, Listener, WebviewUrl, WebviewWindowBuilder, generate_context};
 
-use super::{Action, UpdateFabricPropertiesAction};
-use crate::core::pattern::{Fabric, PatternProject};
+use super::{Action, CropCanvasAction, ResizeCanvasAction, UpdateFabricPropertiesAction};
+use crate::core::pattern::*;
 
 fn setup_app() -> App<MockRuntime> {
   mock_builder().build(generate_context!()).unwrap()
@@ -51,3 +52,199 @@ fn test_update_fabric() {
     action.revoke(&window, &mut patproj).unwrap();
   }
 }
+
+fn coord(value: f32) -> Coord {
+  NotNan::new(value).unwrap()
+}
+
+fn create_pattern_project() -> PatternProject {
+  let mut patproj = PatternProject::default();
+  patproj.pattern.fabric.width = 10;
+  patproj.pattern.fabric.height = 10;
+  patproj.pattern.fullstitches.insert(FullStitch {
+    x: coord(0.0),
+    y: coord(0.0),
+    palindex: 0,
+    kind: FullStitchKind::Full,
+  });
+  patproj.pattern.partstitches.insert(PartStitch {
+    x: coord(9.0),
+    y: coord(9.0),
+    palindex: 0,
+    direction: PartStitchDirection::Forward,
+    kind: PartStitchKind::Half,
+  });
+  patproj.pattern.nodes.insert(Node {
+    x: coord(5.0),
+    y: coord(5.0),
+    rotated: false,
+    palindex: 0,
+    kind: NodeKind::FrenchKnot,
+  });
+  patproj.pattern.lines.insert(Line {
+    x: (coord(1.0), coord(3.0)),
+    y: (coord(1.0), coord(2.0)),
+    palindex: 0,
+    kind: LineKind::Back,
+  });
+  patproj.pattern.specialstitches.insert(SpecialStitch {
+    x: coord(8.0),
+    y: coord(1.0),
+    rotation: Degree::new(0),
+    flip: (false, false),
+    palindex: 0,
+    modindex: 0,
+  });
+  patproj
+}
+
+#[test]
+fn test_canvas_anchor_offset() {
+  assert_eq!(CanvasAnchor::TopLeft.offset((10, 10), (20, 16)), (0, 0));
+  assert_eq!(CanvasAnchor::Center.offset((10, 10), (20, 16)), (5, 3));
+  assert_eq!(CanvasAnchor::BottomRight.offset((10, 10), (20, 16)), (10, 6));
+  assert_eq!(CanvasAnchor::Right.offset((10, 10), (5, 5)), (-5, -3));
+  assert_eq!(CanvasPlacement::Offset(2, -1).offset((10, 10), (5, 5)), (2, -1));
+}
+
+#[test]
+fn test_resize_canvas() {
+  let app = setup_app();
+  let window = WebviewWindowBuilder::new(&app, "main", WebviewUrl::default())
+    .build()
+    .unwrap();
+
+  let mut patproj = create_pattern_project();
+  let original = patproj.clone();
+
+  // Grow the canvas, keeping the content in the center.
+  let action = ResizeCanvasAction::new(14, 12, CanvasAnchor::Center.offset((10, 10), (14, 12)));
+  action.perform(&window, &mut patproj).unwrap();
+  assert_eq!((patproj.pattern.fabric.width, patproj.pattern.fabric.height), (14, 12));
+  let fullstitch = patproj.pattern.fullstitches.iter().next().unwrap();
+  assert_eq!((fullstitch.x, fullstitch.y), (coord(2.0), coord(1.0)));
+  let line = patproj.pattern.lines.iter().next().unwrap();
+  assert_eq!(line.x, (coord(3.0), coord(5.0)));
+  assert_eq!(line.y, (coord(2.0), coord(3.0)));
+  let specialstitch = patproj.pattern.specialstitches.iter().next().unwrap();
+  assert_eq!((specialstitch.x, specialstitch.y), (coord(10.0), coord(2.0)));
+
+  action.revoke(&window, &mut patproj).unwrap();
+  assert_eq!(patproj.pattern.fabric, original.pattern.fabric);
+  assert_eq!(
+    patproj.pattern.fullstitches.iter().collect::<Vec<_>>(),
+    original.pattern.fullstitches.iter().collect::<Vec<_>>()
+  );
+  assert_eq!(
+    patproj.pattern.lines.iter().collect::<Vec<_>>(),
+    original.pattern.lines.iter().collect::<Vec<_>>()
+  );
+}
+
+#[test]
+fn test_shrink_canvas() {
+  let app = setup_app();
+  let window = WebviewWindowBuilder::new(&app, "main", WebviewUrl::default())
+    .build()
+    .unwrap();
+
+  let mut patproj = create_pattern_project();
+  let original = patproj.clone();
+
+  // Shrink the canvas, keeping the bottom-right corner.
+  let action = ResizeCanvasAction::new(6, 6, CanvasAnchor::BottomRight.offset((10, 10), (6, 6)));
+  action.perform(&window, &mut patproj).unwrap();
+  assert_eq!(patproj.pattern.fullstitches.iter().count(), 0);
+  assert_eq!(patproj.pattern.lines.iter().count(), 0);
+  assert_eq!(patproj.pattern.specialstitches.iter().count(), 0);
+  let partstitch = patproj.pattern.partstitches.iter().next().unwrap();
+  assert_eq!((partstitch.x, partstitch.y), (coord(5.0), coord(5.0)));
+  let node = patproj.pattern.nodes.iter().next().unwrap();
+  assert_eq!((node.x, node.y), (coord(1.0), coord(1.0)));
+  assert_eq!(Action::<MockRuntime>::describe(&action).stitches, 3);
+
+  action.revoke(&window, &mut patproj).unwrap();
+  assert_eq!(patproj.pattern.fabric, original.pattern.fabric);
+  assert_eq!(
+    patproj.pattern.specialstitches.iter().collect::<Vec<_>>(),
+    original.pattern.specialstitches.iter().collect::<Vec<_>>()
+  );
+  assert_eq!(
+    patproj.pattern.nodes.iter().collect::<Vec<_>>(),
+    original.pattern.nodes.iter().collect::<Vec<_>>()
+  );
+  assert_eq!(
+    patproj.pattern.partstitches.iter().collect::<Vec<_>>(),
+    original.pattern.partstitches.iter().collect::<Vec<_>>()
+  );
+}
+
+#[test]
+fn test_content_bounds() {
+  let mut patproj = create_pattern_project();
+  assert_eq!(
+    patproj.pattern.content_bounds(),
+    Some(Rect {
+      x: 0,
+      y: 0,
+      width: 10,
+      height: 10,
+    })
+  );
+
+  patproj.pattern.fullstitches = Stitches::new();
+  patproj.pattern.partstitches = Stitches::new();
+  assert_eq!(
+    patproj.pattern.content_bounds(),
+    Some(Rect { x: 1, y: 1, width: 8, height: 5 })
+  );
+
+  assert_eq!(PatternProject::default().pattern.content_bounds(), None);
+}
+
+#[test]
+fn test_content_bounds_of_straight_lines() {
+  let line = |x: (f32, f32), y: (f32, f32)| Line {
+    x: (coord(x.0), coord(x.1)),
+    y: (coord(y.0), coord(y.1)),
+    palindex: 0,
+    kind: LineKind::Back,
+  };
+
+  // A lone horizontal or vertical line still results in a non-empty area to crop the canvas to.
+  let mut pattern = Pattern::default();
+  pattern.lines.insert(line((1.0, 5.0), (3.0, 3.0)));
+  assert_eq!(pattern.content_bounds(), Some(Rect { x: 1, y: 3, width: 4, height: 1 }));
+
+  let mut pattern = Pattern::default();
+  pattern.lines.insert(line((2.0, 2.0), (1.0, 4.0)));
+  assert_eq!(pattern.content_bounds(), Some(Rect { x: 2, y: 1, width: 1, height: 3 }));
+}
+
+#[test]
+fn test_crop_canvas() {
+  let app = setup_app();
+  let window = WebviewWindowBuilder::new(&app, "main", WebviewUrl::default())
+    .build()
+    .unwrap();
+
+  let mut patproj = create_pattern_project();
+  let original = patproj.clone();
+
+  let area = Rect { x: 4, y: 4, width: 3, height: 3 };
+  let action = CropCanvasAction::new(area, 1);
+  action.perform(&window, &mut patproj).unwrap();
+  assert_eq!((patproj.pattern.fabric.width, patproj.pattern.fabric.height), (5, 5));
+  let node = patproj.pattern.nodes.iter().next().unwrap();
+  assert_eq!((node.x, node.y), (coord(2.0), coord(2.0)));
+  assert_eq!(Action::<MockRuntime>::describe(&action).stitches, 4);
+
+  action.revoke(&window, &mut patproj).unwrap();
+  assert_eq!(patproj.pattern.fabric, original.pattern.fabric);
+  assert_eq!(
+    patproj.pattern.nodes.iter().collect::<Vec<_>>(),
+    original.pattern.nodes.iter().collect::<Vec<_>>()
+  );
+  assert_eq!(patproj.pattern.fullstitches.iter().count(), 1);
+  assert_eq!(patproj.pattern.lines.iter().count(), 1);
+}
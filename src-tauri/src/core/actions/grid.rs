@@ -3,9 +3,10 @@ use std::sync::OnceLock;
 use anyhow::Result;
 use base64::Engine;
 use base64::engine::general_purpose::STANDARD;
-use tauri::{Emitter, WebviewWindow};
+use borsh::{BorshDeserialize, BorshSerialize};
+use tauri::WebviewWindow;
 
-use super::Action;
+use super::{Action, ActionDescription, ActionKind, EncodedAction, emit, once_lock};
 use crate::core::pattern::PatternProject;
 use crate::core::pattern::display::Grid;
 
@@ -13,9 +14,10 @@ use crate::core::pattern::display::Grid;
 #[path = "grid.test.rs"]
 mod tests;
 
-#[derive(Clone)]
+#[derive(Clone, BorshSerialize, BorshDeserialize)]
 pub struct UpdateGridPropertiesAction {
   grid: Grid,
+  #[borsh(serialize_with = "once_lock::serialize", deserialize_with = "once_lock::deserialize")]
   old_grid: OnceLock<Grid>,
 }
 
@@ -25,13 +27,17 @@ impl UpdateGridPropertiesAction {
   }
 }
 
+impl ActionKind for UpdateGridPropertiesAction {
+  const KIND: &str = "update_grid_properties";
+}
+
 impl<R: tauri::Runtime> Action<R> for UpdateGridPropertiesAction {
   /// Updates the grid properties.
   ///
   /// **Emits:**
   /// - `grid:update` with the updated grid properties.
   fn perform(&self, window: &WebviewWindow<R>, patproj: &mut PatternProject) -> Result<()> {
-    window.emit("grid:update", STANDARD.encode(borsh::to_vec(&self.grid)?))?;
+    emit(window, "grid:update", STANDARD.encode(borsh::to_vec(&self.grid)?))?;
     let old_grid = std::mem::replace(&mut patproj.display_settings.grid, self.grid.clone());
     if self.old_grid.get().is_none() {
       self.old_grid.set(old_grid).unwrap();
@@ -45,8 +51,27 @@ impl<R: tauri::Runtime> Action<R> for UpdateGridPropertiesAction {
   /// - `grid:update` with the previous grid properties.
   fn revoke(&self, window: &WebviewWindow<R>, patproj: &mut PatternProject) -> Result<()> {
     let old_grid = self.old_grid.get().unwrap();
-    window.emit("grid:update", STANDARD.encode(borsh::to_vec(&old_grid)?))?;
+    emit(window, "grid:update", STANDARD.encode(borsh::to_vec(&old_grid)?))?;
     patproj.display_settings.grid = old_grid.clone();
     Ok(())
   }
+
+  /// Absorbs the subsequent grid update, keeping the original grid properties to restore.
+  fn coalesce(&mut self, next: &dyn Action<R>) -> bool {
+    match next.as_any().downcast_ref::<Self>() {
+      Some(next) => {
+        self.grid = next.grid.clone();
+        true
+      }
+      None => false,
+    }
+  }
+
+  fn describe(&self) -> ActionDescription {
+    ActionDescription::new(Self::KIND, "Update grid")
+  }
+
+  fn encode(&self) -> Result<EncodedAction> {
+    EncodedAction::new(self)
+  }
 }
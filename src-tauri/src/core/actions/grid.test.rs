@@ -48,3 +48,34 @@ fn test_update_fabric() {
     action.revoke(&window, &mut patproj).unwrap();
   }
 }
+
+#[test]
+fn test_coalesce_grid_updates() {
+  let app = setup_app();
+  let window = WebviewWindowBuilder::new(&app, "main", WebviewUrl::default())
+    .build()
+    .unwrap();
+
+  let mut patproj = PatternProject::default();
+  let mut action = UpdateGridPropertiesAction::new(Grid {
+    major_lines_interval: 5,
+    ..Grid::default()
+  });
+  action.perform(&window, &mut patproj).unwrap();
+
+  let next_grid = Grid {
+    major_lines_interval: 15,
+    ..Grid::default()
+  };
+  let next = UpdateGridPropertiesAction::new(next_grid.clone());
+  next.perform(&window, &mut patproj).unwrap();
+
+  assert!(Action::<MockRuntime>::coalesce(&mut action, &next));
+
+  // The coalesced action restores the grid properties from before the first update.
+  action.revoke(&window, &mut patproj).unwrap();
+  assert_eq!(patproj.display_settings.grid, Grid::default());
+
+  action.perform(&window, &mut patproj).unwrap();
+  assert_eq!(patproj.display_settings.grid, next_grid);
+}
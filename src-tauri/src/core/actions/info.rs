@@ -0,0 +1,92 @@
+use std::sync::OnceLock;
+
+use anyhow::Result;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use borsh::{BorshDeserialize, BorshSerialize};
+use tauri::WebviewWindow;
+
+use super::{Action, ActionDescription, ActionKind, EncodedAction, emit, once_lock};
+use crate::core::pattern::{PatternInfo, PatternProject};
+
+#[cfg(test)]
+#[path = "info.test.rs"]
+mod tests;
+
+#[derive(Clone, BorshSerialize, BorshDeserialize)]
+pub struct UpdatePatternInfoAction {
+  info: PatternInfo,
+  #[borsh(serialize_with = "once_lock::serialize", deserialize_with = "once_lock::deserialize")]
+  old_info: OnceLock<PatternInfo>,
+}
+
+impl UpdatePatternInfoAction {
+  pub fn new(info: PatternInfo) -> Self {
+    Self { info, old_info: OnceLock::new() }
+  }
+}
+
+impl ActionKind for UpdatePatternInfoAction {
+  const KIND: &str = "update_pattern_info";
+}
+
+impl<R: tauri::Runtime> Action<R> for UpdatePatternInfoAction {
+  /// Updates the pattern information.
+  /// The modification date is kept as is, since it belongs to the file rather than to the user edits.
+  /// Fails if a keyword contains a comma, since the keywords are stored as a comma-separated list.
+  ///
+  /// **Emits:**
+  /// - `pattern:update_info` with the updated pattern information.
+  fn perform(&self, window: &WebviewWindow<R>, patproj: &mut PatternProject) -> Result<()> {
+    if let Some(keyword) = self.info.keywords.iter().find(|keyword| keyword.contains(',')) {
+      anyhow::bail!("The keyword \"{keyword}\" must not contain commas");
+    }
+    let info = PatternInfo {
+      modified_at: patproj.pattern.info.modified_at,
+      ..self.info.clone()
+    };
+    emit(window, "pattern:update_info", STANDARD.encode(borsh::to_vec(&info)?))?;
+    let old_info = std::mem::replace(&mut patproj.pattern.info, info);
+    if self.old_info.get().is_none() {
+      self.old_info.set(old_info).unwrap();
+    }
+    Ok(())
+  }
+
+  /// Restores the previous pattern information, except for the modification date.
+  ///
+  /// **Emits:**
+  /// - `pattern:update_info` with the previous pattern information.
+  fn revoke(&self, window: &WebviewWindow<R>, patproj: &mut PatternProject) -> Result<()> {
+    let old_info = PatternInfo {
+      modified_at: patproj.pattern.info.modified_at,
+      ..self.old_info.get().unwrap().clone()
+    };
+    emit(
+      window,
+      "pattern:update_info",
+      STANDARD.encode(borsh::to_vec(&old_info)?),
+    )?;
+    patproj.pattern.info = old_info;
+    Ok(())
+  }
+
+  /// Coalesces the consecutive updates, so typing into the text fields produces a single undo step.
+  fn coalesce(&mut self, next: &dyn Action<R>) -> bool {
+    match next.as_any().downcast_ref::<Self>() {
+      Some(next) => {
+        self.info = next.info.clone();
+        true
+      }
+      None => false,
+    }
+  }
+
+  fn describe(&self) -> ActionDescription {
+    ActionDescription::new(Self::KIND, "Update pattern info")
+  }
+
+  fn encode(&self) -> Result<EncodedAction> {
+    EncodedAction::new(self)
+  }
+}
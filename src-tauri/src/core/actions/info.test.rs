@@ -0,0 +1,123 @@
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use tauri::test::{MockRuntime, mock_builder};
+use tauri::{App, Listener, WebviewUrl, WebviewWindowBuilder, generate_context};
+
+use super::{Action, UpdatePatternInfoAction};
+use crate::core::pattern::{PatternInfo, PatternProject};
+
+fn setup_app() -> App<MockRuntime> {
+  mock_builder().build(generate_context!()).unwrap()
+}
+
+fn pattern_info(title: &str) -> PatternInfo {
+  PatternInfo {
+    title: String::from(title),
+    author: String::from("Nazar Antoniuk"),
+    keywords: vec![String::from("flowers"), String::from("spring")],
+    license: String::from("CC BY 4.0"),
+    created_at: Some(1_700_000_000),
+    ..PatternInfo::default()
+  }
+}
+
+#[test]
+fn test_update_pattern_info() {
+  let app = setup_app();
+  let window = WebviewWindowBuilder::new(&app, "main", WebviewUrl::default())
+    .build()
+    .unwrap();
+
+  let mut patproj = PatternProject::default();
+  let info = pattern_info("Spring Flowers");
+  let action = UpdatePatternInfoAction::new(info.clone());
+
+  // Test executing the command.
+  {
+    let expected_info = info.clone();
+    let event_id = window.listen("pattern:update_info", move |e| {
+      let base64: &str = serde_json::from_str(e.payload()).unwrap();
+      let actual: PatternInfo = borsh::from_slice(&STANDARD.decode(base64).unwrap()).unwrap();
+      assert_eq!(actual, expected_info);
+    });
+
+    action.perform(&window, &mut patproj).unwrap();
+    assert_eq!(patproj.pattern.info, info);
+    window.unlisten(event_id);
+  }
+
+  // Test revoking the command.
+  {
+    window.listen("pattern:update_info", move |e| {
+      let base64: &str = serde_json::from_str(e.payload()).unwrap();
+      let actual: PatternInfo = borsh::from_slice(&STANDARD.decode(base64).unwrap()).unwrap();
+      assert_eq!(actual, PatternInfo::default());
+    });
+
+    action.revoke(&window, &mut patproj).unwrap();
+    assert_eq!(patproj.pattern.info, PatternInfo::default());
+  }
+}
+
+#[test]
+fn test_coalesce_pattern_info() {
+  let app = setup_app();
+  let window = WebviewWindowBuilder::new(&app, "main", WebviewUrl::default())
+    .build()
+    .unwrap();
+
+  let mut patproj = PatternProject::default();
+
+  let mut action = UpdatePatternInfoAction::new(pattern_info("Spring"));
+  action.perform(&window, &mut patproj).unwrap();
+
+  let next = UpdatePatternInfoAction::new(pattern_info("Spring Flowers"));
+  next.perform(&window, &mut patproj).unwrap();
+  assert!(Action::<MockRuntime>::coalesce(&mut action, &next));
+
+  action.revoke(&window, &mut patproj).unwrap();
+  assert_eq!(patproj.pattern.info, PatternInfo::default());
+
+  action.perform(&window, &mut patproj).unwrap();
+  assert_eq!(patproj.pattern.info, pattern_info("Spring Flowers"));
+}
+
+#[test]
+fn test_keep_modification_date() {
+  let app = setup_app();
+  let window = WebviewWindowBuilder::new(&app, "main", WebviewUrl::default())
+    .build()
+    .unwrap();
+
+  let mut patproj = PatternProject::default();
+  patproj.pattern.info.modified_at = Some(1_700_000_000);
+
+  let action = UpdatePatternInfoAction::new(pattern_info("Spring Flowers"));
+  action.perform(&window, &mut patproj).unwrap();
+  assert_eq!(patproj.pattern.info.title, "Spring Flowers");
+  assert_eq!(patproj.pattern.info.modified_at, Some(1_700_000_000));
+
+  // The pattern is saved after the update, so undoing the update must not bring the old date back.
+  patproj.pattern.info.modified_at = Some(1_800_000_000);
+  action.revoke(&window, &mut patproj).unwrap();
+  assert_eq!(patproj.pattern.info.title, PatternInfo::default().title);
+  assert_eq!(patproj.pattern.info.modified_at, Some(1_800_000_000));
+}
+
+#[test]
+fn test_reject_keywords_with_commas() {
+  let app = setup_app();
+  let window = WebviewWindowBuilder::new(&app, "main", WebviewUrl::default())
+    .build()
+    .unwrap();
+
+  let mut patproj = PatternProject::default();
+  let action = UpdatePatternInfoAction::new(PatternInfo {
+    keywords: vec![String::from("flowers, spring")],
+    ..PatternInfo::default()
+  });
+
+  // The keywords are comma-separated in OXS, so such a keyword would be split into two.
+  assert!(action.perform(&window, &mut patproj).is_err());
+  assert_eq!(patproj.pattern.info, PatternInfo::default());
+}
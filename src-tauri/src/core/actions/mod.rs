@@ -7,12 +7,26 @@
 //! Each method of the `Action` accepts a reference to the `WebviewWindow` and a mutable reference to the `PatternProject`.
 //! The `WebviewWindow` is used to emit events to the frontend.
 //! The reason for this is that the `Action` can affects many aspects of the `PatternProject` so it is easier to emit an event for each change.
+//! The events should be emitted through the `emit` function, so they can be muted when many actions are replayed at once.
+
+use std::any::Any;
+use std::cell::Cell;
 
 use anyhow::Result;
-use tauri::WebviewWindow;
+use borsh::BorshSerialize;
+use tauri::{Emitter, WebviewWindow};
 
 use super::pattern::PatternProject;
 
+mod backstitch;
+pub use backstitch::*;
+
+mod composite;
+pub use composite::*;
+
+mod confetti;
+pub use confetti::*;
+
 mod display;
 pub use display::*;
 
@@ -22,30 +36,152 @@ pub use fabric::*;
 mod grid;
 pub use grid::*;
 
+mod info;
+pub use info::*;
+
 mod stitches;
 pub use stitches::*;
 
 mod palette;
 pub use palette::*;
 
+mod print;
+pub use print::*;
+
+mod transform;
+pub use transform::*;
+
+mod registry;
+pub use registry::*;
+
 /// An action that can be executed and revoked.
-pub trait Action<R: tauri::Runtime>: Send + Sync + dyn_clone::DynClone {
+pub trait Action<R: tauri::Runtime>: Send + Sync + dyn_clone::DynClone + AsAny {
   /// Perform the action.
   fn perform(&self, window: &WebviewWindow<R>, patproj: &mut PatternProject) -> Result<()>;
 
   /// Revoke (undo) the action.
   fn revoke(&self, window: &WebviewWindow<R>, patproj: &mut PatternProject) -> Result<()>;
+
+  /// Try to absorb the next, already performed, action into this one.
+  /// Returns `true` if the action has been absorbed, so the history doesn't need to store it separately.
+  ///
+  /// This is used to coalesce a series of similar actions (e.g., produced by dragging a slider) into a single undo step.
+  /// By default, actions are not coalesced.
+  fn coalesce(&mut self, _next: &dyn Action<R>) -> bool {
+    false
+  }
+
+  /// Returns the approximate amount of memory occupied by the action in bytes.
+  /// It is used to keep the history within its memory budget, so actions that own heap data should account for it.
+  fn size(&self) -> usize {
+    std::mem::size_of_val(self)
+  }
+
+  /// Describe the action for displaying it in the history.
+  fn describe(&self) -> ActionDescription;
+
+  /// Serialize the action along with the values it has captured, so it can be persisted and rebuilt later.
+  /// The action type must be registered in the `ActionRegistry` to be rebuilt.
+  fn encode(&self) -> Result<EncodedAction>;
 }
 
 dyn_clone::clone_trait_object!(<R: tauri::Runtime> Action<R>);
 
+/// A short summary of an action.
+#[derive(Debug, Clone, PartialEq, BorshSerialize)]
+pub struct ActionDescription {
+  /// The action type, the same as `ActionKind::KIND`.
+  /// The frontend can use it to display a localized label.
+  pub kind: String,
+  /// A human-readable label of the action.
+  pub label: String,
+  /// The number of stitches affected by the action.
+  pub stitches: u32,
+  /// The number of palette items affected by the action.
+  pub palette_items: u32,
+}
+
+impl ActionDescription {
+  pub fn new(kind: &str, label: impl Into<String>) -> Self {
+    Self {
+      kind: kind.to_string(),
+      label: label.into(),
+      stitches: 0,
+      palette_items: 0,
+    }
+  }
+
+  pub fn with_stitches(mut self, stitches: usize) -> Self {
+    self.stitches = stitches as u32;
+    self
+  }
+
+  pub fn with_palette_items(mut self, palette_items: usize) -> Self {
+    self.palette_items = palette_items as u32;
+    self
+  }
+}
+
+thread_local! {
+  static EVENTS_MUTED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Emit the event to the frontend unless the events are muted on the current thread.
+pub fn emit<R: tauri::Runtime, S: serde::Serialize + Clone>(
+  window: &WebviewWindow<R>,
+  event: &str,
+  payload: S,
+) -> tauri::Result<()> {
+  if EVENTS_MUTED.get() {
+    return Ok(());
+  }
+  window.emit(event, payload)
+}
+
+/// Notify the frontend that the pattern has changed as a whole (e.g., it is transformed or resized).
+/// Instead of sending the whole pattern, the frontend is expected to reload it, requesting its stitches by regions.
+pub fn emit_pattern_changed<R: tauri::Runtime>(window: &WebviewWindow<R>) -> tauri::Result<()> {
+  emit(window, "pattern:changed", ())
+}
+
+/// Run the closure with the events of the actions muted.
+/// This is useful when many actions are performed at once and the frontend is updated with a single event afterwards.
+pub fn with_muted_events<T>(f: impl FnOnce() -> T) -> T {
+  /// Restores the previous state even if the closure panics.
+  struct Guard(bool);
+
+  impl Drop for Guard {
+    fn drop(&mut self) {
+      EVENTS_MUTED.set(self.0);
+    }
+  }
+
+  let _guard = Guard(EVENTS_MUTED.replace(true));
+  f()
+}
+
+/// A helper trait to downcast actions to their concrete types.
+pub trait AsAny: Any {
+  fn as_any(&self) -> &dyn Any;
+}
+
+impl<T: Any> AsAny for T {
+  fn as_any(&self) -> &dyn Any {
+    self
+  }
+}
+
 #[cfg(debug_assertions)]
 pub mod mock {
   use super::*;
 
-  #[derive(Clone)]
+  #[derive(Clone, borsh::BorshSerialize, borsh::BorshDeserialize)]
   pub struct MockAction;
 
+  impl ActionKind for MockAction {
+    const KIND: &str = "mock";
+  }
+
   impl<R: tauri::Runtime> Action<R> for MockAction {
     fn perform(&self, _window: &WebviewWindow<R>, _patproj: &mut PatternProject) -> Result<()> {
       Ok(())
@@ -54,5 +190,13 @@ pub mod mock {
     fn revoke(&self, _window: &WebviewWindow<R>, _patproj: &mut PatternProject) -> Result<()> {
       Ok(())
     }
+
+    fn describe(&self) -> ActionDescription {
+      ActionDescription::new(Self::KIND, "Mock action")
+    }
+
+    fn encode(&self) -> Result<EncodedAction> {
+      EncodedAction::new(self)
+    }
   }
 }
@@ -3,9 +3,10 @@ use std::sync::OnceLock;
 use anyhow::Result;
 use base64::Engine;
 use base64::engine::general_purpose::STANDARD;
-use tauri::{Emitter, WebviewWindow};
+use borsh::{BorshDeserialize, BorshSerialize};
+use tauri::WebviewWindow;
 
-use super::Action;
+use super::{Action, ActionDescription, ActionKind, EncodedAction, emit, once_lock};
 use crate::core::pattern::display::{Formats, Symbols};
 use crate::core::pattern::{PaletteItem, PatternProject, Stitch};
 use crate::display::PaletteSettings;
@@ -14,7 +15,7 @@ use crate::display::PaletteSettings;
 #[path = "palette.test.rs"]
 mod tests;
 
-#[derive(Clone)]
+#[derive(Clone, BorshSerialize, BorshDeserialize)]
 pub struct AddPaletteItemAction {
   palitem: PaletteItem,
   symbols: Symbols,
@@ -31,6 +32,10 @@ impl AddPaletteItemAction {
   }
 }
 
+impl ActionKind for AddPaletteItemAction {
+  const KIND: &str = "add_palette_item";
+}
+
 impl<R: tauri::Runtime> Action<R> for AddPaletteItemAction {
   /// Add the palette item to the pattern.
   ///
@@ -40,7 +45,8 @@ impl<R: tauri::Runtime> Action<R> for AddPaletteItemAction {
     patproj.pattern.palette.push(self.palitem.clone());
     patproj.display_settings.symbols.push(self.symbols.clone());
     patproj.display_settings.formats.push(self.formats.clone());
-    window.emit(
+    emit(
+      window,
       "palette:add_palette_item",
       STANDARD.encode(borsh::to_vec(&AddedPaletteItemData {
         palitem: self.palitem.clone(),
@@ -60,18 +66,27 @@ impl<R: tauri::Runtime> Action<R> for AddPaletteItemAction {
     patproj.pattern.palette.pop();
     patproj.display_settings.symbols.pop();
     patproj.display_settings.formats.pop();
-    window.emit("palette:remove_palette_item", patproj.pattern.palette.len())?;
+    emit(window, "palette:remove_palette_item", patproj.pattern.palette.len())?;
     Ok(())
   }
+
+  fn describe(&self) -> ActionDescription {
+    ActionDescription::new(Self::KIND, "Add palette item").with_palette_items(1)
+  }
+
+  fn encode(&self) -> Result<EncodedAction> {
+    EncodedAction::new(self)
+  }
 }
 
-#[derive(Clone)]
+#[derive(Clone, BorshSerialize, BorshDeserialize)]
 pub struct RemovePaletteItemsAction {
   palindexes: Vec<u8>,
+  #[borsh(serialize_with = "once_lock::serialize", deserialize_with = "once_lock::deserialize")]
   metadata: OnceLock<RemovePaletteItemActionMetadata>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, BorshSerialize, BorshDeserialize)]
 struct RemovePaletteItemActionMetadata {
   palitems: Vec<PaletteItem>,
   symbols: Vec<Symbols>,
@@ -90,6 +105,10 @@ impl RemovePaletteItemsAction {
   }
 }
 
+impl ActionKind for RemovePaletteItemsAction {
+  const KIND: &str = "remove_palette_items";
+}
+
 impl<R: tauri::Runtime> Action<R> for RemovePaletteItemsAction {
   /// Remove the palette item from the pattern.
   ///
@@ -107,7 +126,7 @@ impl<R: tauri::Runtime> Action<R> for RemovePaletteItemsAction {
       symbols.push(patproj.display_settings.symbols.remove(palindex));
       formats.push(patproj.display_settings.formats.remove(palindex));
     }
-    window.emit("palette:remove_palette_items", &self.palindexes)?;
+    emit(window, "palette:remove_palette_items", &self.palindexes)?;
 
     // Reverse the vectors to restore the in the order of `palindexes`.
     palitems.reverse();
@@ -115,7 +134,11 @@ impl<R: tauri::Runtime> Action<R> for RemovePaletteItemsAction {
     formats.reverse();
 
     let conflicts = patproj.pattern.remove_stitches_by_palindexes(&self.palindexes);
-    window.emit("stitches:remove_many", STANDARD.encode(borsh::to_vec(&conflicts)?))?;
+    emit(
+      window,
+      "stitches:remove_many",
+      STANDARD.encode(borsh::to_vec(&conflicts)?),
+    )?;
 
     if self.metadata.get().is_none() {
       self
@@ -151,7 +174,8 @@ impl<R: tauri::Runtime> Action<R> for RemovePaletteItemsAction {
       let formats = metadata.formats.get(index).unwrap().clone();
       patproj.display_settings.formats.insert(palindex, formats.clone());
 
-      window.emit(
+      emit(
+        window,
         "palette:add_palette_item",
         STANDARD.encode(borsh::to_vec(&AddedPaletteItemData {
           palindex: palindex as u8,
@@ -167,17 +191,38 @@ impl<R: tauri::Runtime> Action<R> for RemovePaletteItemsAction {
       &self.palindexes,
       patproj.pattern.palette.len() as u8,
     );
-    window.emit(
+    emit(
+      window,
       "stitches:add_many",
       STANDARD.encode(borsh::to_vec(&metadata.conflicts)?),
     )?;
 
     Ok(())
   }
+
+  fn size(&self) -> usize {
+    let metadata_size = self.metadata.get().map_or(0, |metadata| {
+      std::mem::size_of_val(metadata.palitems.as_slice())
+        + std::mem::size_of_val(metadata.symbols.as_slice())
+        + std::mem::size_of_val(metadata.formats.as_slice())
+        + std::mem::size_of_val(metadata.conflicts.as_slice())
+    });
+    std::mem::size_of_val(self) + self.palindexes.len() + metadata_size
+  }
+
+  fn describe(&self) -> ActionDescription {
+    ActionDescription::new(Self::KIND, "Remove palette items")
+      .with_palette_items(self.palindexes.len())
+      .with_stitches(self.metadata.get().map_or(0, |metadata| metadata.conflicts.len()))
+  }
+
+  fn encode(&self) -> Result<EncodedAction> {
+    EncodedAction::new(self)
+  }
 }
 
-#[derive(Debug, Clone, borsh::BorshSerialize)]
-#[cfg_attr(test, derive(PartialEq, borsh::BorshDeserialize))]
+#[derive(Debug, Clone, BorshSerialize)]
+#[cfg_attr(test, derive(PartialEq, BorshDeserialize))]
 struct AddedPaletteItemData {
   palitem: PaletteItem,
   palindex: u8,
@@ -185,9 +230,10 @@ struct AddedPaletteItemData {
   formats: Formats,
 }
 
-#[derive(Clone)]
+#[derive(Clone, BorshSerialize, BorshDeserialize)]
 pub struct UpdatePaletteDisplaySettingsAction {
   settings: PaletteSettings,
+  #[borsh(serialize_with = "once_lock::serialize", deserialize_with = "once_lock::deserialize")]
   old_settings: OnceLock<PaletteSettings>,
 }
 
@@ -200,13 +246,18 @@ impl UpdatePaletteDisplaySettingsAction {
   }
 }
 
+impl ActionKind for UpdatePaletteDisplaySettingsAction {
+  const KIND: &str = "update_palette_display_settings";
+}
+
 impl<R: tauri::Runtime> Action<R> for UpdatePaletteDisplaySettingsAction {
   /// Update the display settings of the palette.
   ///
   /// **Emits:**
   /// - `palette:update_display_settings` with the new display settings.
   fn perform(&self, window: &WebviewWindow<R>, patproj: &mut PatternProject) -> Result<()> {
-    window.emit(
+    emit(
+      window,
       "palette:update_display_settings",
       STANDARD.encode(borsh::to_vec(&self.settings)?),
     )?;
@@ -223,11 +274,191 @@ impl<R: tauri::Runtime> Action<R> for UpdatePaletteDisplaySettingsAction {
   /// - `palette:update_display_settings` with the old display settings.
   fn revoke(&self, window: &WebviewWindow<R>, patproj: &mut PatternProject) -> Result<()> {
     let old_settings = self.old_settings.get().unwrap();
-    window.emit(
+    emit(
+      window,
       "palette:update_display_settings",
       STANDARD.encode(borsh::to_vec(&old_settings)?),
     )?;
     patproj.display_settings.palette_settings = old_settings.clone();
     Ok(())
   }
+
+  fn describe(&self) -> ActionDescription {
+    ActionDescription::new(Self::KIND, "Update palette display settings")
+  }
+
+  fn encode(&self) -> Result<EncodedAction> {
+    EncodedAction::new(self)
+  }
+}
+
+/// A palette item symbols with the index of the palette item.
+#[derive(Debug, Clone, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct PaletteItemSymbols {
+  pub palindex: u8,
+  pub symbols: Symbols,
+}
+
+#[derive(Clone, BorshSerialize, BorshDeserialize)]
+pub struct UpdatePaletteItemSymbolsAction {
+  palindex: u8,
+  symbols: Symbols,
+  #[borsh(serialize_with = "once_lock::serialize", deserialize_with = "once_lock::deserialize")]
+  old_symbols: OnceLock<Symbols>,
+}
+
+impl UpdatePaletteItemSymbolsAction {
+  pub fn new(palindex: u8, symbols: Symbols) -> Self {
+    Self {
+      palindex,
+      symbols,
+      old_symbols: OnceLock::new(),
+    }
+  }
+}
+
+impl ActionKind for UpdatePaletteItemSymbolsAction {
+  const KIND: &str = "update_palette_item_symbols";
+}
+
+impl<R: tauri::Runtime> Action<R> for UpdatePaletteItemSymbolsAction {
+  /// Update the symbols of the palette item.
+  ///
+  /// **Emits:**
+  /// - `palette:update_symbols` with the palette item index and its new symbols.
+  fn perform(&self, window: &WebviewWindow<R>, patproj: &mut PatternProject) -> Result<()> {
+    let Some(symbols) = patproj.display_settings.symbols.get_mut(self.palindex as usize) else {
+      anyhow::bail!("There is no palette item with the index {}", self.palindex);
+    };
+    let old_symbols = std::mem::replace(symbols, self.symbols.clone());
+    emit(
+      window,
+      "palette:update_symbols",
+      STANDARD.encode(borsh::to_vec(&PaletteItemSymbols {
+        palindex: self.palindex,
+        symbols: self.symbols.clone(),
+      })?),
+    )?;
+    if self.old_symbols.get().is_none() {
+      self.old_symbols.set(old_symbols).unwrap();
+    }
+    Ok(())
+  }
+
+  /// Restore the previous symbols of the palette item.
+  ///
+  /// **Emits:**
+  /// - `palette:update_symbols` with the palette item index and its previous symbols.
+  fn revoke(&self, window: &WebviewWindow<R>, patproj: &mut PatternProject) -> Result<()> {
+    let old_symbols = self.old_symbols.get().unwrap();
+    patproj.display_settings.symbols[self.palindex as usize] = old_symbols.clone();
+    emit(
+      window,
+      "palette:update_symbols",
+      STANDARD.encode(borsh::to_vec(&PaletteItemSymbols {
+        palindex: self.palindex,
+        symbols: old_symbols.clone(),
+      })?),
+    )?;
+    Ok(())
+  }
+
+  fn describe(&self) -> ActionDescription {
+    ActionDescription::new(Self::KIND, "Update symbols").with_palette_items(1)
+  }
+
+  fn encode(&self) -> Result<EncodedAction> {
+    EncodedAction::new(self)
+  }
+}
+
+/// A palette item formats with the index of the palette item.
+#[derive(Debug, Clone, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct PaletteItemFormats {
+  pub palindex: u8,
+  pub formats: Formats,
+}
+
+#[derive(Clone, BorshSerialize, BorshDeserialize)]
+pub struct UpdatePaletteItemFormatsAction {
+  palindex: u8,
+  formats: Formats,
+  #[borsh(serialize_with = "once_lock::serialize", deserialize_with = "once_lock::deserialize")]
+  old_formats: OnceLock<Formats>,
+}
+
+impl UpdatePaletteItemFormatsAction {
+  pub fn new(palindex: u8, formats: Formats) -> Self {
+    Self {
+      palindex,
+      formats,
+      old_formats: OnceLock::new(),
+    }
+  }
+}
+
+impl ActionKind for UpdatePaletteItemFormatsAction {
+  const KIND: &str = "update_palette_item_formats";
+}
+
+impl<R: tauri::Runtime> Action<R> for UpdatePaletteItemFormatsAction {
+  /// Update the formats (symbol, line, node and font formats) of the palette item.
+  ///
+  /// **Emits:**
+  /// - `palette:update_formats` with the palette item index and its new formats.
+  fn perform(&self, window: &WebviewWindow<R>, patproj: &mut PatternProject) -> Result<()> {
+    let Some(formats) = patproj.display_settings.formats.get_mut(self.palindex as usize) else {
+      anyhow::bail!("There is no palette item with the index {}", self.palindex);
+    };
+    let old_formats = std::mem::replace(formats, self.formats.clone());
+    emit(
+      window,
+      "palette:update_formats",
+      STANDARD.encode(borsh::to_vec(&PaletteItemFormats {
+        palindex: self.palindex,
+        formats: self.formats.clone(),
+      })?),
+    )?;
+    if self.old_formats.get().is_none() {
+      self.old_formats.set(old_formats).unwrap();
+    }
+    Ok(())
+  }
+
+  /// Restore the previous formats of the palette item.
+  ///
+  /// **Emits:**
+  /// - `palette:update_formats` with the palette item index and its previous formats.
+  fn revoke(&self, window: &WebviewWindow<R>, patproj: &mut PatternProject) -> Result<()> {
+    let old_formats = self.old_formats.get().unwrap();
+    patproj.display_settings.formats[self.palindex as usize] = old_formats.clone();
+    emit(
+      window,
+      "palette:update_formats",
+      STANDARD.encode(borsh::to_vec(&PaletteItemFormats {
+        palindex: self.palindex,
+        formats: old_formats.clone(),
+      })?),
+    )?;
+    Ok(())
+  }
+
+  /// Absorbs the subsequent update of the same palette item formats, e.g., while picking a line color.
+  fn coalesce(&mut self, next: &dyn Action<R>) -> bool {
+    match next.as_any().downcast_ref::<Self>() {
+      Some(next) if next.palindex == self.palindex => {
+        self.formats = next.formats.clone();
+        true
+      }
+      _ => false,
+    }
+  }
+
+  fn describe(&self) -> ActionDescription {
+    ActionDescription::new(Self::KIND, "Update formats").with_palette_items(1)
+  }
+
+  fn encode(&self) -> Result<EncodedAction> {
+    EncodedAction::new(self)
+  }
 }
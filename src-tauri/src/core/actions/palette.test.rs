@@ -5,7 +5,8 @@ use tauri::test::{MockRuntime, mock_builder};
 use tauri::{App, Listener, WebviewUrl, WebviewWindow, WebviewWindowBuilder, generate_context};
 
 use super::{
-  Action, AddPaletteItemAction, AddedPaletteItemData, RemovePaletteItemsAction, UpdatePaletteDisplaySettingsAction,
+  Action, AddPaletteItemAction, AddedPaletteItemData, PaletteItemFormats, PaletteItemSymbols, RemovePaletteItemsAction,
+  UpdatePaletteDisplaySettingsAction, UpdatePaletteItemFormatsAction, UpdatePaletteItemSymbolsAction,
 };
 use crate::core::parser::oxs;
 use crate::core::pattern::display::{Formats, Symbols};
@@ -250,3 +251,82 @@ fn test_update_palette_display_settings() {
     window.unlisten(update_display_settings_event_id);
   }
 }
+
+#[test]
+fn test_update_palette_item_symbols() {
+  let app = setup_app();
+  let window = WebviewWindowBuilder::new(&app, "main", WebviewUrl::default())
+    .build()
+    .unwrap();
+
+  let mut patproj = create_pattern_project();
+  let old_symbols = patproj.display_settings.symbols[1].clone();
+  let new_symbols = Symbols {
+    full: Some(65),
+    half: Some(66),
+    ..Symbols::default()
+  };
+  let action = UpdatePaletteItemSymbolsAction::new(1, new_symbols.clone());
+
+  // Test executing the command.
+  {
+    let expected = PaletteItemSymbols {
+      palindex: 1,
+      symbols: new_symbols.clone(),
+    };
+    let event_id = window.listen("palette:update_symbols", move |e| {
+      let base64: &str = serde_json::from_str(e.payload()).unwrap();
+      let received: PaletteItemSymbols = borsh::from_slice(&STANDARD.decode(base64).unwrap()).unwrap();
+      assert_eq!(received, expected);
+    });
+    action.perform(&window, &mut patproj).unwrap();
+    assert_eq!(patproj.display_settings.symbols[1], new_symbols);
+    window.unlisten(event_id);
+  }
+
+  // Test revoking the command.
+  {
+    action.revoke(&window, &mut patproj).unwrap();
+    assert_eq!(patproj.display_settings.symbols[1], old_symbols);
+  }
+
+  // The palette item must exist.
+  let action = UpdatePaletteItemSymbolsAction::new(u8::MAX, Symbols::default());
+  assert!(action.perform(&window, &mut patproj).is_err());
+}
+
+#[test]
+fn test_update_palette_item_formats() {
+  let app = setup_app();
+  let window = WebviewWindowBuilder::new(&app, "main", WebviewUrl::default())
+    .build()
+    .unwrap();
+
+  let mut patproj = create_pattern_project();
+  let old_formats = patproj.display_settings.formats[0].clone();
+  let mut new_formats = Formats::default();
+  new_formats.back.color = String::from("FF0000");
+  let action = UpdatePaletteItemFormatsAction::new(0, new_formats.clone());
+
+  // Test executing the command.
+  {
+    let expected = PaletteItemFormats {
+      palindex: 0,
+      formats: new_formats.clone(),
+    };
+    let event_id = window.listen("palette:update_formats", move |e| {
+      let base64: &str = serde_json::from_str(e.payload()).unwrap();
+      let received: PaletteItemFormats = borsh::from_slice(&STANDARD.decode(base64).unwrap()).unwrap();
+      assert_eq!(received, expected);
+    });
+    action.perform(&window, &mut patproj).unwrap();
+    assert_eq!(patproj.display_settings.formats[0], new_formats);
+    window.unlisten(event_id);
+  }
+
+  // Test revoking the command.
+  {
+    action.revoke(&window, &mut patproj).unwrap();
+    assert_eq!(patproj.display_settings.formats[0], old_formats);
+  }
+}
@@ -0,0 +1,88 @@
+use std::sync::OnceLock;
+
+use anyhow::Result;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use borsh::{BorshDeserialize, BorshSerialize};
+use tauri::WebviewWindow;
+
+use super::{Action, ActionDescription, ActionKind, EncodedAction, emit, once_lock};
+use crate::core::pattern::PatternProject;
+use crate::core::pattern::print::PrintSettings;
+
+#[cfg(test)]
+#[path = "print.test.rs"]
+mod tests;
+
+#[derive(Clone, BorshSerialize, BorshDeserialize)]
+pub struct UpdatePrintSettingsAction {
+  print_settings: PrintSettings,
+  #[borsh(serialize_with = "once_lock::serialize", deserialize_with = "once_lock::deserialize")]
+  old_print_settings: OnceLock<PrintSettings>,
+}
+
+impl UpdatePrintSettingsAction {
+  pub fn new(print_settings: PrintSettings) -> Self {
+    Self {
+      print_settings,
+      old_print_settings: OnceLock::new(),
+    }
+  }
+}
+
+impl ActionKind for UpdatePrintSettingsAction {
+  const KIND: &str = "update_print_settings";
+}
+
+impl<R: tauri::Runtime> Action<R> for UpdatePrintSettingsAction {
+  /// Updates the print settings.
+  ///
+  /// **Emits:**
+  /// - `print:update` with the updated print settings.
+  fn perform(&self, window: &WebviewWindow<R>, patproj: &mut PatternProject) -> Result<()> {
+    emit(
+      window,
+      "print:update",
+      STANDARD.encode(borsh::to_vec(&self.print_settings)?),
+    )?;
+    let old_print_settings = std::mem::replace(&mut patproj.print_settings, self.print_settings.clone());
+    if self.old_print_settings.get().is_none() {
+      self.old_print_settings.set(old_print_settings).unwrap();
+    }
+    Ok(())
+  }
+
+  /// Restore the previous print settings.
+  ///
+  /// **Emits:**
+  /// - `print:update` with the previous print settings.
+  fn revoke(&self, window: &WebviewWindow<R>, patproj: &mut PatternProject) -> Result<()> {
+    let old_print_settings = self.old_print_settings.get().unwrap();
+    emit(
+      window,
+      "print:update",
+      STANDARD.encode(borsh::to_vec(old_print_settings)?),
+    )?;
+    patproj.print_settings = old_print_settings.clone();
+    Ok(())
+  }
+
+  /// Absorbs the subsequent print settings update, keeping the original print settings to restore.
+  fn coalesce(&mut self, next: &dyn Action<R>) -> bool {
+    match next.as_any().downcast_ref::<Self>() {
+      Some(next) => {
+        self.print_settings = next.print_settings.clone();
+        true
+      }
+      None => false,
+    }
+  }
+
+  fn describe(&self) -> ActionDescription {
+    ActionDescription::new(Self::KIND, "Update print settings")
+  }
+
+  fn encode(&self) -> Result<EncodedAction> {
+    EncodedAction::new(self)
+  }
+}
@@ -0,0 +1,87 @@
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use tauri::test::{MockRuntime, mock_builder};
+use tauri::{App, Listener, WebviewUrl, WebviewWindowBuilder, generate_context};
+
+use super::{Action, UpdatePrintSettingsAction};
+use crate::core::pattern::PatternProject;
+use crate::core::pattern::print::{PageMargins, PrintSettings};
+
+fn setup_app() -> App<MockRuntime> {
+  mock_builder().build(generate_context!()).unwrap()
+}
+
+fn print_settings(header: &str) -> PrintSettings {
+  PrintSettings {
+    header: String::from(header),
+    footer: String::from("Page {page} of {pages}"),
+    margins: PageMargins {
+      left: 1.0,
+      right: 1.0,
+      ..PageMargins::default()
+    },
+    show_adjacent_page_numbers: false,
+    ..PrintSettings::default()
+  }
+}
+
+#[test]
+fn test_update_print_settings() {
+  let app = setup_app();
+  let window = WebviewWindowBuilder::new(&app, "main", WebviewUrl::default())
+    .build()
+    .unwrap();
+
+  let mut patproj = PatternProject::default();
+  let print_settings = print_settings("{title}");
+  let action = UpdatePrintSettingsAction::new(print_settings.clone());
+
+  // Test executing the command.
+  {
+    let expected = print_settings.clone();
+    let event_id = window.listen("print:update", move |e| {
+      let base64: &str = serde_json::from_str(e.payload()).unwrap();
+      let actual: PrintSettings = borsh::from_slice(&STANDARD.decode(base64).unwrap()).unwrap();
+      assert_eq!(actual, expected);
+    });
+
+    action.perform(&window, &mut patproj).unwrap();
+    assert_eq!(patproj.print_settings, print_settings);
+    window.unlisten(event_id);
+  }
+
+  // Test revoking the command.
+  {
+    window.listen("print:update", move |e| {
+      let base64: &str = serde_json::from_str(e.payload()).unwrap();
+      let actual: PrintSettings = borsh::from_slice(&STANDARD.decode(base64).unwrap()).unwrap();
+      assert_eq!(actual, PrintSettings::default());
+    });
+
+    action.revoke(&window, &mut patproj).unwrap();
+    assert_eq!(patproj.print_settings, PrintSettings::default());
+  }
+}
+
+#[test]
+fn test_coalesce_print_settings() {
+  let app = setup_app();
+  let window = WebviewWindowBuilder::new(&app, "main", WebviewUrl::default())
+    .build()
+    .unwrap();
+
+  let mut patproj = PatternProject::default();
+
+  let mut action = UpdatePrintSettingsAction::new(print_settings("{title"));
+  action.perform(&window, &mut patproj).unwrap();
+
+  let next = UpdatePrintSettingsAction::new(print_settings("{title}"));
+  next.perform(&window, &mut patproj).unwrap();
+  assert!(Action::<MockRuntime>::coalesce(&mut action, &next));
+
+  action.revoke(&window, &mut patproj).unwrap();
+  assert_eq!(patproj.print_settings, PrintSettings::default());
+
+  action.perform(&window, &mut patproj).unwrap();
+  assert_eq!(patproj.print_settings, print_settings("{title}"));
+}
@@ -0,0 +1,114 @@
+use std::collections::HashMap;
+use std::io;
+use std::sync::OnceLock;
+
+use anyhow::Result;
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use super::*;
+
+#[cfg(test)]
+#[path = "registry.test.rs"]
+mod tests;
+
+/// An action type that can be persisted.
+pub trait ActionKind {
+  /// The unique identifier of the action type.
+  /// It is stored in the project files, so it must never be changed once released.
+  const KIND: &'static str;
+}
+
+/// An action in the serialized form, tagged with its type.
+#[derive(Debug, Clone, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct EncodedAction {
+  pub kind: String,
+  pub data: Vec<u8>,
+}
+
+impl EncodedAction {
+  pub fn new<A: ActionKind + BorshSerialize>(action: &A) -> Result<Self> {
+    Ok(Self {
+      kind: A::KIND.to_string(),
+      data: borsh::to_vec(action)?,
+    })
+  }
+}
+
+type ActionDecoder<R> = fn(&ActionRegistry<R>, &[u8]) -> Result<Box<dyn Action<R>>>;
+
+/// A registry of the action types used to rebuild the actions from their serialized form.
+pub struct ActionRegistry<R: tauri::Runtime> {
+  decoders: HashMap<&'static str, ActionDecoder<R>>,
+}
+
+impl<R: tauri::Runtime> ActionRegistry<R> {
+  /// Creates an empty registry.
+  pub fn new() -> Self {
+    Self { decoders: HashMap::new() }
+  }
+
+  /// Registers the action type that is deserialized as is.
+  pub fn register<A: Action<R> + ActionKind + BorshDeserialize>(&mut self) {
+    self.register_with(A::KIND, |_, data| Ok(Box::new(borsh::from_slice::<A>(data)?)));
+  }
+
+  /// Registers the action type with a custom decoder.
+  /// The decoder receives the registry itself, so it can decode the nested actions.
+  pub fn register_with(&mut self, kind: &'static str, decoder: ActionDecoder<R>) {
+    self.decoders.insert(kind, decoder);
+  }
+
+  /// Rebuilds the action from its serialized form.
+  pub fn decode(&self, action: &EncodedAction) -> Result<Box<dyn Action<R>>> {
+    match self.decoders.get(action.kind.as_str()) {
+      Some(decoder) => decoder(self, &action.data),
+      None => anyhow::bail!("Unknown action kind: {}", action.kind),
+    }
+  }
+}
+
+impl<R: tauri::Runtime> Default for ActionRegistry<R> {
+  /// Creates a registry with all the built-in action types.
+  fn default() -> Self {
+    let mut registry = Self::new();
+    registry.register_with(CompositeAction::<R>::KIND, CompositeAction::decode);
+    registry.register::<SetDisplayModeAction>();
+    registry.register::<ShowSymbolsAction>();
+    registry.register::<UpdateDisplaySettingsAction>();
+    registry.register::<UpdateFabricPropertiesAction>();
+    registry.register::<ResizeCanvasAction>();
+    registry.register::<CropCanvasAction>();
+    registry.register::<UpdateGridPropertiesAction>();
+    registry.register::<UpdatePatternInfoAction>();
+    registry.register::<UpdatePrintSettingsAction>();
+    registry.register::<AddPaletteItemAction>();
+    registry.register::<RemovePaletteItemsAction>();
+    registry.register::<UpdatePaletteDisplaySettingsAction>();
+    registry.register::<UpdatePaletteItemSymbolsAction>();
+    registry.register::<UpdatePaletteItemFormatsAction>();
+    registry.register::<AddStitchAction>();
+    registry.register::<RemoveStitchAction>();
+    registry.register::<TransformPatternAction>();
+    registry.register::<OptimizeLinesAction>();
+    registry.register::<CleanupConfettiAction>();
+    registry
+  }
+}
+
+/// Borsh (de)serialization of the values captured by the actions during their first performing.
+/// Use it as `#[borsh(serialize_with = "once_lock::serialize", deserialize_with = "once_lock::deserialize")]`.
+pub mod once_lock {
+  use super::*;
+
+  pub fn serialize<T: BorshSerialize, W: io::Write>(value: &OnceLock<T>, writer: &mut W) -> io::Result<()> {
+    value.get().serialize(writer)
+  }
+
+  pub fn deserialize<T: BorshDeserialize, R: io::Read>(reader: &mut R) -> io::Result<OnceLock<T>> {
+    let lock = OnceLock::new();
+    if let Some(value) = Option::<T>::deserialize_reader(reader)? {
+      let _ = lock.set(value);
+    }
+    Ok(lock)
+  }
+}
@@ -0,0 +1,83 @@
+use ordered_float::NotNan;
+use tauri::test::{MockRuntime, mock_builder};
+use tauri::{App, WebviewUrl, WebviewWindowBuilder, generate_context};
+
+use super::*;
+use crate::core::pattern::display::Grid;
+use crate::core::pattern::*;
+
+fn setup_app() -> App<MockRuntime> {
+  mock_builder().build(generate_context!()).unwrap()
+}
+
+fn full_stitch(x: f32, y: f32, palindex: u8) -> Stitch {
+  Stitch::Full(FullStitch {
+    x: NotNan::new(x).unwrap(),
+    y: NotNan::new(y).unwrap(),
+    palindex,
+    kind: FullStitchKind::Full,
+  })
+}
+
+#[test]
+fn rebuilds_performed_action() {
+  let app = setup_app();
+  let window = WebviewWindowBuilder::new(&app, "main", WebviewUrl::default())
+    .build()
+    .unwrap();
+  let registry = ActionRegistry::<MockRuntime>::default();
+
+  let mut patproj = PatternProject::default();
+  patproj.pattern.add_stitch(full_stitch(0.0, 0.0, 0));
+
+  // The new stitch replaces the existing one, which must be restored after revoking the rebuilt action.
+  let action = AddStitchAction::new(full_stitch(0.0, 0.0, 1));
+  Action::<MockRuntime>::perform(&action, &window, &mut patproj).unwrap();
+
+  let encoded = Action::<MockRuntime>::encode(&action).unwrap();
+  assert_eq!(encoded.kind, AddStitchAction::KIND);
+
+  let rebuilt = registry.decode(&encoded).unwrap();
+  rebuilt.revoke(&window, &mut patproj).unwrap();
+  assert!(patproj.pattern.contains_stitch(&full_stitch(0.0, 0.0, 0)));
+}
+
+#[test]
+fn rebuilds_composite_action() {
+  let app = setup_app();
+  let window = WebviewWindowBuilder::new(&app, "main", WebviewUrl::default())
+    .build()
+    .unwrap();
+  let registry = ActionRegistry::<MockRuntime>::default();
+
+  let mut patproj = PatternProject::default();
+  let grid = Grid {
+    major_lines_interval: 15,
+    ..Grid::default()
+  };
+  let actions: Vec<Box<dyn Action<MockRuntime>>> = vec![
+    Box::new(UpdateGridPropertiesAction::new(grid.clone())),
+    Box::new(AddStitchAction::new(full_stitch(1.0, 1.0, 0))),
+  ];
+  let action = CompositeAction::new(actions);
+  action.perform(&window, &mut patproj).unwrap();
+
+  let rebuilt = registry.decode(&action.encode().unwrap()).unwrap();
+  rebuilt.revoke(&window, &mut patproj).unwrap();
+  assert_eq!(patproj.display_settings.grid, Grid::default());
+  assert!(!patproj.pattern.contains_stitch(&full_stitch(1.0, 1.0, 0)));
+
+  rebuilt.perform(&window, &mut patproj).unwrap();
+  assert_eq!(patproj.display_settings.grid, grid);
+  assert!(patproj.pattern.contains_stitch(&full_stitch(1.0, 1.0, 0)));
+}
+
+#[test]
+fn fails_on_unknown_action_kind() {
+  let registry = ActionRegistry::<MockRuntime>::default();
+  let encoded = EncodedAction {
+    kind: String::from("unknown"),
+    data: Vec::new(),
+  };
+  assert!(registry.decode(&encoded).is_err());
+}
@@ -3,18 +3,20 @@ use std::sync::OnceLock;
 use anyhow::Result;
 use base64::Engine;
 use base64::engine::general_purpose::STANDARD;
-use tauri::{Emitter, WebviewWindow};
+use borsh::{BorshDeserialize, BorshSerialize};
+use tauri::WebviewWindow;
 
-use super::Action;
+use super::{Action, ActionDescription, ActionKind, EncodedAction, emit, once_lock};
 use crate::core::pattern::{PatternProject, Stitch};
 
 #[cfg(test)]
 #[path = "stitches.test.rs"]
 mod tests;
 
-#[derive(Clone)]
+#[derive(Clone, BorshSerialize, BorshDeserialize)]
 pub struct AddStitchAction {
   stitch: Stitch,
+  #[borsh(serialize_with = "once_lock::serialize", deserialize_with = "once_lock::deserialize")]
   conflicts: OnceLock<Vec<Stitch>>,
 }
 
@@ -27,6 +29,10 @@ impl AddStitchAction {
   }
 }
 
+impl ActionKind for AddStitchAction {
+  const KIND: &str = "add_stitch";
+}
+
 impl<R: tauri::Runtime> Action<R> for AddStitchAction {
   /// Add the stitch to the pattern.
   ///
@@ -35,8 +41,16 @@ impl<R: tauri::Runtime> Action<R> for AddStitchAction {
   /// - `stitches:remove_many` with the removed stitches that conflict with the new stitch
   fn perform(&self, window: &WebviewWindow<R>, patproj: &mut PatternProject) -> Result<()> {
     let conflicts = patproj.pattern.add_stitch(self.stitch);
-    window.emit("stitches:add_one", STANDARD.encode(borsh::to_vec(&self.stitch)?))?;
-    window.emit("stitches:remove_many", STANDARD.encode(borsh::to_vec(&conflicts)?))?;
+    emit(
+      window,
+      "stitches:add_one",
+      STANDARD.encode(borsh::to_vec(&self.stitch)?),
+    )?;
+    emit(
+      window,
+      "stitches:remove_many",
+      STANDARD.encode(borsh::to_vec(&conflicts)?),
+    )?;
     if self.conflicts.get().is_none() {
       self.conflicts.set(conflicts).unwrap();
     }
@@ -52,17 +66,39 @@ impl<R: tauri::Runtime> Action<R> for AddStitchAction {
     let conflicts = self.conflicts.get().unwrap();
     patproj.pattern.remove_stitch(self.stitch);
     patproj.pattern.add_stitches(conflicts.clone());
-    window.emit("stitches:remove_one", STANDARD.encode(borsh::to_vec(&self.stitch)?))?;
-    window.emit("stitches:add_many", STANDARD.encode(borsh::to_vec(&conflicts)?))?;
+    emit(
+      window,
+      "stitches:remove_one",
+      STANDARD.encode(borsh::to_vec(&self.stitch)?),
+    )?;
+    emit(window, "stitches:add_many", STANDARD.encode(borsh::to_vec(&conflicts)?))?;
     Ok(())
   }
+
+  fn size(&self) -> usize {
+    std::mem::size_of_val(self)
+      + self
+        .conflicts
+        .get()
+        .map_or(0, |conflicts| std::mem::size_of_val(conflicts.as_slice()))
+  }
+
+  fn describe(&self) -> ActionDescription {
+    ActionDescription::new(Self::KIND, "Add stitch")
+      .with_stitches(1 + self.conflicts.get().map_or(0, |conflicts| conflicts.len()))
+  }
+
+  fn encode(&self) -> Result<EncodedAction> {
+    EncodedAction::new(self)
+  }
 }
 
-#[derive(Clone)]
+#[derive(Clone, BorshSerialize, BorshDeserialize)]
 pub struct RemoveStitchAction {
   // Actual stitch contains only the necessary stitch properties ...
   target_stitch: Stitch,
   // ... while the actual stitch contains all properties.
+  #[borsh(serialize_with = "once_lock::serialize", deserialize_with = "once_lock::deserialize")]
   actual_stitch: OnceLock<Stitch>,
 }
 
@@ -75,6 +111,10 @@ impl RemoveStitchAction {
   }
 }
 
+impl ActionKind for RemoveStitchAction {
+  const KIND: &str = "remove_stitch";
+}
+
 impl<R: tauri::Runtime> Action<R> for RemoveStitchAction {
   /// Remove the stitch from the pattern.
   ///
@@ -85,7 +125,7 @@ impl<R: tauri::Runtime> Action<R> for RemoveStitchAction {
     if self.actual_stitch.get().is_none() {
       self.actual_stitch.set(stitch).unwrap();
     }
-    window.emit("stitches:remove_one", STANDARD.encode(borsh::to_vec(&stitch)?))?;
+    emit(window, "stitches:remove_one", STANDARD.encode(borsh::to_vec(&stitch)?))?;
     Ok(())
   }
 
@@ -96,7 +136,15 @@ impl<R: tauri::Runtime> Action<R> for RemoveStitchAction {
   fn revoke(&self, window: &WebviewWindow<R>, patproj: &mut PatternProject) -> Result<()> {
     let stitch = self.actual_stitch.get().unwrap();
     patproj.pattern.add_stitch(*stitch);
-    window.emit("stitches:add_one", STANDARD.encode(borsh::to_vec(&stitch)?))?;
+    emit(window, "stitches:add_one", STANDARD.encode(borsh::to_vec(&stitch)?))?;
     Ok(())
   }
+
+  fn describe(&self) -> ActionDescription {
+    ActionDescription::new(Self::KIND, "Remove stitch").with_stitches(1)
+  }
+
+  fn encode(&self) -> Result<EncodedAction> {
+    EncodedAction::new(self)
+  }
 }
@@ -0,0 +1,99 @@
+use std::sync::OnceLock;
+
+use anyhow::Result;
+use borsh::{BorshDeserialize, BorshSerialize};
+use tauri::WebviewWindow;
+
+use super::{Action, ActionDescription, ActionKind, EncodedAction, emit_pattern_changed, once_lock};
+use crate::core::pattern::{PatternProject, Rect, StitchChanges, Transformation};
+
+#[cfg(test)]
+#[path = "transform.test.rs"]
+mod tests;
+
+/// Rotates or mirrors the whole pattern or its area.
+#[derive(Clone, BorshSerialize, BorshDeserialize)]
+pub struct TransformPatternAction {
+  transformation: Transformation,
+  area: Option<Rect>,
+  #[borsh(serialize_with = "once_lock::serialize", deserialize_with = "once_lock::deserialize")]
+  old_fabric_size: OnceLock<(u16, u16)>,
+  #[borsh(serialize_with = "once_lock::serialize", deserialize_with = "once_lock::deserialize")]
+  changes: OnceLock<StitchChanges>,
+}
+
+impl TransformPatternAction {
+  pub fn new(transformation: Transformation, area: Option<Rect>) -> Self {
+    Self {
+      transformation,
+      area,
+      old_fabric_size: OnceLock::new(),
+      changes: OnceLock::new(),
+    }
+  }
+}
+
+impl ActionKind for TransformPatternAction {
+  const KIND: &str = "transform_pattern";
+}
+
+impl<R: tauri::Runtime> Action<R> for TransformPatternAction {
+  /// Transforms the stitches of the pattern or its area.
+  ///
+  /// **Emits:**
+  /// - `pattern:changed`, since many stitches and the fabric may be changed.
+  fn perform(&self, window: &WebviewWindow<R>, patproj: &mut PatternProject) -> Result<()> {
+    let old_fabric_size = (patproj.pattern.fabric.width, patproj.pattern.fabric.height);
+    let changes = patproj.pattern.transform(self.transformation, self.area);
+    emit_pattern_changed(window)?;
+
+    if self.old_fabric_size.get().is_none() {
+      self.old_fabric_size.set(old_fabric_size).unwrap();
+    }
+    if self.changes.get().is_none() {
+      self.changes.set(changes).unwrap();
+    }
+
+    Ok(())
+  }
+
+  /// Restores the transformed stitches and the fabric size.
+  ///
+  /// **Emits:**
+  /// - `pattern:changed`.
+  fn revoke(&self, window: &WebviewWindow<R>, patproj: &mut PatternProject) -> Result<()> {
+    let &(width, height) = self.old_fabric_size.get().unwrap();
+    patproj.pattern.revert_stitch_changes(self.changes.get().unwrap());
+    patproj.pattern.fabric.width = width;
+    patproj.pattern.fabric.height = height;
+    emit_pattern_changed(window)?;
+    Ok(())
+  }
+
+  fn size(&self) -> usize {
+    std::mem::size_of_val(self)
+      + self.changes.get().map_or(0, |changes| {
+        std::mem::size_of_val(changes.removed.as_slice())
+          + std::mem::size_of_val(changes.removed_specialstitches.as_slice())
+          + std::mem::size_of_val(changes.added.as_slice())
+          + std::mem::size_of_val(changes.added_specialstitches.as_slice())
+      })
+  }
+
+  fn describe(&self) -> ActionDescription {
+    let label = match self.transformation {
+      Transformation::Rotate90 | Transformation::Rotate180 | Transformation::Rotate270 => "Rotate",
+      Transformation::FlipHorizontal | Transformation::FlipVertical => "Mirror",
+    };
+    ActionDescription::new(Self::KIND, label).with_stitches(
+      self
+        .changes
+        .get()
+        .map_or(0, |changes| changes.added.len() + changes.added_specialstitches.len()),
+    )
+  }
+
+  fn encode(&self) -> Result<EncodedAction> {
+    EncodedAction::new(self)
+  }
+}
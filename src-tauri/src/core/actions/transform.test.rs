@@ -0,0 +1,64 @@
+use ordered_float::NotNan;
+use tauri::test::{MockRuntime, mock_builder};
+use tauri::{App, WebviewUrl, WebviewWindowBuilder, generate_context};
+
+use super::{Action, TransformPatternAction};
+use crate::core::pattern::*;
+
+fn setup_app() -> App<MockRuntime> {
+  mock_builder().build(generate_context!()).unwrap()
+}
+
+fn create_pattern_project() -> PatternProject {
+  let mut patproj = PatternProject::default();
+  patproj.pattern.fabric.width = 10;
+  patproj.pattern.fabric.height = 6;
+  patproj.pattern.partstitches.insert(PartStitch {
+    x: NotNan::new(1.0).unwrap(),
+    y: NotNan::new(2.0).unwrap(),
+    palindex: 0,
+    direction: PartStitchDirection::Forward,
+    kind: PartStitchKind::Half,
+  });
+  patproj.pattern.specialstitches.insert(SpecialStitch {
+    x: NotNan::new(4.0).unwrap(),
+    y: NotNan::new(4.0).unwrap(),
+    rotation: Degree::new(0),
+    flip: (false, false),
+    palindex: 0,
+    modindex: 0,
+  });
+  patproj
+}
+
+#[test]
+fn test_transform_pattern() {
+  let app = setup_app();
+  let window = WebviewWindowBuilder::new(&app, "main", WebviewUrl::default())
+    .build()
+    .unwrap();
+
+  let mut patproj = create_pattern_project();
+  let original = patproj.clone();
+
+  let action = TransformPatternAction::new(Transformation::Rotate270, None);
+  action.perform(&window, &mut patproj).unwrap();
+  assert_eq!((patproj.pattern.fabric.width, patproj.pattern.fabric.height), (6, 10));
+  let partstitch = patproj.pattern.partstitches.iter().next().unwrap();
+  assert_eq!((*partstitch.x, *partstitch.y), (2.0, 8.0));
+  assert_eq!(partstitch.direction, PartStitchDirection::Backward);
+  let specialstitch = patproj.pattern.specialstitches.iter().next().unwrap();
+  assert_eq!(specialstitch.rotation, Degree::new(270));
+  assert_eq!(Action::<MockRuntime>::describe(&action).stitches, 2);
+
+  action.revoke(&window, &mut patproj).unwrap();
+  assert_eq!(patproj.pattern.fabric, original.pattern.fabric);
+  assert_eq!(
+    patproj.pattern.partstitches.iter().collect::<Vec<_>>(),
+    original.pattern.partstitches.iter().collect::<Vec<_>>()
+  );
+  assert_eq!(
+    patproj.pattern.specialstitches.iter().collect::<Vec<_>>(),
+    original.pattern.specialstitches.iter().collect::<Vec<_>>()
+  );
+}
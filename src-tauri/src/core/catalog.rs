@@ -0,0 +1,237 @@
+//! This module contains the thread catalogs, i.e. the lists of threads produced by the manufacturers.
+//!
+//! The catalogs of DMC, Anchor, Madeira and others are bundled with the app.
+//! Users can add their own catalogs by putting JSON files of the same format into the app document directory.
+//! Every catalog is named after its file, and the custom catalogs take precedence over the bundled ones.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+use anyhow::Result;
+use borsh::{BorshDeserialize, BorshSerialize};
+use serde::{Deserialize, Serialize};
+
+use super::convert::Lab;
+use super::pattern::PaletteItem;
+
+#[cfg(test)]
+#[path = "catalog.test.rs"]
+mod tests;
+
+/// A thread as it is stored in the catalog files.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, BorshSerialize, BorshDeserialize)]
+pub struct CatalogThread {
+  pub brand: String,
+  pub number: String,
+  pub name: String,
+  /// The thread color as the `RRGGBB` hex string.
+  pub color: String,
+}
+
+impl From<CatalogThread> for PaletteItem {
+  fn from(thread: CatalogThread) -> Self {
+    PaletteItem {
+      brand: thread.brand,
+      number: thread.number,
+      name: thread.name,
+      color: thread.color,
+      blends: None,
+      bead: None,
+      strands: None,
+    }
+  }
+}
+
+/// A short description of the catalog to list the available catalogs without their threads.
+#[derive(Debug, Clone, PartialEq, BorshSerialize)]
+pub struct CatalogInfo {
+  pub name: String,
+  /// Whether the catalog is added by the user.
+  pub custom: bool,
+  pub size: u32,
+}
+
+/// The criteria to search the threads by.
+#[derive(Debug, Default, Clone, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct ThreadQuery {
+  /// The text that the thread number starts with or the thread name contains, case-insensitive.
+  pub text: Option<String>,
+  /// The color to find the closest threads to, as the `RRGGBB` hex string.
+  pub color: Option<String>,
+  /// The catalogs to search in. All the catalogs are searched if it is empty.
+  pub catalogs: Vec<String>,
+  /// The brand of the threads, case-insensitive. The threads of all the brands are searched if it is not specified.
+  pub brand: Option<String>,
+  /// The maximum number of the found threads. The number is not limited if it is zero.
+  pub limit: u32,
+}
+
+#[derive(Debug)]
+struct Catalog {
+  custom: bool,
+  threads: Vec<CatalogThread>,
+  /// The lowercase thread numbers mapped to the thread positions.
+  /// If several threads have the same number, the first one is kept here, though all of them are searchable.
+  numbers: HashMap<String, usize>,
+  /// The lowercase thread names in the order of the threads.
+  names: Vec<String>,
+  /// The thread colors in the order of the threads. Unparsable colors are `None`.
+  colors: Vec<Option<Lab>>,
+}
+
+impl Catalog {
+  fn new(threads: Vec<CatalogThread>, custom: bool) -> Self {
+    let mut numbers = HashMap::with_capacity(threads.len());
+    for (index, thread) in threads.iter().enumerate() {
+      let number = thread.number.to_lowercase();
+      if numbers.contains_key(&number) {
+        log::warn!(
+          "The thread {} {} is duplicated in the catalog",
+          thread.brand,
+          thread.number
+        );
+        continue;
+      }
+      numbers.insert(number, index);
+    }
+    Self {
+      custom,
+      numbers,
+      names: threads.iter().map(|thread| thread.name.to_lowercase()).collect(),
+      colors: threads.iter().map(|thread| Lab::from_hex(&thread.color)).collect(),
+      threads,
+    }
+  }
+}
+
+/// The thread catalogs indexed to be searched.
+#[derive(Debug, Default)]
+pub struct ThreadCatalog {
+  catalogs: BTreeMap<String, Catalog>,
+}
+
+impl ThreadCatalog {
+  /// Loads the catalogs from the directory of the bundled catalogs and then from the directory of the custom ones.
+  /// The directories that don't exist are skipped.
+  pub fn load<P: AsRef<Path>>(bundled_dir: P, custom_dir: P) -> Result<Self> {
+    let mut catalog = Self::default();
+    catalog.load_dir(bundled_dir, false)?;
+    catalog.load_dir(custom_dir, true)?;
+    Ok(catalog)
+  }
+
+  /// Loads all the `.json` catalogs in the directory.
+  /// The broken files are skipped, so a single invalid custom catalog doesn't make the others unavailable.
+  pub fn load_dir<P: AsRef<Path>>(&mut self, dir: P, custom: bool) -> Result<()> {
+    let dir = dir.as_ref();
+    if !dir.exists() {
+      return Ok(());
+    }
+
+    for entry in std::fs::read_dir(dir)? {
+      let path = entry?.path();
+      if path.extension().is_none_or(|extension| extension != "json") {
+        continue;
+      }
+      let Some(name) = path.file_stem().map(|stem| stem.to_string_lossy().to_string()) else {
+        continue;
+      };
+      match read_catalog_file(&path) {
+        Ok(threads) => self.insert(name, threads, custom),
+        Err(err) => log::warn!("Failed to load the thread catalog {path:?}: {err:?}"),
+      }
+    }
+    Ok(())
+  }
+
+  /// Adds the catalog replacing the existing one of the same name.
+  pub fn insert(&mut self, name: String, threads: Vec<CatalogThread>, custom: bool) {
+    self.catalogs.insert(name, Catalog::new(threads, custom));
+  }
+
+  /// Returns the available catalogs sorted by their names.
+  pub fn catalogs(&self) -> Vec<CatalogInfo> {
+    self
+      .catalogs
+      .iter()
+      .map(|(name, catalog)| CatalogInfo {
+        name: name.clone(),
+        custom: catalog.custom,
+        size: catalog.threads.len() as u32,
+      })
+      .collect()
+  }
+
+  /// Returns the threads of the catalog in their original order.
+  pub fn get(&self, name: &str) -> Option<&[CatalogThread]> {
+    self.catalogs.get(name).map(|catalog| catalog.threads.as_slice())
+  }
+
+  /// Finds the thread of the catalog by its number, case-insensitive.
+  /// If the number is duplicated in the catalog, the first thread with it is returned.
+  pub fn find(&self, name: &str, number: &str) -> Option<&CatalogThread> {
+    let catalog = self.catalogs.get(name)?;
+    catalog
+      .numbers
+      .get(&number.to_lowercase())
+      .map(|&index| &catalog.threads[index])
+  }
+
+  /// Searches the threads matching the query.
+  /// The threads with duplicated numbers are all found, unlike in `find`.
+  ///
+  /// If the color is specified, the threads are sorted from the closest to the farthest ones (by CIEDE2000).
+  /// Otherwise, the exact number matches come first, then the number prefix matches, then the name matches.
+  /// The threads of the same relevance keep the order of the catalogs.
+  pub fn search(&self, query: &ThreadQuery) -> Vec<CatalogThread> {
+    let text = query.text.as_deref().map(str::trim).filter(|text| !text.is_empty());
+    let text = text.map(str::to_lowercase);
+    let color = query.color.as_deref().and_then(Lab::from_hex);
+    let brand = query.brand.as_deref().map(str::trim).filter(|brand| !brand.is_empty());
+
+    let mut found = Vec::new();
+    for (name, catalog) in self.catalogs.iter() {
+      if !query.catalogs.is_empty() && !query.catalogs.contains(name) {
+        continue;
+      }
+
+      for (index, thread) in catalog.threads.iter().enumerate() {
+        if brand.is_some_and(|brand| !thread.brand.eq_ignore_ascii_case(brand)) {
+          continue;
+        }
+        let number = thread.number.to_lowercase();
+        let relevance = match text.as_deref() {
+          None => 0,
+          Some(text) if number == text => 0,
+          Some(text) if number.starts_with(text) => 1,
+          Some(text) if catalog.names[index].contains(text) => 2,
+          Some(_) => continue,
+        };
+        let distance = match color {
+          Some(color) => match catalog.colors[index] {
+            Some(thread_color) => color.delta_e(&thread_color),
+            None => continue,
+          },
+          None => 0.0,
+        };
+        found.push((distance, relevance, thread));
+      }
+    }
+
+    // The sort is stable, so the threads of the same relevance keep their order.
+    if color.is_some() {
+      found.sort_by(|a, b| a.0.total_cmp(&b.0));
+    } else {
+      found.sort_by_key(|&(_, relevance, _)| relevance);
+    }
+    if query.limit > 0 {
+      found.truncate(query.limit as usize);
+    }
+    found.into_iter().map(|(_, _, thread)| thread.clone()).collect()
+  }
+}
+
+fn read_catalog_file(path: &Path) -> Result<Vec<CatalogThread>> {
+  let content = std::fs::read_to_string(path)?;
+  Ok(serde_json::from_str(&content)?)
+}
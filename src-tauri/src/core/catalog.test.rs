@@ -0,0 +1,212 @@
+use super::*;
+
+fn thread(brand: &str, number: &str, name: &str, color: &str) -> CatalogThread {
+  CatalogThread {
+    brand: String::from(brand),
+    number: String::from(number),
+    name: String::from(name),
+    color: String::from(color),
+  }
+}
+
+fn catalog() -> ThreadCatalog {
+  let mut catalog = ThreadCatalog::default();
+  catalog.insert(
+    String::from("DMC"),
+    vec![
+      thread("DMC", "3", "Tin-MD", "A2A1A5"),
+      thread("DMC", "310", "Black", "000000"),
+      thread("DMC", "321", "Red", "C72B3B"),
+      thread("DMC", "3865", "Winter White", "F9F7F1"),
+      thread("DMC", "B5200", "Snow White", "FFFFFF"),
+    ],
+    false,
+  );
+  catalog.insert(
+    String::from("Anchor"),
+    vec![
+      thread("Anchor", "1", "Snow White", "EBEFEA"),
+      thread("Anchor", "403", "Black", "1E1E1E"),
+    ],
+    false,
+  );
+  catalog
+}
+
+fn numbers(threads: &[CatalogThread]) -> Vec<&str> {
+  threads.iter().map(|thread| thread.number.as_str()).collect()
+}
+
+fn query(text: Option<&str>, color: Option<&str>) -> ThreadQuery {
+  ThreadQuery {
+    text: text.map(String::from),
+    color: color.map(String::from),
+    ..ThreadQuery::default()
+  }
+}
+
+#[test]
+fn loads_bundled_catalogs() {
+  let bundled_dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("resources/palettes");
+  let catalog = ThreadCatalog::load(bundled_dir.clone(), bundled_dir.join("missing")).unwrap();
+
+  let names = catalog.catalogs().into_iter().map(|info| info.name).collect::<Vec<_>>();
+  assert!(names.contains(&String::from("DMC")));
+  assert!(names.contains(&String::from("Anchor")));
+  assert!(names.contains(&String::from("Madeira")));
+  assert!(catalog.catalogs().iter().all(|info| !info.custom && info.size > 0));
+
+  assert_eq!(catalog.find("DMC", "310").unwrap().name, "Black");
+}
+
+#[test]
+fn loads_custom_catalogs() {
+  let bundled_dir = tempfile::tempdir().unwrap();
+  let custom_dir = tempfile::tempdir().unwrap();
+  let write = |dir: &tempfile::TempDir, file_name: &str, threads: &[CatalogThread]| {
+    std::fs::write(dir.path().join(file_name), serde_json::to_string(threads).unwrap()).unwrap();
+  };
+  write(&bundled_dir, "DMC.json", &[thread("DMC", "310", "Black", "000000")]);
+  write(
+    &bundled_dir,
+    "Anchor.json",
+    &[thread("Anchor", "403", "Black", "1E1E1E")],
+  );
+  write(
+    &custom_dir,
+    "DMC.json",
+    &[thread("DMC", "310", "Black (custom)", "000000")],
+  );
+  write(&custom_dir, "Silk.json", &[thread("Silk", "1", "Ivory", "FFFFF0")]);
+  std::fs::write(custom_dir.path().join("Broken.json"), "not a catalog").unwrap();
+  std::fs::write(custom_dir.path().join("notes.txt"), "not a catalog").unwrap();
+
+  let catalog = ThreadCatalog::load(bundled_dir.path(), custom_dir.path()).unwrap();
+  assert_eq!(
+    catalog.catalogs(),
+    vec![
+      CatalogInfo {
+        name: String::from("Anchor"),
+        custom: false,
+        size: 1,
+      },
+      CatalogInfo {
+        name: String::from("DMC"),
+        custom: true,
+        size: 1,
+      },
+      CatalogInfo {
+        name: String::from("Silk"),
+        custom: true,
+        size: 1,
+      },
+    ]
+  );
+  assert_eq!(catalog.find("DMC", "310").unwrap().name, "Black (custom)");
+}
+
+#[test]
+fn finds_threads_by_number() {
+  let catalog = catalog();
+  assert_eq!(catalog.find("DMC", "b5200").unwrap().name, "Snow White");
+  assert_eq!(catalog.find("Anchor", "403").unwrap().name, "Black");
+  assert!(catalog.find("DMC", "403").is_none());
+  assert!(catalog.find("Madeira", "310").is_none());
+
+  assert_eq!(numbers(catalog.get("Anchor").unwrap()), vec!["1", "403"]);
+  assert!(catalog.get("Madeira").is_none());
+}
+
+#[test]
+fn searches_threads_by_text() {
+  let catalog = catalog();
+
+  // The exact number match comes first, then the number prefix matches.
+  assert_eq!(
+    numbers(&catalog.search(&query(Some("3"), None))),
+    vec!["3", "310", "321", "3865"]
+  );
+  assert_eq!(numbers(&catalog.search(&query(Some("31"), None))), vec!["310"]);
+  // The text is trimmed and matched against the names regardless of the case.
+  assert_eq!(
+    numbers(&catalog.search(&query(Some(" white "), None))),
+    vec!["1", "3865", "B5200"]
+  );
+  assert_eq!(
+    numbers(&catalog.search(&query(Some("BLACK"), None))),
+    vec!["403", "310"]
+  );
+  assert!(catalog.search(&query(Some("purple"), None)).is_empty());
+}
+
+#[test]
+fn searches_threads_by_color() {
+  let catalog = catalog();
+  assert_eq!(
+    numbers(&catalog.search(&query(None, Some("101010")))),
+    vec!["310", "403", "321", "3", "1", "3865", "B5200"]
+  );
+  assert_eq!(
+    numbers(&catalog.search(&query(Some("white"), Some("FFFFFF")))),
+    vec!["B5200", "3865", "1"]
+  );
+}
+
+#[test]
+fn filters_and_limits_found_threads() {
+  let catalog = catalog();
+  let found = catalog.search(&ThreadQuery {
+    catalogs: vec![String::from("DMC")],
+    limit: 2,
+    ..query(None, Some("FFFFFF"))
+  });
+  assert_eq!(numbers(&found), vec!["B5200", "3865"]);
+
+  let found = catalog.search(&ThreadQuery {
+    catalogs: vec![String::from("Anchor")],
+    ..query(Some("black"), None)
+  });
+  assert_eq!(numbers(&found), vec!["403"]);
+}
+
+#[test]
+fn filters_threads_by_brand() {
+  let mut catalog = catalog();
+  catalog.insert(
+    String::from("Mixed"),
+    vec![
+      thread("DMC", "310", "Black", "000000"),
+      thread("Anchor", "403", "Black", "1E1E1E"),
+    ],
+    true,
+  );
+
+  let found = catalog.search(&ThreadQuery {
+    brand: Some(String::from("anchor")),
+    ..query(Some("black"), None)
+  });
+  assert_eq!(numbers(&found), vec!["403", "403"]);
+  assert!(found.iter().all(|thread| thread.brand == "Anchor"));
+}
+
+#[test]
+fn keeps_duplicated_thread_numbers() {
+  let mut catalog = ThreadCatalog::default();
+  catalog.insert(
+    String::from("DMC"),
+    vec![
+      thread("DMC", "310", "Black", "000000"),
+      thread("DMC", "310", "Black (new)", "050505"),
+    ],
+    false,
+  );
+
+  // The first thread is found by its number, but all of them are searchable.
+  assert_eq!(catalog.find("DMC", "310").unwrap().name, "Black");
+  let names = catalog
+    .search(&query(Some("310"), None))
+    .into_iter()
+    .map(|thread| thread.name)
+    .collect::<Vec<_>>();
+  assert_eq!(names, vec!["Black", "Black (new)"]);
+}
@@ -0,0 +1,118 @@
+//! Perceptual color math used to match the image colors to the threads.
+
+#[cfg(test)]
+#[path = "color.test.rs"]
+mod tests;
+
+/// The reference white of the D65 illuminant used by sRGB.
+const WHITE_POINT: [f64; 3] = [0.95047, 1.0, 1.08883];
+
+/// A color in the CIE L\*a\*b\* color space.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Lab {
+  pub l: f32,
+  pub a: f32,
+  pub b: f32,
+}
+
+impl Lab {
+  pub fn from_rgb([r, g, b]: [u8; 3]) -> Self {
+    let linearize = |channel: u8| {
+      let channel = channel as f64 / 255.0;
+      if channel <= 0.04045 {
+        channel / 12.92
+      } else {
+        ((channel + 0.055) / 1.055).powf(2.4)
+      }
+    };
+    let (r, g, b) = (linearize(r), linearize(g), linearize(b));
+
+    let x = 0.4124564 * r + 0.3575761 * g + 0.1804375 * b;
+    let y = 0.2126729 * r + 0.7151522 * g + 0.0721750 * b;
+    let z = 0.0193339 * r + 0.1191920 * g + 0.9503041 * b;
+
+    let f = |t: f64| {
+      if t > 0.008856 {
+        t.cbrt()
+      } else {
+        7.787 * t + 16.0 / 116.0
+      }
+    };
+    let (fx, fy, fz) = (f(x / WHITE_POINT[0]), f(y / WHITE_POINT[1]), f(z / WHITE_POINT[2]));
+
+    Self {
+      l: (116.0 * fy - 16.0) as f32,
+      a: (500.0 * (fx - fy)) as f32,
+      b: (200.0 * (fy - fz)) as f32,
+    }
+  }
+
+  /// Parses the color from the `RRGGBB` hex string, as it is stored in the palette items.
+  pub fn from_hex(hex: &str) -> Option<Self> {
+    let hex = hex.trim_start_matches('#');
+    if hex.len() != 6 {
+      return None;
+    }
+    let channel = |index: usize| u8::from_str_radix(hex.get(index..index + 2)?, 16).ok();
+    Some(Self::from_rgb([channel(0)?, channel(2)?, channel(4)?]))
+  }
+
+  /// Returns the perceptual difference between the colors according to the CIEDE2000 formula.
+  pub fn delta_e(&self, other: &Lab) -> f32 {
+    let (l1, a1, b1) = (self.l as f64, self.a as f64, self.b as f64);
+    let (l2, a2, b2) = (other.l as f64, other.a as f64, other.b as f64);
+    let pow25_7 = 25f64.powi(7);
+
+    let c_mean = (a1.hypot(b1) + a2.hypot(b2)) / 2.0;
+    let g = 0.5 * (1.0 - (c_mean.powi(7) / (c_mean.powi(7) + pow25_7)).sqrt());
+    let (a1, a2) = ((1.0 + g) * a1, (1.0 + g) * a2);
+    let (c1, c2) = (a1.hypot(b1), a2.hypot(b2));
+    let hue = |b: f64, a: f64| {
+      if a == 0.0 && b == 0.0 {
+        0.0
+      } else {
+        b.atan2(a).to_degrees().rem_euclid(360.0)
+      }
+    };
+    let (h1, h2) = (hue(b1, a1), hue(b2, a2));
+
+    let delta_l = l2 - l1;
+    let delta_c = c2 - c1;
+    let delta_h = if c1 * c2 == 0.0 {
+      0.0
+    } else {
+      match h2 - h1 {
+        diff if diff > 180.0 => diff - 360.0,
+        diff if diff < -180.0 => diff + 360.0,
+        diff => diff,
+      }
+    };
+    let delta_h = 2.0 * (c1 * c2).sqrt() * (delta_h / 2.0).to_radians().sin();
+
+    let l_mean = (l1 + l2) / 2.0;
+    let c_mean = (c1 + c2) / 2.0;
+    let h_mean = if c1 * c2 == 0.0 {
+      h1 + h2
+    } else if (h1 - h2).abs() <= 180.0 {
+      (h1 + h2) / 2.0
+    } else if h1 + h2 < 360.0 {
+      (h1 + h2 + 360.0) / 2.0
+    } else {
+      (h1 + h2 - 360.0) / 2.0
+    };
+
+    let t = 1.0 - 0.17 * (h_mean - 30.0).to_radians().cos()
+      + 0.24 * (2.0 * h_mean).to_radians().cos()
+      + 0.32 * (3.0 * h_mean + 6.0).to_radians().cos()
+      - 0.20 * (4.0 * h_mean - 63.0).to_radians().cos();
+    let delta_theta = 30.0 * (-((h_mean - 275.0) / 25.0).powi(2)).exp();
+    let r_c = 2.0 * (c_mean.powi(7) / (c_mean.powi(7) + pow25_7)).sqrt();
+    let s_l = 1.0 + 0.015 * (l_mean - 50.0).powi(2) / (20.0 + (l_mean - 50.0).powi(2)).sqrt();
+    let s_c = 1.0 + 0.045 * c_mean;
+    let s_h = 1.0 + 0.015 * c_mean * t;
+    let r_t = -(2.0 * delta_theta).to_radians().sin() * r_c;
+
+    let (l, c, h) = (delta_l / s_l, delta_c / s_c, delta_h / s_h);
+    (l * l + c * c + h * h + r_t * c * h).sqrt() as f32
+  }
+}
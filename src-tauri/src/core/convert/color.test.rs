@@ -0,0 +1,45 @@
+use super::*;
+
+fn lab(l: f32, a: f32, b: f32) -> Lab {
+  Lab { l, a, b }
+}
+
+#[test]
+fn converts_rgb_to_lab() {
+  let white = Lab::from_rgb([255, 255, 255]);
+  assert!((white.l - 100.0).abs() < 0.01);
+  assert!(white.a.abs() < 0.01 && white.b.abs() < 0.01);
+
+  let black = Lab::from_rgb([0, 0, 0]);
+  assert!(black.l.abs() < 0.01);
+
+  let red = Lab::from_rgb([255, 0, 0]);
+  assert!((red.l - 53.24).abs() < 0.05);
+  assert!((red.a - 80.09).abs() < 0.05);
+  assert!((red.b - 67.20).abs() < 0.05);
+}
+
+#[test]
+fn parses_hex_colors() {
+  assert_eq!(Lab::from_hex("FF0000"), Some(Lab::from_rgb([255, 0, 0])));
+  assert_eq!(Lab::from_hex("#00ff00"), Some(Lab::from_rgb([0, 255, 0])));
+  assert_eq!(Lab::from_hex("FFF"), None);
+  assert_eq!(Lab::from_hex("GG0000"), None);
+}
+
+#[test]
+fn computes_ciede2000() {
+  // The reference values are taken from the test data by Sharma, Wu and Dalal.
+  let cases = [
+    (lab(50.0, 2.6772, -79.7751), lab(50.0, 0.0, -82.7485), 2.0425),
+    (lab(50.0, 3.1571, -77.2803), lab(50.0, 0.0, -82.7485), 2.8615),
+    (lab(50.0, 2.5, 0.0), lab(73.0, 25.0, -18.0), 27.1492),
+    (lab(50.0, 2.5, 0.0), lab(50.0, 0.0, -2.5), 4.3065),
+    (lab(60.2574, -34.0099, 36.2677), lab(60.4626, -34.1751, 39.4387), 1.2644),
+  ];
+  for (first, second, expected) in cases {
+    assert!((first.delta_e(&second) - expected).abs() < 0.001);
+    assert!((second.delta_e(&first) - expected).abs() < 0.001);
+  }
+  assert_eq!(lab(50.0, 10.0, 10.0).delta_e(&lab(50.0, 10.0, 10.0)), 0.0);
+}
@@ -0,0 +1,203 @@
+use std::collections::HashMap;
+
+use anyhow::{Result, bail};
+use borsh::{BorshDeserialize, BorshSerialize};
+use image::imageops::{self, FilterType};
+
+use super::color::Lab;
+use super::quantize::{map_pixels, select_colors};
+use crate::core::pattern::display::DisplaySettings;
+use crate::core::pattern::*;
+use crate::core::symbols::{SymbolCandidate, assign_symbols};
+
+#[cfg(test)]
+#[path = "convert.test.rs"]
+mod tests;
+
+/// The pixels with lower opacity are considered transparent and are not stitched.
+const MIN_OPACITY: u8 = 128;
+
+/// The maximum width and height of the pattern, in stitches.
+/// Larger patterns take too much memory and time to convert, and are hardly ever stitched.
+pub const MAX_PATTERN_SIZE: u16 = 1000;
+
+/// The size of the pattern to convert the image into.
+#[derive(Debug, Clone, Copy, PartialEq, BorshSerialize, BorshDeserialize)]
+pub enum PatternSize {
+  /// The size in stitches.
+  /// A zero dimension is calculated from the other one to keep the image aspect ratio.
+  Stitches { width: u16, height: u16 },
+  /// The size in inches, which is converted to stitches using the fabric count.
+  /// A zero dimension is calculated from the other one to keep the image aspect ratio.
+  Inches { width: f32, height: f32 },
+}
+
+impl PatternSize {
+  /// Returns the pattern size in stitches for the image of the given size (in pixels).
+  /// Fails if the pattern would be larger than `MAX_PATTERN_SIZE` in any dimension.
+  pub fn to_stitches(&self, (image_width, image_height): (u32, u32), spi: StitchesPerInch) -> Result<(u16, u16)> {
+    let (width, height) = match *self {
+      PatternSize::Stitches { width, height } => (width as f32, height as f32),
+      PatternSize::Inches { width, height } => (width * spi.0 as f32, height * spi.1 as f32),
+    };
+    let ratio = image_width as f32 / image_height as f32;
+    let (width, height) = match (width > 0.0, height > 0.0) {
+      (true, true) => (width, height),
+      (true, false) => (width, width / ratio),
+      (false, true) => (height * ratio, height),
+      (false, false) => bail!("The pattern size must be specified"),
+    };
+
+    let (width, height) = (width.round().max(1.0), height.round().max(1.0));
+    if width > MAX_PATTERN_SIZE as f32 || height > MAX_PATTERN_SIZE as f32 {
+      bail!("The pattern size {width}x{height} exceeds the maximum of {MAX_PATTERN_SIZE}x{MAX_PATTERN_SIZE} stitches");
+    }
+    Ok((width as u16, height as u16))
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct ConversionOptions {
+  pub size: PatternSize,
+  pub spi: StitchesPerInch,
+  /// The maximum number of threads in the palette.
+  pub max_colors: u8,
+  /// Whether to dither the colors to render the gradients more smoothly.
+  pub dithering: bool,
+}
+
+impl Default for ConversionOptions {
+  fn default() -> Self {
+    Self {
+      size: PatternSize::Stitches { width: 100, height: 0 },
+      spi: Fabric::default().spi,
+      max_colors: 20,
+      dithering: false,
+    }
+  }
+}
+
+/// A request to convert the image into a pattern.
+#[derive(Debug, Clone, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct ImageConversion {
+  /// The PNG or JPEG image.
+  pub image: Vec<u8>,
+  /// The name of the thread catalog, e.g., `DMC`.
+  pub catalog: String,
+  pub options: ConversionOptions,
+}
+
+/// Converts the PNG or JPEG image into a pattern of full stitches.
+///
+/// The image is resized to the pattern size, so each pixel becomes a full stitch (or nothing, if it is transparent).
+/// The colors are matched to the threads of the catalog perceptually, using the CIEDE2000 color difference.
+/// The palette is ordered as the catalog and only includes the threads that are actually used.
+/// The symbols are assigned from the given candidates (see `symbol_candidates`).
+pub fn convert_image(
+  bytes: &[u8],
+  catalog: &[PaletteItem],
+  options: &ConversionOptions,
+  candidates: &[SymbolCandidate],
+) -> Result<PatternProject> {
+  if options.max_colors == 0 {
+    bail!("The maximum number of colors must be positive");
+  }
+  let catalog_colors = catalog
+    .iter()
+    .map(|item| {
+      Lab::from_hex(&item.color).ok_or_else(|| {
+        anyhow::anyhow!(
+          "Invalid color {} of the thread {} {}",
+          item.color,
+          item.brand,
+          item.number
+        )
+      })
+    })
+    .collect::<Result<Vec<_>>>()?;
+  if catalog_colors.is_empty() {
+    bail!("The thread catalog is empty");
+  }
+
+  let image = image::load_from_memory(bytes)?.to_rgba8();
+  let (width, height) = options.size.to_stitches(image.dimensions(), options.spi)?;
+  let image = imageops::resize(&image, width as u32, height as u32, FilterType::Triangle);
+
+  // The conversion to Lab is relatively expensive, so it is done once per distinct color.
+  let mut colors = HashMap::<[u8; 3], (Lab, usize)>::new();
+  for pixel in image.pixels().filter(|pixel| pixel[3] >= MIN_OPACITY) {
+    let rgb = [pixel[0], pixel[1], pixel[2]];
+    colors.entry(rgb).or_insert_with(|| (Lab::from_rgb(rgb), 0)).1 += 1;
+  }
+  let pixels = image
+    .pixels()
+    .map(|pixel| {
+      if pixel[3] >= MIN_OPACITY {
+        Some(colors[&[pixel[0], pixel[1], pixel[2]]].0)
+      } else {
+        None
+      }
+    })
+    .collect::<Vec<_>>();
+
+  let selected = select_colors(
+    &colors.into_values().collect::<Vec<_>>(),
+    &catalog_colors,
+    options.max_colors as usize,
+  );
+  let selected_colors = selected.iter().map(|&index| catalog_colors[index]).collect::<Vec<_>>();
+  let mapped = map_pixels(&pixels, width as usize, &selected_colors, options.dithering);
+
+  // Some of the selected threads may end up unused after the dithering, so we drop them.
+  let mut stitch_counts = vec![0; selected.len()];
+  for &index in mapped.iter().flatten() {
+    stitch_counts[index] += 1;
+  }
+  let mut palindexes = vec![None; selected.len()];
+  let mut palette = Vec::new();
+  for (index, &catalog_index) in selected.iter().enumerate() {
+    if stitch_counts[index] > 0 {
+      palindexes[index] = Some(palette.len() as u8);
+      palette.push(PaletteItem {
+        blends: None,
+        bead: None,
+        strands: None,
+        ..catalog[catalog_index].clone()
+      });
+    }
+  }
+
+  let mut pattern = Pattern::new(Fabric {
+    width,
+    height,
+    spi: options.spi,
+    ..Fabric::default()
+  });
+  pattern.fullstitches = mapped
+    .into_iter()
+    .enumerate()
+    .filter_map(|(i, index)| {
+      let palindex = palindexes[index?]?;
+      Some(FullStitch {
+        x: Coord::new((i % width as usize) as f32).unwrap(),
+        y: Coord::new((i / width as usize) as f32).unwrap(),
+        palindex,
+        kind: FullStitchKind::Full,
+      })
+    })
+    .collect();
+  pattern.palette = palette;
+
+  let mut display_settings = DisplaySettings::new(pattern.palette.len());
+  let all_palindexes = (0..pattern.palette.len() as u8).collect::<Vec<_>>();
+  let usage = pattern.stitch_counts();
+  for (palindex, symbols) in assign_symbols(candidates, &display_settings.symbols, &usage, &all_palindexes) {
+    display_settings.symbols[palindex as usize] = symbols;
+  }
+
+  Ok(PatternProject {
+    pattern,
+    display_settings,
+    ..PatternProject::default()
+  })
+}
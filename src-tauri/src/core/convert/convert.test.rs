@@ -0,0 +1,190 @@
+use std::io::Cursor;
+
+use image::{ImageFormat, Rgba, RgbaImage};
+
+use super::*;
+
+fn encode(image: RgbaImage, format: ImageFormat) -> Vec<u8> {
+  let mut bytes = Vec::new();
+  image.write_to(&mut Cursor::new(&mut bytes), format).unwrap();
+  bytes
+}
+
+fn thread(number: &str, color: &str) -> PaletteItem {
+  PaletteItem {
+    brand: String::from("DMC"),
+    number: String::from(number),
+    name: String::new(),
+    color: String::from(color),
+    blends: None,
+    bead: None,
+    strands: None,
+  }
+}
+
+fn catalog() -> Vec<PaletteItem> {
+  vec![
+    thread("310", "000000"),
+    thread("321", "C72B3B"),
+    thread("699", "056517"),
+    thread("820", "0E365C"),
+    thread("B5200", "FFFFFF"),
+  ]
+}
+
+fn candidates(count: u16) -> Vec<SymbolCandidate> {
+  (0..count)
+    .map(|i| SymbolCandidate {
+      code_point: 65 + i,
+      glyph_id: i as u32 + 1,
+      density: 1.0 - i as f32 / 100.0,
+    })
+    .collect()
+}
+
+fn options(width: u16, height: u16) -> ConversionOptions {
+  ConversionOptions {
+    size: PatternSize::Stitches { width, height },
+    ..ConversionOptions::default()
+  }
+}
+
+/// The first three columns are red and the last one is white, so the red is the dominant color.
+fn two_color_image() -> RgbaImage {
+  RgbaImage::from_fn(4, 2, |x, _| {
+    if x < 3 {
+      Rgba([200, 40, 50, 255])
+    } else {
+      Rgba([250, 250, 250, 255])
+    }
+  })
+}
+
+#[test]
+fn calculates_pattern_size() {
+  let stitches = |width, height| PatternSize::Stitches { width, height };
+  assert_eq!(stitches(50, 40).to_stitches((200, 100), (14, 14)).unwrap(), (50, 40));
+  assert_eq!(stitches(50, 0).to_stitches((200, 100), (14, 14)).unwrap(), (50, 25));
+  assert_eq!(stitches(0, 50).to_stitches((200, 100), (14, 14)).unwrap(), (100, 50));
+  assert!(stitches(0, 0).to_stitches((200, 100), (14, 14)).is_err());
+
+  let inches = |width, height| PatternSize::Inches { width, height };
+  assert_eq!(inches(5.0, 0.0).to_stitches((200, 100), (14, 14)).unwrap(), (70, 35));
+  assert_eq!(inches(2.0, 1.0).to_stitches((200, 100), (18, 16)).unwrap(), (36, 16));
+  assert!(inches(10000.0, 0.0).to_stitches((200, 100), (14, 14)).is_err());
+}
+
+#[test]
+fn caps_pattern_size() {
+  let stitches = |width, height| PatternSize::Stitches { width, height };
+  let max = MAX_PATTERN_SIZE;
+  assert_eq!(
+    stitches(max, max).to_stitches((200, 100), (14, 14)).unwrap(),
+    (max, max)
+  );
+  assert!(stitches(max + 1, 10).to_stitches((200, 100), (14, 14)).is_err());
+  assert!(stitches(10, u16::MAX).to_stitches((200, 100), (14, 14)).is_err());
+  // The other dimension is calculated from the aspect ratio, so it exceeds the maximum as well.
+  assert!(stitches(0, max).to_stitches((200, 100), (14, 14)).is_err());
+
+  let inches = |width, height| PatternSize::Inches { width, height };
+  assert!(inches(100.0, 0.0).to_stitches((200, 100), (14, 14)).is_err());
+}
+
+#[test]
+fn converts_image_into_pattern() {
+  let image = encode(two_color_image(), ImageFormat::Png);
+  let patproj = convert_image(&image, &catalog(), &options(4, 2), &candidates(10)).unwrap();
+  let pattern = &patproj.pattern;
+
+  assert_eq!((pattern.fabric.width, pattern.fabric.height), (4, 2));
+  assert_eq!(pattern.fabric.spi, ConversionOptions::default().spi);
+
+  // The palette only includes the used threads in the catalog order.
+  let numbers = pattern
+    .palette
+    .iter()
+    .map(|item| item.number.as_str())
+    .collect::<Vec<_>>();
+  assert_eq!(numbers, vec!["321", "B5200"]);
+
+  assert_eq!(pattern.fullstitches.len(), 8);
+  let red = FullStitch {
+    x: Coord::new(0.0).unwrap(),
+    y: Coord::new(1.0).unwrap(),
+    palindex: 0,
+    kind: FullStitchKind::Full,
+  };
+  let white = FullStitch {
+    x: Coord::new(3.0).unwrap(),
+    y: Coord::new(0.0).unwrap(),
+    palindex: 1,
+    kind: FullStitchKind::Full,
+  };
+  assert_eq!(pattern.fullstitches.get(&red), Some(&red));
+  assert_eq!(pattern.fullstitches.get(&white), Some(&white));
+
+  // The most used thread gets the most contrasting symbol.
+  assert_eq!(patproj.display_settings.symbols.len(), 2);
+  assert_eq!(patproj.display_settings.symbols[0].full, Some(65));
+  assert_eq!(patproj.display_settings.symbols[1].full, Some(66));
+}
+
+#[test]
+fn limits_number_of_colors() {
+  let image = encode(two_color_image(), ImageFormat::Png);
+  let options = ConversionOptions { max_colors: 1, ..options(4, 2) };
+  let patproj = convert_image(&image, &catalog(), &options, &candidates(10)).unwrap();
+
+  assert_eq!(patproj.pattern.palette.len(), 1);
+  assert_eq!(patproj.pattern.palette[0].number, "321");
+  assert_eq!(patproj.pattern.fullstitches.len(), 8);
+}
+
+#[test]
+fn skips_transparent_pixels() {
+  let image = RgbaImage::from_fn(2, 2, |x, _| {
+    if x == 0 {
+      Rgba([0, 0, 0, 255])
+    } else {
+      Rgba([0, 0, 0, 0])
+    }
+  });
+  let image = encode(image, ImageFormat::Png);
+  let patproj = convert_image(&image, &catalog(), &options(2, 2), &candidates(10)).unwrap();
+
+  assert_eq!(patproj.pattern.palette.len(), 1);
+  assert_eq!(patproj.pattern.palette[0].number, "310");
+  assert_eq!(patproj.pattern.fullstitches.len(), 2);
+  assert!(
+    patproj
+      .pattern
+      .fullstitches
+      .iter()
+      .all(|stitch| stitch.x.into_inner() == 0.0)
+  );
+}
+
+#[test]
+fn converts_jpeg_images() {
+  let image = RgbaImage::from_pixel(8, 8, Rgba([5, 100, 25, 255]));
+  let image = image::DynamicImage::ImageRgba8(image).to_rgb8();
+  let mut bytes = Vec::new();
+  image.write_to(&mut Cursor::new(&mut bytes), ImageFormat::Jpeg).unwrap();
+
+  let patproj = convert_image(&bytes, &catalog(), &options(4, 0), &candidates(10)).unwrap();
+  assert_eq!((patproj.pattern.fabric.width, patproj.pattern.fabric.height), (4, 4));
+  assert_eq!(patproj.pattern.palette.len(), 1);
+  assert_eq!(patproj.pattern.palette[0].number, "699");
+}
+
+#[test]
+fn rejects_invalid_input() {
+  let image = encode(two_color_image(), ImageFormat::Png);
+  assert!(convert_image(b"not an image", &catalog(), &options(4, 2), &[]).is_err());
+  assert!(convert_image(&image, &[], &options(4, 2), &[]).is_err());
+  assert!(convert_image(&image, &[thread("1", "nope")], &options(4, 2), &[]).is_err());
+
+  let options = ConversionOptions { max_colors: 0, ..options(4, 2) };
+  assert!(convert_image(&image, &catalog(), &options, &[]).is_err());
+}
@@ -0,0 +1,11 @@
+//! This module contains the conversion of images into cross-stitch patterns.
+
+mod color;
+pub use color::*;
+
+mod quantize;
+pub use quantize::*;
+
+#[allow(clippy::module_inception)]
+mod convert;
+pub use convert::*;
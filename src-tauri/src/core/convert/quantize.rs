@@ -0,0 +1,117 @@
+//! This module contains the color quantization, i.e. reducing the image colors to a limited set of threads.
+
+use std::collections::BTreeMap;
+
+use super::color::Lab;
+
+#[cfg(test)]
+#[path = "quantize.test.rs"]
+mod tests;
+
+/// The Floyd–Steinberg error distribution: the offsets of the neighbouring pixels and their shares of the error.
+const DITHERING_KERNEL: [(isize, isize, f32); 4] = [
+  (1, 0, 7.0 / 16.0),
+  (-1, 1, 3.0 / 16.0),
+  (0, 1, 5.0 / 16.0),
+  (1, 1, 1.0 / 16.0),
+];
+
+/// Returns the index of the palette color closest to the given one.
+pub fn closest(palette: &[Lab], color: &Lab) -> Option<usize> {
+  palette
+    .iter()
+    .map(|candidate| color.delta_e(candidate))
+    .enumerate()
+    .min_by(|(_, a), (_, b)| a.total_cmp(b))
+    .map(|(index, _)| index)
+}
+
+/// Selects at most `max_colors` threads of the catalog to represent the image colors.
+///
+/// - `colors` are the distinct image colors along with the number of pixels of each color.
+/// - `catalog` are the colors of the catalog threads.
+///
+/// Every image color is matched to the closest thread first.
+/// Then the least used thread is merged into the closest of the remaining ones until there are few enough of them.
+/// Returns the indexes of the selected threads in the catalog order.
+pub fn select_colors(colors: &[(Lab, usize)], catalog: &[Lab], max_colors: usize) -> Vec<usize> {
+  let mut usage = BTreeMap::<usize, usize>::new();
+  for (color, count) in colors {
+    if let Some(index) = closest(catalog, color) {
+      *usage.entry(index).or_default() += count;
+    }
+  }
+
+  while usage.len() > max_colors.max(1) {
+    // Among the equally used threads, the one that comes later in the catalog is merged first.
+    let (&least_used, &count) = usage
+      .iter()
+      .min_by_key(|&(&index, &count)| (count, std::cmp::Reverse(index)))
+      .unwrap();
+    usage.remove(&least_used);
+
+    let remaining = usage.keys().copied().collect::<Vec<_>>();
+    let palette = remaining.iter().map(|&index| catalog[index]).collect::<Vec<_>>();
+    let target = remaining[closest(&palette, &catalog[least_used]).unwrap()];
+    *usage.get_mut(&target).unwrap() += count;
+  }
+
+  usage.into_keys().collect()
+}
+
+/// Maps the image pixels to the palette colors, optionally dithering them.
+///
+/// - `pixels` are the image pixels row by row, where `None` stands for a transparent pixel.
+/// - `width` is the image width in pixels.
+///
+/// The dithering distributes the error of each pixel to its neighbours using the Floyd–Steinberg method.
+/// It is performed in the Lab color space, so the error is measured the same way the colors are matched.
+/// The transparent pixels are kept as is and don't take the error.
+/// Returns the palette indexes of the pixels.
+pub fn map_pixels(pixels: &[Option<Lab>], width: usize, palette: &[Lab], dithering: bool) -> Vec<Option<usize>> {
+  let height = if width == 0 { 0 } else { pixels.len() / width };
+  let mut errors = vec![[0.0f32; 3]; pixels.len()];
+  let mut result = Vec::with_capacity(pixels.len());
+
+  for (i, pixel) in pixels.iter().enumerate() {
+    let Some(pixel) = pixel else {
+      result.push(None);
+      continue;
+    };
+
+    let [dl, da, db] = errors[i];
+    let color = Lab {
+      l: pixel.l + dl,
+      a: pixel.a + da,
+      b: pixel.b + db,
+    };
+    let index = closest(palette, &color);
+    result.push(index);
+    if !dithering {
+      continue;
+    }
+
+    if let Some(index) = index {
+      let error = [
+        color.l - palette[index].l,
+        color.a - palette[index].a,
+        color.b - palette[index].b,
+      ];
+      let (x, y) = ((i % width) as isize, (i / width) as isize);
+      for (dx, dy, share) in DITHERING_KERNEL {
+        let (nx, ny) = (x + dx, y + dy);
+        if nx < 0 || nx >= width as isize || ny >= height as isize {
+          continue;
+        }
+        let neighbour = ny as usize * width + nx as usize;
+        if pixels[neighbour].is_some() {
+          for channel in 0..3 {
+            errors[neighbour][channel] += error[channel] * share;
+          }
+        }
+      }
+    }
+  }
+
+  result
+}
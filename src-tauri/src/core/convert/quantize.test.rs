@@ -0,0 +1,71 @@
+use super::*;
+
+fn rgb(r: u8, g: u8, b: u8) -> Lab {
+  Lab::from_rgb([r, g, b])
+}
+
+#[test]
+fn finds_closest_color() {
+  let palette = [rgb(0, 0, 0), rgb(255, 255, 255), rgb(255, 0, 0)];
+  assert_eq!(closest(&palette, &rgb(20, 20, 20)), Some(0));
+  assert_eq!(closest(&palette, &rgb(240, 240, 240)), Some(1));
+  assert_eq!(closest(&palette, &rgb(200, 30, 30)), Some(2));
+  assert_eq!(closest(&[], &rgb(0, 0, 0)), None);
+}
+
+#[test]
+fn selects_closest_threads() {
+  let catalog = [rgb(255, 0, 0), rgb(0, 255, 0), rgb(0, 0, 255), rgb(255, 255, 255)];
+  let colors = [(rgb(250, 10, 10), 10), (rgb(5, 5, 250), 5), (rgb(245, 5, 5), 3)];
+  assert_eq!(select_colors(&colors, &catalog, 10), vec![0, 2]);
+}
+
+#[test]
+fn merges_least_used_threads() {
+  let catalog = [rgb(255, 0, 0), rgb(200, 0, 0), rgb(0, 0, 255), rgb(255, 255, 255)];
+  let colors = [
+    (rgb(255, 0, 0), 10),
+    (rgb(200, 0, 0), 2),
+    (rgb(0, 0, 255), 8),
+    (rgb(255, 255, 255), 5),
+  ];
+
+  // The dark red is the least used one, so it is merged into the red.
+  assert_eq!(select_colors(&colors, &catalog, 3), vec![0, 2, 3]);
+  // Then the white takes the least pixels.
+  assert_eq!(select_colors(&colors, &catalog, 2), vec![0, 2]);
+  // At least one color is always selected.
+  assert_eq!(select_colors(&colors, &catalog, 0), vec![0]);
+}
+
+#[test]
+fn maps_pixels_without_dithering() {
+  let palette = [rgb(0, 0, 0), rgb(255, 255, 255)];
+  let gray = Some(rgb(100, 100, 100));
+  let pixels = [gray, gray, None, gray];
+  assert_eq!(
+    map_pixels(&pixels, 2, &palette, false),
+    vec![Some(0), Some(0), None, Some(0)]
+  );
+}
+
+#[test]
+fn maps_pixels_with_dithering() {
+  let palette = [rgb(0, 0, 0), rgb(255, 255, 255)];
+  let gray = Some(rgb(119, 119, 119));
+  let pixels = vec![gray; 16];
+  let mapped = map_pixels(&pixels, 4, &palette, true);
+
+  // The mid gray is rendered as a mix of black and white instead of the solid black.
+  let white = mapped.iter().filter(|&&index| index == Some(1)).count();
+  assert!(white > 0 && white < 16);
+}
+
+#[test]
+fn keeps_transparent_pixels_when_dithering() {
+  let palette = [rgb(0, 0, 0), rgb(255, 255, 255)];
+  let pixels = [Some(rgb(119, 119, 119)), None, None, Some(rgb(119, 119, 119))];
+  let mapped = map_pixels(&pixels, 2, &palette, true);
+  assert_eq!(mapped[1], None);
+  assert_eq!(mapped[2], None);
+}
@@ -0,0 +1,204 @@
+//! This module contains the utilities to inspect the stitch fonts.
+//! Stitch fonts are regular TrueType fonts whose glyphs are used as the symbols of the palette items.
+
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::Result;
+use borsh::BorshSerialize;
+use font_kit::canvas::{Canvas, Format, RasterizationOptions};
+use font_kit::family_name::FamilyName;
+use font_kit::font::Font;
+use font_kit::hinting::HintingOptions;
+use font_kit::properties::Properties;
+use font_kit::source::SystemSource;
+use pathfinder_geometry::transform2d::Transform2F;
+use pathfinder_geometry::vector::{Vector2F, Vector2I};
+
+use crate::utils::path::stitch_font_path;
+
+#[cfg(test)]
+#[path = "fonts.test.rs"]
+mod tests;
+
+/// The size (in pixels) at which the glyphs are rasterized to measure them.
+const MEASURE_SIZE: f32 = 32.0;
+
+pub struct StitchFont {
+  font: Font,
+}
+
+impl StitchFont {
+  pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self> {
+    Self::from_bytes(std::fs::read(path)?)
+  }
+
+  pub fn from_bytes(bytes: Vec<u8>) -> Result<Self> {
+    Ok(Self {
+      font: Font::from_bytes(Arc::new(bytes), 0)?,
+    })
+  }
+
+  /// Loads the font of the given family installed in the system.
+  pub fn from_system(font_family: &str) -> Result<Self> {
+    let handle =
+      SystemSource::new().select_best_match(&[FamilyName::Title(font_family.to_string())], &Properties::new())?;
+    Ok(Self { font: handle.load()? })
+  }
+
+  /// Loads the bundled stitch font of the given family, falling back to the system font.
+  pub fn resolve<R: tauri::Runtime>(app_handle: &tauri::AppHandle<R>, font_family: &str) -> Result<Self> {
+    let font_path = stitch_font_path(app_handle, font_family)?;
+    if font_path.exists() {
+      Self::from_path(font_path)
+    } else {
+      Self::from_system(font_family)
+    }
+  }
+
+  pub fn family_name(&self) -> String {
+    self.font.family_name()
+  }
+
+  /// Returns the code points that have a glyph in the font along with their glyph IDs.
+  /// Whitespace and control characters are skipped since they can't be used as symbols.
+  pub fn code_points(&self) -> Vec<(u16, u32)> {
+    // Symbols are stored as `u16`, so we only need to look at the Basic Multilingual Plane.
+    (0..=u16::MAX)
+      .filter_map(|code_point| {
+        let ch = char::from_u32(code_point as u32)?;
+        if ch.is_whitespace() || ch.is_control() {
+          return None;
+        }
+        // The glyph `0` is always the `.notdef` glyph.
+        let glyph_id = self.font.glyph_for_char(ch).filter(|&glyph_id| glyph_id != 0)?;
+        Some((code_point, glyph_id))
+      })
+      .collect()
+  }
+
+  /// Returns the share of the em square covered by the glyph ink, in the range `[0, 1]`.
+  /// The denser the glyph, the more it contrasts with the background.
+  pub fn ink_density(&self, glyph_id: u32) -> Result<f32> {
+    let canvas = self.rasterize(glyph_id, MEASURE_SIZE)?;
+    let ink = canvas.pixels.iter().map(|&pixel| pixel as f32 / 255.0).sum::<f32>();
+    Ok((ink / (MEASURE_SIZE * MEASURE_SIZE)).min(1.0))
+  }
+
+  /// Describes the font and all its glyphs that can be used as symbols.
+  /// If `preview_size` is specified, the glyphs are also rendered into square previews of that size (in pixels).
+  pub fn describe(&self, preview_size: Option<u32>) -> Result<FontDescription> {
+    let metrics = self.font.metrics();
+    let mut glyphs = Vec::new();
+    for (code_point, glyph_id) in self.code_points() {
+      let advance = self.font.advance(glyph_id)?;
+      let bounds = self.font.typographic_bounds(glyph_id)?;
+      let preview = match preview_size {
+        Some(size) => Some(self.render_preview(glyph_id, size)?),
+        None => None,
+      };
+      glyphs.push(GlyphDescription {
+        code_point,
+        glyph_id,
+        advance: advance.x(),
+        bounds: GlyphBounds {
+          x: bounds.origin_x(),
+          y: bounds.origin_y(),
+          width: bounds.width(),
+          height: bounds.height(),
+        },
+        preview,
+      });
+    }
+    Ok(FontDescription {
+      family_name: self.family_name(),
+      units_per_em: metrics.units_per_em,
+      ascent: metrics.ascent,
+      descent: metrics.descent,
+      glyphs,
+    })
+  }
+
+  /// Renders the glyph into a square grayscale image as it would be laid out in a text line.
+  /// The glyph is centered horizontally and its baseline is placed according to the font ascent.
+  pub fn render_preview(&self, glyph_id: u32, size: u32) -> Result<GlyphPreview> {
+    let metrics = self.font.metrics();
+    let scale = size as f32 / metrics.units_per_em as f32;
+    let advance = self.font.advance(glyph_id)?.x() * scale;
+    let baseline = metrics.ascent * scale;
+
+    let mut canvas = Canvas::new(Vector2I::splat(size as i32), Format::A8);
+    self.font.rasterize_glyph(
+      &mut canvas,
+      glyph_id,
+      size as f32,
+      Transform2F::from_translation(Vector2F::new((size as f32 - advance) / 2.0, baseline)),
+      HintingOptions::None,
+      RasterizationOptions::GrayscaleAa,
+    )?;
+    Ok(GlyphPreview {
+      width: size,
+      height: size,
+      pixels: canvas.pixels,
+    })
+  }
+
+  /// Rasterizes the glyph into a grayscale canvas that fits its ink bounds.
+  fn rasterize(&self, glyph_id: u32, size: f32) -> Result<Canvas> {
+    let bounds = self.font.raster_bounds(
+      glyph_id,
+      size,
+      Transform2F::default(),
+      HintingOptions::None,
+      RasterizationOptions::GrayscaleAa,
+    )?;
+    let mut canvas = Canvas::new(bounds.size(), Format::A8);
+    if bounds.width() > 0 && bounds.height() > 0 {
+      self.font.rasterize_glyph(
+        &mut canvas,
+        glyph_id,
+        size,
+        Transform2F::from_translation(-bounds.origin().to_f32()),
+        HintingOptions::None,
+        RasterizationOptions::GrayscaleAa,
+      )?;
+    }
+    Ok(canvas)
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, BorshSerialize)]
+pub struct FontDescription {
+  pub family_name: String,
+  pub units_per_em: u32,
+  pub ascent: f32,
+  pub descent: f32,
+  pub glyphs: Vec<GlyphDescription>,
+}
+
+/// A glyph of the font along with its metrics in font units.
+#[derive(Debug, Clone, PartialEq, BorshSerialize)]
+pub struct GlyphDescription {
+  pub code_point: u16,
+  pub glyph_id: u32,
+  pub advance: f32,
+  pub bounds: GlyphBounds,
+  pub preview: Option<GlyphPreview>,
+}
+
+/// The bounding box of the glyph outline relative to its origin on the baseline.
+#[derive(Debug, Clone, PartialEq, BorshSerialize)]
+pub struct GlyphBounds {
+  pub x: f32,
+  pub y: f32,
+  pub width: f32,
+  pub height: f32,
+}
+
+/// A grayscale image of the glyph, one byte per pixel, row by row.
+#[derive(Debug, Clone, PartialEq, BorshSerialize)]
+pub struct GlyphPreview {
+  pub width: u32,
+  pub height: u32,
+  pub pixels: Vec<u8>,
+}
@@ -0,0 +1,46 @@
+use super::*;
+
+fn load_font() -> StitchFont {
+  let font_path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("resources/fonts/ursasoftware.ttf");
+  StitchFont::from_path(font_path).unwrap()
+}
+
+#[test]
+fn test_code_points() {
+  let font = load_font();
+  let code_points = font.code_points();
+
+  assert!(!code_points.is_empty());
+  assert!(code_points.iter().all(|&(_, glyph_id)| glyph_id != 0));
+  assert!(
+    code_points
+      .iter()
+      .all(|&(code_point, _)| !char::from_u32(code_point as u32).unwrap().is_whitespace())
+  );
+}
+
+#[test]
+fn test_describe() {
+  let font = load_font();
+
+  let description = font.describe(None).unwrap();
+  assert!(description.units_per_em > 0);
+  assert_eq!(description.glyphs.len(), font.code_points().len());
+  assert!(description.glyphs.iter().all(|glyph| glyph.preview.is_none()));
+
+  let description = font.describe(Some(16)).unwrap();
+  for glyph in description.glyphs.iter() {
+    let preview = glyph.preview.as_ref().unwrap();
+    assert_eq!((preview.width, preview.height), (16, 16));
+    assert_eq!(preview.pixels.len(), 16 * 16);
+  }
+}
+
+#[test]
+fn test_ink_density() {
+  let font = load_font();
+  for (_, glyph_id) in font.code_points() {
+    let density = font.ink_density(glyph_id).unwrap();
+    assert!((0.0..=1.0).contains(&density));
+  }
+}
@@ -1,29 +1,225 @@
 //! This module contains the definition of a history of actions.
 //! The history is stored per pattern project.
 
-use super::actions::Action;
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use tauri::{Emitter, WebviewWindow};
+
+use super::actions::{Action, ActionDescription, ActionRegistry, CompositeAction, EncodedAction};
+use crate::state::PatternKey;
 
 #[cfg(test)]
 #[path = "history.test.rs"]
 mod tests;
 
+/// The maximum delay between two similar actions to coalesce them into a single undo step.
+const COALESCE_TIMEOUT: Duration = Duration::from_millis(1000);
+
+/// The limits of the history size.
+/// When any of them is exceeded, the oldest actions are evicted from the undo stack.
+/// The most recent step is always kept, even if it exceeds the limits alone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HistoryLimits {
+  /// The maximum number of undo steps.
+  pub max_depth: usize,
+  /// The approximate maximum amount of memory, in bytes, occupied by the undo steps.
+  pub max_memory: usize,
+}
+
+impl Default for HistoryLimits {
+  fn default() -> Self {
+    Self {
+      max_depth: 500,
+      max_memory: 128 * 1024 * 1024,
+    }
+  }
+}
+
 /// A history of actions.
 pub struct History<R: tauri::Runtime> {
   undo_stack: Vec<Box<dyn Action<R>>>,
   redo_stack: Vec<Box<dyn Action<R>>>,
+  /// The size of the undo stack at the moment the pattern was saved.
+  /// It is `None` when the saved state can't be reached anymore, i.e. it was in the cleared redo stack.
+  saved_position: Option<usize>,
+  /// The dirty state that was reported to the frontend the last time.
+  reported_dirty: bool,
+  /// The actions of the currently open transaction.
+  transaction: Option<Transaction<R>>,
+  /// The time the last action was pushed.
+  /// It is `None` when the last action must not be coalesced with the next one.
+  last_pushed_at: Option<Instant>,
+  limits: HistoryLimits,
+}
+
+/// A group of actions that should be undone and redone as a single one.
+struct Transaction<R: tauri::Runtime> {
+  actions: Vec<Box<dyn Action<R>>>,
+  /// The number of nested transactions that are not committed yet.
+  depth: usize,
 }
 
 impl<R: tauri::Runtime> History<R> {
+  pub fn new(limits: HistoryLimits) -> Self {
+    Self {
+      undo_stack: Vec::new(),
+      redo_stack: Vec::new(),
+      saved_position: Some(0),
+      reported_dirty: false,
+      transaction: None,
+      last_pushed_at: None,
+      limits,
+    }
+  }
+
+  /// Creates an empty history of a pattern that differs from its file, e.g. a pattern restored from a snapshot.
+  /// Since there is no saved state to return to, the pattern stays dirty until it is saved.
+  pub fn unsaved(limits: HistoryLimits) -> Self {
+    Self {
+      saved_position: None,
+      ..Self::new(limits)
+    }
+  }
+
   /// Add an action object to the history.
   /// This pushes the action object to the undo stack and clears the redo stack.
+  ///
+  /// If a transaction is open, the action is added to it instead.
+  /// If the action is similar to the previous one and was pushed shortly after it, they are coalesced.
+  /// The oldest actions are evicted if the history exceeds its limits.
   pub fn push(&mut self, action: Box<dyn Action<R>>) {
-    self.undo_stack.push(action);
+    if self
+      .saved_position
+      .is_some_and(|position| position > self.undo_stack.len())
+    {
+      self.saved_position = None;
+    }
     self.redo_stack.clear();
+
+    let now = Instant::now();
+    let can_coalesce = self
+      .last_pushed_at
+      .is_some_and(|pushed_at| now.duration_since(pushed_at) <= COALESCE_TIMEOUT);
+    self.last_pushed_at = Some(now);
+
+    if let Some(transaction) = self.transaction.as_mut() {
+      let coalesced = can_coalesce
+        && transaction
+          .actions
+          .last_mut()
+          .is_some_and(|last| last.coalesce(action.as_ref()));
+      if !coalesced {
+        transaction.actions.push(action);
+      }
+      return;
+    }
+
+    // The saved state must stay reachable, so the action on top of it is never coalesced.
+    let on_saved_position = self.saved_position == Some(self.undo_stack.len());
+    if can_coalesce
+      && !on_saved_position
+      && self
+        .undo_stack
+        .last_mut()
+        .is_some_and(|last| last.coalesce(action.as_ref()))
+    {
+      self.evict();
+      return;
+    }
+
+    self.undo_stack.push(action);
+    self.evict();
+  }
+
+  /// Convert the history into a form that can be stored in the project file.
+  /// The actions of an open transaction are stored as a single undo step.
+  pub fn persist(&self) -> Result<PersistedHistory> {
+    let mut undo_stack = self
+      .undo_stack
+      .iter()
+      .map(|action| action.encode())
+      .collect::<Result<Vec<_>>>()?;
+    if let Some(transaction) = self
+      .transaction
+      .as_ref()
+      .filter(|transaction| !transaction.actions.is_empty())
+    {
+      undo_stack.push(CompositeAction::new(transaction.actions.clone()).encode()?);
+    }
+    let redo_stack = self
+      .redo_stack
+      .iter()
+      .map(|action| action.encode())
+      .collect::<Result<Vec<_>>>()?;
+    Ok(PersistedHistory { undo_stack, redo_stack })
+  }
+
+  /// Rebuild the history from its persisted form.
+  /// The restored state is considered saved, since it is the state the history was persisted with.
+  pub fn restore(persisted: &PersistedHistory, registry: &ActionRegistry<R>, limits: HistoryLimits) -> Result<Self> {
+    let mut history = Self::new(limits);
+    history.undo_stack = persisted
+      .undo_stack
+      .iter()
+      .map(|action| registry.decode(action))
+      .collect::<Result<Vec<_>>>()?;
+    history.redo_stack = persisted
+      .redo_stack
+      .iter()
+      .map(|action| registry.decode(action))
+      .collect::<Result<Vec<_>>>()?;
+    history.mark_saved();
+    history.evict();
+    Ok(history)
+  }
+
+  /// Update the limits of the history and evict the actions that don't fit into them anymore.
+  pub fn set_limits(&mut self, limits: HistoryLimits) {
+    self.limits = limits;
+    self.evict();
+  }
+
+  /// Returns the approximate amount of memory occupied by the undo stack in bytes.
+  ///
+  /// The redo stack is not counted, since only the undo steps are evicted.
+  /// It only consists of the undone steps, which have already fitted into the budget, and is cleared by new actions.
+  pub fn memory_usage(&self) -> usize {
+    self.undo_stack.iter().map(|action| action.size()).sum()
+  }
+
+  /// Remove the oldest actions from the undo stack until the history fits into its limits.
+  fn evict(&mut self) {
+    let mut memory_usage = self.memory_usage();
+    let mut evicted = 0;
+    for action in self.undo_stack.iter() {
+      let remaining = self.undo_stack.len() - evicted;
+      let exceeds_depth = remaining > self.limits.max_depth;
+      let exceeds_memory = memory_usage > self.limits.max_memory;
+      if remaining <= 1 || (!exceeds_depth && !exceeds_memory) {
+        break;
+      }
+      memory_usage -= action.size();
+      evicted += 1;
+    }
+    if evicted == 0 {
+      return;
+    }
+
+    self.undo_stack.drain(..evicted);
+    // The saved state is reachable only if it is not older than the oldest remaining action.
+    self.saved_position = self.saved_position.and_then(|position| position.checked_sub(evicted));
   }
 
   /// Get the last action object from the undo stack.
   /// This pops the action object from the undo stack and pushes it to the redo stack, then returns it.
+  ///
+  /// An open transaction is committed beforehand.
   pub fn undo(&mut self) -> Option<Box<dyn Action<R>>> {
+    self.finish_transaction();
+    self.last_pushed_at = None;
     self.undo_stack.pop().inspect(|action| {
       self.redo_stack.push(action.clone());
     })
@@ -31,18 +227,242 @@ impl<R: tauri::Runtime> History<R> {
 
   /// Get the last action object from the redo stack.
   /// This pops the action object from the redo stack and pushes it to the undo stack, then returns it.
+  ///
+  /// An open transaction is committed beforehand.
   pub fn redo(&mut self) -> Option<Box<dyn Action<R>>> {
+    self.finish_transaction();
+    self.last_pushed_at = None;
     self.redo_stack.pop().inspect(|action| {
       self.undo_stack.push(action.clone());
     })
   }
+
+  /// Undo or redo as many actions as needed to reach the state after the given number of actions in the history timeline.
+  /// Every action is passed to `apply` to be revoked or performed, and it is moved between the stacks only if that succeeds.
+  /// So, if an action fails, the history stops at the last applied one and still matches the pattern.
+  ///
+  /// An open transaction is committed beforehand.
+  pub fn jump_to<F>(&mut self, position: usize, mut apply: F) -> Result<()>
+  where
+    F: FnMut(HistoryStep<'_, R>) -> Result<()>,
+  {
+    self.finish_transaction();
+    self.last_pushed_at = None;
+
+    let total = self.undo_stack.len() + self.redo_stack.len();
+    if position > total {
+      anyhow::bail!("The history position {position} is out of range (0..={total})");
+    }
+
+    while self.undo_stack.len() > position {
+      apply(HistoryStep::Undo(self.undo_stack.last().unwrap().as_ref()))?;
+      self.redo_stack.extend(self.undo_stack.pop());
+    }
+    while self.undo_stack.len() < position {
+      apply(HistoryStep::Redo(self.redo_stack.last().unwrap().as_ref()))?;
+      self.undo_stack.extend(self.redo_stack.pop());
+    }
+    Ok(())
+  }
+
+  /// Describe the history as a timeline of actions.
+  /// The actions of an open transaction are described as a single one.
+  pub fn describe(&self) -> HistoryDescription {
+    let mut actions = self
+      .undo_stack
+      .iter()
+      .map(|action| action.describe())
+      .collect::<Vec<_>>();
+    if let Some(transaction) = self
+      .transaction
+      .as_ref()
+      .filter(|transaction| !transaction.actions.is_empty())
+    {
+      actions.push(CompositeAction::new(transaction.actions.clone()).describe());
+    }
+    let position = actions.len() as u32;
+    actions.extend(self.redo_stack.iter().rev().map(|action| action.describe()));
+    HistoryDescription { actions, position }
+  }
+
+  /// Start a transaction.
+  /// All the actions pushed until the transaction is committed are grouped into a single undo step.
+  ///
+  /// Transactions can be nested, in which case the actions are grouped when the outermost transaction is committed.
+  pub fn begin_transaction(&mut self) {
+    match self.transaction.as_mut() {
+      Some(transaction) => transaction.depth += 1,
+      None => {
+        self.transaction = Some(Transaction { actions: Vec::new(), depth: 1 });
+        self.last_pushed_at = None;
+      }
+    }
+  }
+
+  /// Commit the current transaction.
+  /// It does nothing if there is no open transaction.
+  pub fn commit_transaction(&mut self) {
+    if let Some(transaction) = self.transaction.as_mut() {
+      transaction.depth -= 1;
+      if transaction.depth == 0 {
+        self.finish_transaction();
+      }
+    }
+  }
+
+  /// Whether there is an open transaction.
+  pub fn in_transaction(&self) -> bool {
+    self.transaction.is_some()
+  }
+
+  /// Close the current transaction regardless of its depth and push its actions to the undo stack as a single one.
+  fn finish_transaction(&mut self) {
+    if let Some(transaction) = self.transaction.take() {
+      self.last_pushed_at = None;
+      let mut actions = transaction.actions;
+      let action = match actions.len() {
+        0 => return,
+        1 => actions.pop().unwrap(),
+        _ => Box::new(CompositeAction::new(actions)),
+      };
+      self.push(action);
+      self.last_pushed_at = None;
+    }
+  }
+
+  /// Whether the pattern differs from the last saved state.
+  pub fn is_dirty(&self) -> bool {
+    self.has_pending_actions() || self.saved_position != Some(self.undo_stack.len())
+  }
+
+  /// Remember the current state as the saved one.
+  pub fn mark_saved(&mut self) {
+    self.saved_position = Some(self.undo_stack.len());
+  }
+
+  /// Whether there is an action to undo.
+  pub fn can_undo(&self) -> bool {
+    !self.undo_stack.is_empty() || self.has_pending_actions()
+  }
+
+  /// Whether there is an action to redo.
+  pub fn can_redo(&self) -> bool {
+    !self.redo_stack.is_empty()
+  }
+
+  fn has_pending_actions(&self) -> bool {
+    self
+      .transaction
+      .as_ref()
+      .is_some_and(|transaction| !transaction.actions.is_empty())
+  }
+
+  /// Notify the frontend that the history of the closed pattern is gone.
+  ///
+  /// **Emits:**
+  /// - `history:changed` with the pattern key and no actions to undo and redo.
+  pub fn notify_closed(window: &WebviewWindow<R>, pattern_key: &PatternKey) -> Result<()> {
+    emit_history_changed(
+      window,
+      &HistoryChangedData {
+        pattern_key: pattern_key.clone(),
+        can_undo: false,
+        can_redo: false,
+        undo_action: None,
+        redo_action: None,
+      },
+    )
+  }
+
+  /// Notify the frontend about changes of the history state.
+  /// It should be called after the history has been modified or a pattern has been opened.
+  ///
+  /// **Emits:**
+  /// - `history:changed` with the pattern key, whether there are actions to undo and redo, and their descriptions.
+  /// - `pattern:dirty_changed` with the pattern key and the new dirty state if it has changed since the last notification.
+  pub fn notify(&mut self, window: &WebviewWindow<R>, pattern_key: &PatternKey) -> Result<()> {
+    let undo_action = match self
+      .transaction
+      .as_ref()
+      .filter(|transaction| !transaction.actions.is_empty())
+    {
+      Some(transaction) => Some(CompositeAction::new(transaction.actions.clone()).describe()),
+      None => self.undo_stack.last().map(|action| action.describe()),
+    };
+    emit_history_changed(
+      window,
+      &HistoryChangedData {
+        pattern_key: pattern_key.clone(),
+        can_undo: self.can_undo(),
+        can_redo: self.can_redo(),
+        undo_action,
+        redo_action: self.redo_stack.last().map(|action| action.describe()),
+      },
+    )?;
+
+    let is_dirty = self.is_dirty();
+    if is_dirty != self.reported_dirty {
+      window.emit(
+        "pattern:dirty_changed",
+        STANDARD.encode(borsh::to_vec(&DirtyChangedData {
+          pattern_key: pattern_key.clone(),
+          is_dirty,
+        })?),
+      )?;
+      self.reported_dirty = is_dirty;
+    }
+    Ok(())
+  }
+}
+
+/// A single step to reach a certain state of the history.
+pub enum HistoryStep<'a, R: tauri::Runtime> {
+  /// The action to revoke.
+  Undo(&'a dyn Action<R>),
+  /// The action to perform.
+  Redo(&'a dyn Action<R>),
+}
+
+/// The history as it is displayed to the user.
+#[derive(Debug, Default, Clone, PartialEq, borsh::BorshSerialize)]
+pub struct HistoryDescription {
+  /// All the actions in the chronological order.
+  pub actions: Vec<ActionDescription>,
+  /// The number of the applied actions, i.e. the actions before this position can be undone and the rest can be redone.
+  pub position: u32,
+}
+
+/// The serializable form of the history that is stored in the project file.
+#[derive(Debug, Clone, PartialEq, borsh::BorshSerialize, borsh::BorshDeserialize)]
+pub struct PersistedHistory {
+  undo_stack: Vec<EncodedAction>,
+  redo_stack: Vec<EncodedAction>,
+}
+
+fn emit_history_changed<R: tauri::Runtime>(window: &WebviewWindow<R>, data: &HistoryChangedData) -> Result<()> {
+  window.emit("history:changed", STANDARD.encode(borsh::to_vec(data)?))?;
+  Ok(())
+}
+
+#[derive(Debug, Clone, borsh::BorshSerialize)]
+struct HistoryChangedData {
+  pattern_key: PatternKey,
+  can_undo: bool,
+  can_redo: bool,
+  /// The description of the action that will be undone next.
+  undo_action: Option<ActionDescription>,
+  /// The description of the action that will be redone next.
+  redo_action: Option<ActionDescription>,
+}
+
+#[derive(Debug, Clone, borsh::BorshSerialize)]
+struct DirtyChangedData {
+  pattern_key: PatternKey,
+  is_dirty: bool,
 }
 
 impl<R: tauri::Runtime> Default for History<R> {
   fn default() -> Self {
-    Self {
-      undo_stack: Vec::new(),
-      redo_stack: Vec::new(),
-    }
+    Self::new(HistoryLimits::default())
   }
 }
@@ -1,7 +1,17 @@
-use tauri::test::MockRuntime;
+use std::time::Duration;
 
-use super::History;
+use anyhow::Result;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use borsh::BorshDeserialize;
+use tauri::test::{MockRuntime, mock_builder};
+use tauri::{Listener, WebviewUrl, WebviewWindow, WebviewWindowBuilder, generate_context};
+
+use super::{History, HistoryLimits, HistoryStep};
 use crate::core::actions::mock::MockAction;
+use crate::core::actions::{Action, ActionDescription, ActionKind, ActionRegistry, CompositeAction, EncodedAction};
+use crate::core::pattern::PatternProject;
+use crate::state::PatternKey;
 
 #[test]
 fn test_push() {
@@ -47,3 +57,398 @@ fn test_redo() {
   assert_eq!(history.redo_stack.len(), 0);
   assert!(history.redo().is_none());
 }
+
+#[test]
+fn test_dirty_tracking() {
+  let mut history = History::<MockRuntime>::default();
+  assert!(!history.is_dirty());
+
+  history.push(Box::new(MockAction));
+  assert!(history.is_dirty());
+
+  history.undo();
+  assert!(!history.is_dirty());
+
+  history.redo();
+  assert!(history.is_dirty());
+
+  history.mark_saved();
+  assert!(!history.is_dirty());
+
+  history.push(Box::new(MockAction));
+  assert!(history.is_dirty());
+
+  history.undo();
+  assert!(!history.is_dirty());
+}
+
+#[test]
+fn test_unreachable_saved_state() {
+  let mut history = History::<MockRuntime>::default();
+  history.push(Box::new(MockAction));
+  history.push(Box::new(MockAction));
+  history.mark_saved();
+
+  // The saved state is now in the redo stack.
+  history.undo();
+  assert!(history.is_dirty());
+
+  // Pushing a new action clears the redo stack, so the saved state can't be reached anymore.
+  history.push(Box::new(MockAction));
+  history.undo();
+  history.undo();
+  assert!(history.is_dirty());
+  history.redo();
+  history.redo();
+  assert!(history.is_dirty());
+}
+
+#[test]
+fn test_unsaved_history() {
+  let mut history = History::<MockRuntime>::unsaved(HistoryLimits::default());
+  assert!(history.is_dirty());
+
+  history.push(Box::new(MockAction));
+  history.undo();
+  assert!(history.is_dirty());
+
+  history.mark_saved();
+  assert!(!history.is_dirty());
+}
+
+#[test]
+fn test_transaction() {
+  let mut history = History::<MockRuntime>::default();
+  history.push(Box::new(MockAction));
+
+  history.begin_transaction();
+  history.push(Box::new(MockAction));
+  history.push(Box::new(MockAction));
+  assert!(history.in_transaction());
+  assert_eq!(history.undo_stack.len(), 1);
+  history.commit_transaction();
+
+  assert!(!history.in_transaction());
+  assert_eq!(history.undo_stack.len(), 2);
+
+  // The whole transaction is undone as a single step.
+  assert!(history.undo().is_some());
+  assert_eq!(history.undo_stack.len(), 1);
+  assert_eq!(history.redo_stack.len(), 1);
+}
+
+#[test]
+fn test_nested_transaction() {
+  let mut history = History::<MockRuntime>::default();
+
+  history.begin_transaction();
+  history.push(Box::new(MockAction));
+  history.begin_transaction();
+  history.push(Box::new(MockAction));
+  history.commit_transaction();
+  assert!(history.in_transaction());
+  history.push(Box::new(MockAction));
+  history.commit_transaction();
+
+  assert!(!history.in_transaction());
+  assert_eq!(history.undo_stack.len(), 1);
+}
+
+#[test]
+fn test_empty_transaction() {
+  let mut history = History::<MockRuntime>::default();
+  history.begin_transaction();
+  history.commit_transaction();
+  assert!(history.undo_stack.is_empty());
+  assert!(!history.is_dirty());
+}
+
+#[test]
+fn test_undo_commits_transaction() {
+  let mut history = History::<MockRuntime>::default();
+  history.begin_transaction();
+  history.push(Box::new(MockAction));
+  history.push(Box::new(MockAction));
+  assert!(history.is_dirty());
+
+  assert!(history.undo().is_some());
+  assert!(!history.in_transaction());
+  assert!(history.undo_stack.is_empty());
+  assert_eq!(history.redo_stack.len(), 1);
+}
+
+#[test]
+fn test_coalesce() {
+  let mut history = History::<MockRuntime>::default();
+  history.push(Box::new(CoalescingAction));
+  history.push(Box::new(CoalescingAction));
+  assert_eq!(history.undo_stack.len(), 1);
+
+  // The action on top of the saved state is kept intact.
+  history.mark_saved();
+  history.push(Box::new(CoalescingAction));
+  assert_eq!(history.undo_stack.len(), 2);
+  assert!(history.is_dirty());
+
+  history.push(Box::new(CoalescingAction));
+  assert_eq!(history.undo_stack.len(), 2);
+
+  // Different actions are not coalesced.
+  history.push(Box::new(MockAction));
+  assert_eq!(history.undo_stack.len(), 3);
+
+  // Actions are not coalesced across undo and redo.
+  history.push(Box::new(CoalescingAction));
+  history.undo();
+  history.redo();
+  history.push(Box::new(CoalescingAction));
+  assert_eq!(history.undo_stack.len(), 5);
+}
+
+#[derive(Clone, borsh::BorshSerialize, borsh::BorshDeserialize)]
+struct CoalescingAction;
+
+impl ActionKind for CoalescingAction {
+  const KIND: &str = "coalescing";
+}
+
+impl Action<MockRuntime> for CoalescingAction {
+  fn perform(&self, _window: &WebviewWindow<MockRuntime>, _patproj: &mut PatternProject) -> Result<()> {
+    Ok(())
+  }
+
+  fn revoke(&self, _window: &WebviewWindow<MockRuntime>, _patproj: &mut PatternProject) -> Result<()> {
+    Ok(())
+  }
+
+  fn coalesce(&mut self, next: &dyn Action<MockRuntime>) -> bool {
+    next.as_any().is::<Self>()
+  }
+
+  fn describe(&self) -> ActionDescription {
+    ActionDescription::new(Self::KIND, "Test action")
+  }
+
+  fn encode(&self) -> Result<EncodedAction> {
+    EncodedAction::new(self)
+  }
+}
+
+#[test]
+fn test_depth_limit() {
+  let mut history = History::<MockRuntime>::new(HistoryLimits {
+    max_depth: 2,
+    ..HistoryLimits::default()
+  });
+  history.push(Box::new(MockAction));
+  history.mark_saved();
+  history.push(Box::new(MockAction));
+  history.push(Box::new(MockAction));
+  assert_eq!(history.undo_stack.len(), 2);
+
+  // The saved state was shifted along with the evicted action.
+  history.undo();
+  history.undo();
+  assert!(!history.is_dirty());
+
+  history.redo();
+  history.redo();
+  history.push(Box::new(MockAction));
+  assert_eq!(history.undo_stack.len(), 2);
+
+  // The saved state has been evicted, so it can't be reached anymore.
+  history.undo();
+  history.undo();
+  assert!(history.is_dirty());
+}
+
+#[test]
+fn test_memory_limit() {
+  let action_size = Action::<MockRuntime>::size(&SizedAction(100));
+  let mut history = History::<MockRuntime>::new(HistoryLimits {
+    max_memory: action_size * 3,
+    ..HistoryLimits::default()
+  });
+
+  for _ in 0..5 {
+    history.push(Box::new(SizedAction(100)));
+  }
+  assert_eq!(history.undo_stack.len(), 3);
+  assert!(history.memory_usage() <= action_size * 3);
+
+  // The most recent action is kept even if it exceeds the budget alone.
+  history.push(Box::new(SizedAction(1000)));
+  assert_eq!(history.undo_stack.len(), 1);
+
+  // The most recent action is kept even if the depth is zero.
+  history.set_limits(HistoryLimits {
+    max_depth: 0,
+    ..HistoryLimits::default()
+  });
+  assert_eq!(history.undo_stack.len(), 1);
+}
+
+#[test]
+fn test_memory_limit_ignores_redo_stack() {
+  let action_size = Action::<MockRuntime>::size(&SizedAction(100));
+  let mut history = History::<MockRuntime>::new(HistoryLimits {
+    max_memory: action_size * 3,
+    ..HistoryLimits::default()
+  });
+  for _ in 0..3 {
+    history.push(Box::new(SizedAction(100)));
+  }
+  history.undo();
+
+  // The remaining undo steps fit into the budget, so the undone step doesn't make them evicted.
+  history.set_limits(HistoryLimits {
+    max_memory: action_size * 2,
+    ..HistoryLimits::default()
+  });
+  assert_eq!(history.undo_stack.len(), 2);
+  assert_eq!(history.redo_stack.len(), 1);
+}
+
+#[derive(Clone, borsh::BorshSerialize, borsh::BorshDeserialize)]
+struct SizedAction(usize);
+
+impl ActionKind for SizedAction {
+  const KIND: &str = "sized";
+}
+
+impl Action<MockRuntime> for SizedAction {
+  fn perform(&self, _window: &WebviewWindow<MockRuntime>, _patproj: &mut PatternProject) -> Result<()> {
+    Ok(())
+  }
+
+  fn revoke(&self, _window: &WebviewWindow<MockRuntime>, _patproj: &mut PatternProject) -> Result<()> {
+    Ok(())
+  }
+
+  fn size(&self) -> usize {
+    self.0
+  }
+
+  fn describe(&self) -> ActionDescription {
+    ActionDescription::new(Self::KIND, "Test action")
+  }
+
+  fn encode(&self) -> Result<EncodedAction> {
+    EncodedAction::new(self)
+  }
+}
+
+#[test]
+fn test_persist_and_restore() {
+  let mut history = History::<MockRuntime>::default();
+  history.push(Box::new(MockAction));
+  history.push(Box::new(MockAction));
+  history.push(Box::new(MockAction));
+  history.undo();
+  history.begin_transaction();
+  history.push(Box::new(MockAction));
+  history.push(Box::new(MockAction));
+
+  let persisted = history.persist().unwrap();
+  let registry = {
+    let mut registry = ActionRegistry::<MockRuntime>::new();
+    registry.register_with(CompositeAction::<MockRuntime>::KIND, CompositeAction::decode);
+    registry.register::<MockAction>();
+    registry
+  };
+  let mut restored = History::restore(&persisted, &registry, HistoryLimits::default()).unwrap();
+
+  // The open transaction is stored as a single undo step.
+  assert_eq!(restored.undo_stack.len(), 3);
+  assert!(restored.redo_stack.is_empty());
+  assert!(!restored.is_dirty());
+
+  restored.undo();
+  assert!(restored.is_dirty());
+}
+
+#[test]
+fn test_describe() {
+  let mut history = History::<MockRuntime>::default();
+  history.push(Box::new(MockAction));
+  history.push(Box::new(MockAction));
+  history.push(Box::new(MockAction));
+  history.undo();
+
+  let description = history.describe();
+  assert_eq!(description.actions.len(), 3);
+  assert_eq!(description.position, 2);
+  assert_eq!(description.actions[0].kind, MockAction::KIND);
+}
+
+#[test]
+fn test_jump_to() {
+  let mut history = History::<MockRuntime>::default();
+  for _ in 0..4 {
+    history.push(Box::new(MockAction));
+  }
+
+  let (mut undone, mut redone) = (0, 0);
+  let mut apply = |step: HistoryStep<'_, MockRuntime>| {
+    match step {
+      HistoryStep::Undo(_) => undone += 1,
+      HistoryStep::Redo(_) => redone += 1,
+    }
+    Ok(())
+  };
+  history.jump_to(1, &mut apply).unwrap();
+  history.jump_to(3, &mut apply).unwrap();
+  assert_eq!((undone, redone), (3, 2));
+  assert_eq!(history.undo_stack.len(), 3);
+  assert_eq!(history.redo_stack.len(), 1);
+
+  assert!(history.jump_to(5, |_| Ok(())).is_err());
+}
+
+#[test]
+fn test_jump_to_stops_at_failed_action() {
+  let mut history = History::<MockRuntime>::default();
+  for _ in 0..4 {
+    history.push(Box::new(MockAction));
+  }
+
+  // The third action to revoke fails, so only the first two ones are moved to the redo stack.
+  let mut applied = 0;
+  let result = history.jump_to(0, |_| {
+    if applied == 2 {
+      anyhow::bail!("The action failed");
+    }
+    applied += 1;
+    Ok(())
+  });
+  assert!(result.is_err());
+  assert_eq!(history.undo_stack.len(), 2);
+  assert_eq!(history.redo_stack.len(), 2);
+}
+
+#[test]
+fn test_notify_history_changed() {
+  let app = mock_builder().build(generate_context!()).unwrap();
+  let window = WebviewWindowBuilder::new(&app, "main", WebviewUrl::default())
+    .build()
+    .unwrap();
+
+  let (sender, receiver) = std::sync::mpsc::channel();
+  window.listen("history:changed", move |e| {
+    let base64: &str = serde_json::from_str(e.payload()).unwrap();
+    sender.send(STANDARD.decode(base64).unwrap()).unwrap();
+  });
+
+  let pattern_key = PatternKey::from("pattern.oxs");
+  let mut history = History::<MockRuntime>::default();
+  history.push(Box::new(MockAction));
+  history.notify(&window, &pattern_key).unwrap();
+
+  let data = receiver.recv_timeout(Duration::from_secs(1)).unwrap();
+  // The payload starts with the pattern key followed by the `can_undo` and `can_redo` flags.
+  let (key, can_undo, can_redo): (PatternKey, bool, bool) =
+    BorshDeserialize::deserialize(&mut data.as_slice()).unwrap();
+  assert_eq!(key, pattern_key);
+  assert!(can_undo);
+  assert!(!can_redo);
+}
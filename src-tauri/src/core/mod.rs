@@ -1,4 +1,8 @@
 pub mod actions;
+pub mod catalog;
+pub mod convert;
+pub mod fonts;
 pub mod history;
 pub mod parser;
 pub mod pattern;
+pub mod symbols;
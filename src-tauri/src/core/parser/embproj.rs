@@ -1,28 +1,46 @@
-use std::io::Write;
+use std::io::{self, Write};
 
 use anyhow::Result;
 
+use crate::core::history::PersistedHistory;
 use crate::core::parser::oxs;
 use crate::core::pattern::PatternProject;
 use crate::display::DisplaySettings;
+use crate::utils::fs::write_atomically;
+
+#[cfg(test)]
+#[path = "embproj.test.rs"]
+mod tests;
+
+/// The archive entry with the undo history.
+const HISTORY_ENTRY: &str = "history.bin";
 
 pub fn parse_pattern(file_path: std::path::PathBuf) -> Result<PatternProject> {
-  log::info!("Parsing the EMBPROJ pattern file");
+  let mut patproj = parse_pattern_from_reader(std::fs::File::open(&file_path)?)?;
+  patproj.file_path = file_path;
+  Ok(patproj)
+}
 
-  let temp = tempfile::Builder::new().tempdir()?;
-  let temp = temp.path();
+/// Parses the EMBPROJ pattern from an arbitrary seekable source (an in-memory buffer, an archive entry, etc.).
+/// The returned pattern project has an empty file path.
+pub fn parse_pattern_from_reader<R: io::Read + io::Seek>(reader: R) -> Result<PatternProject> {
+  log::info!("Parsing the EMBPROJ pattern file");
 
-  // `quick-xml` doesn't support reading from a `ZipFile`'s directly because it doesn't implement `std::io::BufRead` trait,
-  // so we extract all the files to a temporary directory to read them as regular files.
-  zip_extract::extract(std::fs::File::open(&file_path)?, temp, true)?;
+  let mut archive = zip::ZipArchive::new(reader)?;
 
-  let mut patproj = oxs::v1::parse_pattern(temp.join("pattern.oxs"), Default::default())?;
+  // `quick-xml` requires the `std::io::BufRead` trait, which isn't implemented by the `ZipFile`,
+  // so we wrap the archive entries into a buffered reader.
+  let mut patproj =
+    oxs::v1::parse_pattern_from_reader(io::BufReader::new(archive.by_name("pattern.oxs")?), Default::default())?;
   let DisplaySettings {
     display_mode,
     palette_settings,
     grid,
     ..
-  } = oxs::parse_display_settings(temp.join("display_settings.xml"), patproj.pattern.palette.len())?;
+  } = oxs::parse_display_settings_from_reader(
+    io::BufReader::new(archive.by_name("display_settings.xml")?),
+    patproj.pattern.palette.len(),
+  )?;
 
   patproj.display_settings.display_mode = display_mode;
   patproj.display_settings.palette_settings = palette_settings;
@@ -31,14 +49,44 @@ pub fn parse_pattern(file_path: std::path::PathBuf) -> Result<PatternProject> {
   Ok(patproj)
 }
 
-pub fn save_pattern(patproj: &PatternProject, package_info: &tauri::PackageInfo) -> Result<()> {
+/// Parses the undo history stored in the EMBPROJ pattern file.
+/// Returns `None` if the file has no history.
+pub fn parse_history(file_path: &std::path::Path) -> Result<Option<PersistedHistory>> {
+  parse_history_from_reader(std::fs::File::open(file_path)?)
+}
+
+pub fn parse_history_from_reader<R: io::Read + io::Seek>(reader: R) -> Result<Option<PersistedHistory>> {
+  let mut archive = zip::ZipArchive::new(reader)?;
+  let entry = match archive.by_name(HISTORY_ENTRY) {
+    Ok(entry) => entry,
+    Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+    Err(err) => return Err(err.into()),
+  };
+  Ok(Some(borsh::from_reader(&mut io::BufReader::new(entry))?))
+}
+
+/// Saves the EMBPROJ pattern atomically, keeping the given number of its previous versions as backups.
+pub fn save_pattern(
+  patproj: &PatternProject,
+  history: Option<&PersistedHistory>,
+  package_info: &tauri::PackageInfo,
+  backups: usize,
+) -> Result<()> {
   log::info!("Saving the EMBPROJ pattern file");
-  let file = std::fs::OpenOptions::new()
-    .create(true)
-    .write(true)
-    .truncate(true)
-    .open(&patproj.file_path)?;
-  let mut zip = zip::ZipWriter::new(file);
+  write_atomically(&patproj.file_path, backups, |file| {
+    save_pattern_to_writer(file, patproj, history, package_info)
+  })
+}
+
+/// Writes the EMBPROJ archive into an arbitrary seekable destination.
+/// The undo history is stored only if it is provided.
+pub fn save_pattern_to_writer<W: io::Write + io::Seek>(
+  writer: W,
+  patproj: &PatternProject,
+  history: Option<&PersistedHistory>,
+  package_info: &tauri::PackageInfo,
+) -> Result<()> {
+  let mut zip = zip::ZipWriter::new(writer);
   let options = zip::write::SimpleFileOptions::default().compression_method(zip::CompressionMethod::Zstd);
 
   zip.start_file("pattern.oxs", options)?;
@@ -47,6 +95,11 @@ pub fn save_pattern(patproj: &PatternProject, package_info: &tauri::PackageInfo)
   zip.start_file("display_settings.xml", options)?;
   zip.write_all(&oxs::save_display_settings_to_vec(&patproj.display_settings)?)?;
 
+  if let Some(history) = history {
+    zip.start_file(HISTORY_ENTRY, options)?;
+    zip.write_all(&borsh::to_vec(history)?)?;
+  }
+
   zip.finish()?;
   Ok(())
 }
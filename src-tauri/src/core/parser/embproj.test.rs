@@ -0,0 +1,31 @@
+use std::io::Cursor;
+
+use tauri::generate_context;
+use tauri::test::mock_builder;
+
+use super::*;
+
+#[test]
+fn parses_pattern_from_reader() {
+  let app = mock_builder().build(generate_context!()).unwrap();
+  let dir = tempfile::tempdir().unwrap();
+
+  let oxs_path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("testdata/patterns/rainbow.oxs");
+  let mut patproj = oxs::parse_pattern(oxs_path).unwrap();
+  patproj.file_path = dir.path().join("rainbow.embproj");
+  save_pattern(&patproj, None, app.package_info(), 0).unwrap();
+
+  let file_path = patproj.file_path;
+  let from_file = parse_pattern(file_path.clone()).unwrap();
+  let from_reader = parse_pattern_from_reader(Cursor::new(std::fs::read(&file_path).unwrap())).unwrap();
+
+  assert_eq!(from_file.file_path, file_path);
+  assert!(from_reader.file_path.as_os_str().is_empty());
+  // The pattern isn't comparable, so we compare its serialized form, which includes all the stitches.
+  assert_eq!(
+    borsh::to_vec(&from_reader.pattern).unwrap(),
+    borsh::to_vec(&from_file.pattern).unwrap()
+  );
+  assert_eq!(from_reader.display_settings, from_file.display_settings);
+  assert_eq!(from_reader.print_settings, from_file.print_settings);
+}
@@ -1,4 +1,4 @@
-use std::io;
+use std::io::{self, Seek};
 
 use anyhow::{Result, bail};
 use quick_xml::events::{BytesDecl, BytesStart, Event};
@@ -9,10 +9,22 @@ use super::v1;
 use crate::core::pattern::PatternProject;
 use crate::display::*;
 
+#[cfg(test)]
+#[path = "oxs.test.rs"]
+mod tests;
+
 pub fn parse_pattern(file_path: std::path::PathBuf) -> Result<PatternProject> {
+  let mut patproj = parse_pattern_from_reader(std::fs::File::open(&file_path)?)?;
+  patproj.file_path = file_path;
+  Ok(patproj)
+}
+
+/// Parses the OXS pattern from an arbitrary seekable source (an in-memory buffer, an archive entry, etc.).
+/// The returned pattern project has an empty file path.
+pub fn parse_pattern_from_reader<R: io::Read + io::Seek>(reader: R) -> Result<PatternProject> {
   log::info!("Parsing the OXS pattern");
 
-  let mut reader = quick_xml::Reader::from_file(&file_path)?;
+  let mut reader = Reader::from_reader(io::BufReader::new(reader));
   let mut buf = Vec::new();
   let (oxs_version, software) = loop {
     match reader.read_event_into(&mut buf) {
@@ -38,17 +50,21 @@ pub fn parse_pattern(file_path: std::path::PathBuf) -> Result<PatternProject> {
     log::warn!("Unknown OXS version: {uv}");
   }
 
-  let patproj = v1::parse_pattern(file_path.clone(), software)?;
-  Ok(patproj)
+  // The version-specific parser reads the document from the very beginning.
+  let mut reader = reader.into_inner();
+  reader.rewind()?;
+
+  v1::parse_pattern_from_reader(reader, software)
 }
 
-pub fn save_pattern(patproj: &PatternProject, package_info: &tauri::PackageInfo) -> Result<()> {
+/// Saves the OXS pattern atomically, keeping the given number of its previous versions as backups.
+pub fn save_pattern(patproj: &PatternProject, package_info: &tauri::PackageInfo, backups: usize) -> Result<()> {
   log::info!("Saving the OXS pattern");
-  v1::save_pattern(patproj.file_path.clone(), patproj, package_info)
+  v1::save_pattern(patproj.file_path.clone(), patproj, package_info, backups)
 }
 
-pub fn parse_display_settings(file_path: std::path::PathBuf, palette_size: usize) -> Result<DisplaySettings> {
-  let mut reader = Reader::from_file(&file_path)?;
+pub fn parse_display_settings_from_reader<R: io::BufRead>(reader: R, palette_size: usize) -> Result<DisplaySettings> {
+  let mut reader = Reader::from_reader(reader);
   reader.config_mut().expand_empty_elements = true;
   reader.config_mut().check_end_names = true;
   reader.config_mut().trim_text(true);
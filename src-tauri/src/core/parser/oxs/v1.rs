@@ -10,11 +10,14 @@ use super::utils::*;
 use crate::core::pattern::*;
 use crate::display::{DisplaySettings, Formats, Symbols};
 use crate::print::PrintSettings;
+use crate::utils::fs::write_atomically;
 
-pub fn parse_pattern(file_path: std::path::PathBuf, software: Software) -> Result<PatternProject> {
+/// Parses the OXS v1 pattern from a buffered source.
+/// The returned pattern project has an empty file path.
+pub fn parse_pattern_from_reader<R: io::BufRead>(reader: R, software: Software) -> Result<PatternProject> {
   log::trace!("OXS version is 1.x in the {software:?} edition");
 
-  let mut reader = Reader::from_file(&file_path)?;
+  let mut reader = Reader::from_reader(reader);
   reader.config_mut().expand_empty_elements = true;
   reader.config_mut().check_end_names = true;
   reader.config_mut().trim_text(true);
@@ -81,7 +84,7 @@ pub fn parse_pattern(file_path: std::path::PathBuf, software: Software) -> Resul
   }
 
   Ok(PatternProject {
-    file_path,
+    file_path: Default::default(),
     pattern,
     display_settings,
     print_settings: PrintSettings::default(),
@@ -92,13 +95,11 @@ pub fn save_pattern(
   file_path: std::path::PathBuf,
   patproj: &PatternProject,
   package_info: &tauri::PackageInfo,
+  backups: usize,
 ) -> Result<()> {
-  let mut file = std::fs::OpenOptions::new()
-    .create(true)
-    .write(true)
-    .truncate(true)
-    .open(file_path)?;
-  save_pattern_inner(&mut file, patproj, package_info)
+  write_atomically(&file_path, backups, |file| {
+    save_pattern_inner(file, patproj, package_info)
+  })
 }
 
 pub fn save_pattern_to_vec(patproj: &PatternProject, package_info: &tauri::PackageInfo) -> Result<Vec<u8>> {
@@ -161,6 +162,20 @@ fn read_pattern_properties(attributes: &MapAttributes) -> Result<(u16, u16, Patt
     company: attributes.get("company").unwrap_or(&String::new()).to_owned(),
     copyright: attributes.get("copyright").unwrap_or(&String::new()).to_owned(),
     description: attributes.get("instructions").unwrap_or(&String::new()).to_owned(),
+    keywords: attributes
+      .get("keywords")
+      .map(|keywords| {
+        keywords
+          .split(',')
+          .map(|keyword| keyword.trim().to_owned())
+          .filter(|keyword| !keyword.is_empty())
+          .collect()
+      })
+      .unwrap_or_default(),
+    source: attributes.get("source").unwrap_or(&String::new()).to_owned(),
+    license: attributes.get("license").unwrap_or(&String::new()).to_owned(),
+    created_at: attributes.get("created").and_then(|value| value.parse().ok()),
+    modified_at: attributes.get("modified").and_then(|value| value.parse().ok()),
   };
 
   let spi = (
@@ -199,6 +214,25 @@ fn write_pattern_properties<W: io::Write>(
       ("stitchesperinch_y", spi.1.to_string().as_str()),
       ("palettecount", palette_size.to_string().as_str()),
     ])
+    // The extended metadata isn't a part of the OXS specification, so we write only the fields that are set.
+    .with_attributes(
+      [
+        ("keywords", info.keywords.join(", ")),
+        ("source", info.source.clone()),
+        ("license", info.license.clone()),
+        (
+          "created",
+          info.created_at.map(|value| value.to_string()).unwrap_or_default(),
+        ),
+        (
+          "modified",
+          info.modified_at.map(|value| value.to_string()).unwrap_or_default(),
+        ),
+      ]
+      .iter()
+      .filter(|(_, value)| !value.is_empty())
+      .map(|(key, value)| (*key, value.as_str())),
+    )
     .write_empty()?;
   Ok(())
 }
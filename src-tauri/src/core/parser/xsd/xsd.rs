@@ -54,61 +54,68 @@ const SPECIAL_STITCH_NAME_LENGTH: usize = 255;
 
 pub fn parse_pattern(file_path: std::path::PathBuf) -> Result<PatternProject> {
   log::info!("Parsing the XSD pattern file");
+  // The parser does a lot of small reads and seeks, so it is faster to read the whole file into memory first.
   let buf = std::fs::read(&file_path)?;
-  let mut cursor = std::io::Cursor::new(buf);
+  let mut patproj = parse_pattern_from_reader(std::io::Cursor::new(buf))?;
+  patproj.file_path = file_path;
+  Ok(patproj)
+}
 
-  let signature = read_signature(&mut cursor)?;
+/// Parses the XSD pattern from an arbitrary seekable source (an in-memory buffer, an archive entry, etc.).
+/// The returned pattern project has an empty file path.
+pub fn parse_pattern_from_reader<R: Read + Seek>(mut reader: R) -> Result<PatternProject> {
+  let signature = read_signature(&mut reader)?;
   if signature != VALID_SIGNATURE {
     log::error!("The file has an invalid signature. Expected {VALID_SIGNATURE:#06X}, but got {signature:#06X}");
     anyhow::bail!("The signature of Pattern Maker v4 is incorrect");
   }
 
-  cursor.seek_relative(739)?; // Skip the unknown data.
+  reader.seek_relative(739)?; // Skip the unknown data.
 
-  let pattern_width = cursor.read_u16::<LittleEndian>()?;
-  let pattern_height = cursor.read_u16::<LittleEndian>()?;
+  let pattern_width = reader.read_u16::<LittleEndian>()?;
+  let pattern_height = reader.read_u16::<LittleEndian>()?;
 
   let total_stitches_count = (pattern_width as usize) * (pattern_height as usize);
-  let small_stitches_count = cursor.read_u32::<LittleEndian>()? as usize;
-  let joints_count = cursor.read_u16::<LittleEndian>()?;
+  let small_stitches_count = reader.read_u32::<LittleEndian>()? as usize;
+  let joints_count = reader.read_u16::<LittleEndian>()?;
 
-  let spi = (cursor.read_u16::<LittleEndian>()?, cursor.read_u16::<LittleEndian>()?);
-  cursor.seek_relative(6)?;
+  let spi = (reader.read_u16::<LittleEndian>()?, reader.read_u16::<LittleEndian>()?);
+  reader.seek_relative(6)?;
 
-  let palette = read_palette(&mut cursor)?;
-  let formats = read_formats(&mut cursor, palette.len())?;
-  let symbols = read_symbols(&mut cursor, palette.len())?;
+  let palette = read_palette(&mut reader)?;
+  let formats = read_formats(&mut reader, palette.len())?;
+  let symbols = read_symbols(&mut reader, palette.len())?;
 
-  let pattern_settings = read_pattern_settings(&mut cursor)?;
-  let grid = read_grid_settings(&mut cursor)?;
+  let pattern_settings = read_pattern_settings(&mut reader)?;
+  let grid = read_grid_settings(&mut reader)?;
 
-  let fabric_color_name = cursor.read_cstring(FABRIC_COLOR_NAME_LENGTH)?;
-  let fabric_color = cursor.read_hex_color()?;
-  cursor.seek_relative(65)?;
-  let pattern_info = read_pattern_info(&mut cursor)?;
-  cursor.seek_relative(6)?;
-  let fabric_kind_name = cursor.read_cstring(FABRIC_KIND_NAME_LENGTH)?;
-  cursor.seek_relative(206)?;
+  let fabric_color_name = reader.read_cstring(FABRIC_COLOR_NAME_LENGTH)?;
+  let fabric_color = reader.read_hex_color()?;
+  reader.seek_relative(65)?;
+  let pattern_info = read_pattern_info(&mut reader)?;
+  reader.seek_relative(6)?;
+  let fabric_kind_name = reader.read_cstring(FABRIC_KIND_NAME_LENGTH)?;
+  reader.seek_relative(206)?;
 
-  let (stitch_settings, outlined_stitches, stitch_outline) = read_stitch_settings(&mut cursor)?;
-  let symbol_settings = read_symbol_settings(&mut cursor)?;
+  let (stitch_settings, outlined_stitches, stitch_outline) = read_stitch_settings(&mut reader)?;
+  let symbol_settings = read_symbol_settings(&mut reader)?;
 
-  cursor.seek_relative(16412)?; // Skip library info.
-  cursor.seek_relative(512)?; // Skip machine export info.
+  reader.seek_relative(16412)?; // Skip library info.
+  reader.seek_relative(512)?; // Skip machine export info.
 
   let (fullstitches, partstitches) = read_stitches(
-    &mut cursor,
+    &mut reader,
     pattern_width as usize,
     total_stitches_count,
     small_stitches_count,
   )?;
 
-  let special_stitch_models = read_special_stitch_models(&mut cursor)?;
+  let special_stitch_models = read_special_stitch_models(&mut reader)?;
 
-  let (nodes, lines, _curves, specialstitches) = read_joints(&mut cursor, joints_count)?;
+  let (nodes, lines, _curves, specialstitches) = read_joints(&mut reader, joints_count)?;
 
   Ok(PatternProject {
-    file_path,
+    file_path: Default::default(),
     pattern: Pattern {
       info: pattern_info,
       palette,
@@ -532,6 +539,7 @@ fn read_pattern_info<R: Read + Seek>(reader: &mut R) -> Result<PatternInfo> {
     company: reader.read_cstring(COMPANY_NAME_LENGTH)?,
     copyright: reader.read_cstring(COPYRIGHT_LENGTH)?,
     description: reader.read_cstring(PATTERN_NOTES_LENGTH)?,
+    ..PatternInfo::default()
   })
 }
 
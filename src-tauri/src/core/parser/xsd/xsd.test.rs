@@ -260,6 +260,7 @@ fn reads_pattern_info() {
       company: String::from("Embroidery Studio"),
       copyright: String::from("Embroidery Studio"),
       description: String::from("Shows different stitch types"),
+      ..PatternInfo::default()
     }
   );
 }
@@ -632,3 +633,18 @@ fn reads_joints() {
     assert_eq!(loaded, expected);
   }
 }
+
+#[test]
+fn parses_pattern_from_reader() {
+  let file_path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("testdata/patterns/specials.xsd");
+  let from_file = parse_pattern(file_path.clone()).unwrap();
+  let from_reader = parse_pattern_from_reader(Cursor::new(std::fs::read(&file_path).unwrap())).unwrap();
+
+  assert_eq!(from_file.file_path, file_path);
+  assert!(from_reader.file_path.as_os_str().is_empty());
+  assert_eq!(from_reader.pattern.info, from_file.pattern.info);
+  assert_eq!(from_reader.pattern.fabric, from_file.pattern.fabric);
+  assert_eq!(from_reader.pattern.palette, from_file.pattern.palette);
+  assert_eq!(from_reader.display_settings, from_file.display_settings);
+  assert_eq!(from_reader.print_settings, from_file.print_settings);
+}
@@ -0,0 +1,176 @@
+use std::collections::BTreeMap;
+
+use super::*;
+
+#[cfg(test)]
+#[path = "backstitch.test.rs"]
+mod tests;
+
+/// The lines are placed on the half-cell grid, so their coordinates are doubled to work with integers.
+const GRID_SCALE: f32 = 2.0;
+
+/// An infinite straight line that line segments can lie on.
+///
+/// It is defined by its direction reduced to the smallest integer vector and its offset from the origin.
+/// All the collinear segments share the same support line regardless of their direction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct SupportLine {
+  dx: i64,
+  dy: i64,
+  offset: i64,
+}
+
+impl SupportLine {
+  /// Returns the support line of the segment and the positions of its ends on it.
+  /// Returns `None` for zero-length segments and for the ones not placed on the half-cell grid.
+  fn of(line: &Line) -> Option<(Self, i64, i64)> {
+    let (x1, y1) = (to_grid(line.x.0)?, to_grid(line.y.0)?);
+    let (x2, y2) = (to_grid(line.x.1)?, to_grid(line.y.1)?);
+    let (dx, dy) = (x2 - x1, y2 - y1);
+    if dx == 0 && dy == 0 {
+      return None;
+    }
+
+    let divisor = gcd(dx.abs(), dy.abs());
+    let (mut dx, mut dy) = (dx / divisor, dy / divisor);
+    if dx < 0 || (dx == 0 && dy < 0) {
+      (dx, dy) = (-dx, -dy);
+    }
+
+    let support = Self {
+      dx,
+      dy,
+      offset: dy * x1 - dx * y1,
+    };
+    Some((support, support.position(x1, y1), support.position(x2, y2)))
+  }
+
+  /// The distance between two adjacent grid points of the line, in the units of `position`.
+  fn step(&self) -> i64 {
+    self.dx * self.dx + self.dy * self.dy
+  }
+
+  /// Returns the position of the point along the line.
+  fn position(&self, x: i64, y: i64) -> i64 {
+    self.dx * x + self.dy * y
+  }
+
+  /// Returns the grid point at the position along the line.
+  fn point(&self, position: i64) -> (Coord, Coord) {
+    let step = self.step();
+    let x = (self.dx * position + self.dy * self.offset) / step;
+    let y = (self.dy * position - self.dx * self.offset) / step;
+    (from_grid(x), from_grid(y))
+  }
+}
+
+fn to_grid(coord: Coord) -> Option<i64> {
+  let value = coord.into_inner() * GRID_SCALE;
+  if value.fract() == 0.0 { Some(value as i64) } else { None }
+}
+
+fn from_grid(value: i64) -> Coord {
+  Coord::new(value as f32 / GRID_SCALE).unwrap()
+}
+
+fn gcd(a: i64, b: i64) -> i64 {
+  if b == 0 { a } else { gcd(b, a % b) }
+}
+
+/// Optimizes the segments lying on the same support line.
+///
+/// The line is split into unit steps between the grid points.
+/// Each step belongs to the first segment covering it, so overlapping segments are split.
+/// Then the adjacent steps of the same palette item and kind are joined into a single segment.
+/// The segments that remain the same are kept as is, including their direction.
+fn optimize_support_line(support: SupportLine, segments: &[(Line, i64, i64)]) -> Vec<Line> {
+  let step = support.step();
+
+  let mut steps = BTreeMap::new();
+  for &(line, start, end) in segments {
+    let (start, end) = (start.min(end), start.max(end));
+    for position in (start..end).step_by(step as usize) {
+      steps.entry(position).or_insert((line.palindex, line.kind));
+    }
+  }
+
+  let mut runs: Vec<(i64, i64, (u8, LineKind))> = Vec::new();
+  for (position, owner) in steps {
+    match runs.last_mut() {
+      Some((_, end, last_owner)) if *end == position && *last_owner == owner => *end = position + step,
+      _ => runs.push((position, position + step, owner)),
+    }
+  }
+
+  runs
+    .into_iter()
+    .map(|(start, end, (palindex, kind))| {
+      let original = segments.iter().find(|&&(line, line_start, line_end)| {
+        line.palindex == palindex
+          && line.kind == kind
+          && line_start.min(line_end) == start
+          && line_start.max(line_end) == end
+      });
+      match original {
+        Some(&(line, _, _)) => line,
+        None => {
+          let (x1, y1) = support.point(start);
+          let (x2, y2) = support.point(end);
+          Line {
+            x: (x1, x2),
+            y: (y1, y2),
+            palindex,
+            kind,
+          }
+        }
+      }
+    })
+    .collect()
+}
+
+impl Pattern {
+  /// Optimizes the lines (back and straight stitches) of the pattern.
+  ///
+  /// - Zero-length lines are removed.
+  /// - Collinear lines of the same palette item and kind that touch or overlap are merged.
+  ///   So are the duplicates, including the ones drawn in the opposite direction.
+  /// - Collinear lines of different palette items or kinds are split, so they don't overlap.
+  ///   The overlapped part is kept by the line that comes first in the pattern.
+  ///
+  /// Returns the changes needed to revert the optimization.
+  pub fn optimize_lines(&mut self) -> StitchChanges {
+    let mut changes = StitchChanges::default();
+    let mut supports: BTreeMap<SupportLine, Vec<(Line, i64, i64)>> = BTreeMap::new();
+    for &line in self.lines.iter() {
+      match SupportLine::of(&line) {
+        Some((support, start, end)) => supports.entry(support).or_default().push((line, start, end)),
+        None if line.x.0 == line.x.1 && line.y.0 == line.y.1 => changes.removed.push(Stitch::Line(line)),
+        None => {}
+      }
+    }
+
+    for (support, segments) in supports {
+      let mut optimized = optimize_support_line(support, &segments);
+      let mut original = segments.iter().map(|&(line, _, _)| line).collect::<Vec<_>>();
+      optimized.sort();
+      original.sort();
+      if optimized != original {
+        changes.removed.extend(original.into_iter().map(Stitch::Line));
+        changes.added.extend(optimized.into_iter().map(Stitch::Line));
+      }
+    }
+
+    for stitch in changes.removed.iter() {
+      if let Stitch::Line(line) = stitch {
+        self.lines.remove(line);
+      }
+    }
+    for stitch in changes.added.iter() {
+      if let Stitch::Line(line) = stitch {
+        self.lines.insert(*line);
+      }
+    }
+
+    changes
+  }
+}
@@ -0,0 +1,88 @@
+use ordered_float::NotNan;
+
+use super::*;
+
+fn line(x: (f32, f32), y: (f32, f32), palindex: u8) -> Line {
+  Line {
+    x: (NotNan::new(x.0).unwrap(), NotNan::new(x.1).unwrap()),
+    y: (NotNan::new(y.0).unwrap(), NotNan::new(y.1).unwrap()),
+    palindex,
+    kind: LineKind::Back,
+  }
+}
+
+fn optimize(lines: &[Line]) -> (Vec<Line>, StitchChanges) {
+  let mut pattern = Pattern::default();
+  pattern.lines = Stitches::from_iter(lines.iter().copied());
+  let changes = pattern.optimize_lines();
+  (pattern.lines.iter().copied().collect(), changes)
+}
+
+#[test]
+fn merges_adjacent_collinear_lines() {
+  let (lines, changes) = optimize(&[
+    line((0.0, 1.0), (0.0, 0.0), 0),
+    line((1.0, 2.0), (0.0, 0.0), 0),
+    line((3.0, 2.0), (0.0, 0.0), 0),
+  ]);
+  assert_eq!(lines, vec![line((0.0, 3.0), (0.0, 0.0), 0)]);
+  assert_eq!(changes.removed.len(), 3);
+  assert_eq!(changes.added.len(), 1);
+}
+
+#[test]
+fn merges_lines_on_half_cell_grid() {
+  let (lines, _) = optimize(&[line((0.5, 1.0), (0.5, 1.0), 0), line((1.0, 1.5), (1.0, 1.5), 0)]);
+  assert_eq!(lines, vec![line((0.5, 1.5), (0.5, 1.5), 0)]);
+}
+
+#[test]
+fn removes_duplicates_and_zero_length_lines() {
+  let (lines, changes) = optimize(&[
+    line((0.0, 1.0), (0.0, 1.0), 0),
+    line((1.0, 0.0), (1.0, 0.0), 0),
+    line((2.0, 2.0), (2.0, 2.0), 0),
+  ]);
+  assert_eq!(lines, vec![line((0.0, 1.0), (0.0, 1.0), 0)]);
+  assert_eq!(changes.removed.len(), 3);
+  assert_eq!(changes.added.len(), 1);
+}
+
+#[test]
+fn splits_overlapping_lines() {
+  let (lines, _) = optimize(&[line((0.0, 2.0), (0.0, 0.0), 0), line((1.0, 3.0), (0.0, 0.0), 1)]);
+  assert_eq!(
+    lines,
+    vec![line((0.0, 2.0), (0.0, 0.0), 0), line((2.0, 3.0), (0.0, 0.0), 1)]
+  );
+}
+
+#[test]
+fn keeps_lines_that_cannot_be_merged() {
+  let original = [
+    line((0.0, 1.0), (0.0, 0.0), 0),
+    line((1.0, 1.0), (0.0, 1.0), 0),
+    line((1.0, 2.0), (0.0, 0.0), 1),
+    line((4.0, 3.0), (0.0, 0.0), 0),
+  ];
+  let (lines, changes) = optimize(&original);
+  assert_eq!(lines.len(), original.len());
+  assert_eq!(changes, StitchChanges::default());
+}
+
+#[test]
+fn reverts_optimization() {
+  let original = [
+    line((0.0, 1.0), (0.0, 0.0), 0),
+    line((1.0, 2.0), (0.0, 0.0), 0),
+    line((1.0, 1.0), (1.0, 1.0), 0),
+  ];
+  let mut pattern = Pattern::default();
+  pattern.lines = Stitches::from_iter(original);
+  let changes = pattern.optimize_lines();
+  pattern.revert_stitch_changes(&changes);
+
+  let mut expected = original.to_vec();
+  expected.sort();
+  assert_eq!(pattern.lines.iter().copied().collect::<Vec<_>>(), expected);
+}
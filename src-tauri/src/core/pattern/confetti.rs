@@ -0,0 +1,172 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use super::*;
+
+#[cfg(test)]
+#[path = "confetti.test.rs"]
+mod tests;
+
+/// The criteria of the confetti, i.e. the full stitches that are painful to stitch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct ConfettiOptions {
+  /// A full stitch is isolated if there is no full stitch of the same palette item within this distance (in cells).
+  /// It is clamped to `1..=MAX_RADIUS`.
+  pub radius: u16,
+  /// Groups of connected full stitches of the same palette item smaller than this are islands.
+  pub min_island_size: u16,
+}
+
+impl ConfettiOptions {
+  /// The largest radius that is taken into account.
+  /// Every stitch is checked against all the cells within the radius, so larger ones would make the cleanup unbearably slow.
+  pub const MAX_RADIUS: u16 = 8;
+
+  fn radius(&self) -> i32 {
+    self.radius.clamp(1, Self::MAX_RADIUS) as i32
+  }
+}
+
+impl Default for ConfettiOptions {
+  fn default() -> Self {
+    Self { radius: 1, min_island_size: 3 }
+  }
+}
+
+/// The confetti found in the pattern.
+#[derive(Debug, Default, Clone, PartialEq, BorshSerialize)]
+pub struct ConfettiAnalysis {
+  /// The isolated full stitches.
+  pub isolated: Vec<FullStitch>,
+  /// The tiny islands of full stitches.
+  /// They may include the isolated stitches, since those are usually islands of a single stitch.
+  pub islands: Vec<Vec<FullStitch>>,
+}
+
+type Cell = (i32, i32);
+
+/// The full stitches of the pattern (petites are not taken into account) by their cells.
+struct FullStitchGrid(HashMap<Cell, FullStitch>);
+
+impl FullStitchGrid {
+  fn new(pattern: &Pattern) -> Self {
+    let cells = pattern
+      .fullstitches
+      .iter()
+      .filter(|stitch| stitch.kind == FullStitchKind::Full)
+      .map(|&stitch| (cell_of(&stitch), stitch))
+      .collect();
+    Self(cells)
+  }
+
+  /// Returns the full stitches within the distance from the cell, except the one in the cell itself.
+  fn neighbours(&self, (x, y): Cell, radius: i32) -> impl Iterator<Item = &FullStitch> {
+    (y - radius..=y + radius)
+      .flat_map(move |ny| (x - radius..=x + radius).map(move |nx| (nx, ny)))
+      .filter(move |&cell| cell != (x, y))
+      .filter_map(move |cell| self.0.get(&cell))
+  }
+}
+
+fn cell_of(stitch: &FullStitch) -> Cell {
+  (stitch.x.into_inner() as i32, stitch.y.into_inner() as i32)
+}
+
+impl Pattern {
+  /// Finds the isolated full stitches and the tiny islands of them.
+  pub fn analyze_confetti(&self, options: &ConfettiOptions) -> ConfettiAnalysis {
+    let grid = FullStitchGrid::new(self);
+    let radius = options.radius();
+
+    let isolated = self
+      .fullstitches
+      .iter()
+      .filter(|stitch| stitch.kind == FullStitchKind::Full)
+      .filter(|stitch| {
+        !grid
+          .neighbours(cell_of(stitch), radius)
+          .any(|neighbour| neighbour.palindex == stitch.palindex)
+      })
+      .copied()
+      .collect();
+
+    let mut islands = Vec::new();
+    let mut visited = HashSet::new();
+    for stitch in self
+      .fullstitches
+      .iter()
+      .filter(|stitch| stitch.kind == FullStitchKind::Full)
+    {
+      if !visited.insert(cell_of(stitch)) {
+        continue;
+      }
+
+      // Collect the connected stitches of the same palette item, including the diagonal neighbours.
+      let mut island = Vec::new();
+      let mut queue = VecDeque::from([*stitch]);
+      while let Some(current) = queue.pop_front() {
+        island.push(current);
+        for &neighbour in grid.neighbours(cell_of(&current), 1) {
+          if neighbour.palindex == stitch.palindex && visited.insert(cell_of(&neighbour)) {
+            queue.push_back(neighbour);
+          }
+        }
+      }
+
+      if island.len() < options.min_island_size as usize {
+        island.sort();
+        islands.push(island);
+      }
+    }
+
+    ConfettiAnalysis { isolated, islands }
+  }
+
+  /// Returns the confetti recolored to the dominant palette item of their neighbours.
+  ///
+  /// Each island is recolored as a whole, looking at the stitches within the radius around it.
+  /// The stitches of the island's own palette item are not taken into account.
+  /// If there are several dominant palette items, the first one is used.
+  /// The confetti without neighbours are left as is.
+  pub fn recolor_confetti(&self, options: &ConfettiOptions) -> Vec<FullStitch> {
+    let grid = FullStitchGrid::new(self);
+    let radius = options.radius();
+    let ConfettiAnalysis { isolated, mut islands } = self.analyze_confetti(options);
+
+    let in_islands = islands.iter().flatten().map(cell_of).collect::<HashSet<_>>();
+    islands.extend(
+      isolated
+        .into_iter()
+        .filter(|stitch| !in_islands.contains(&cell_of(stitch)))
+        .map(|stitch| vec![stitch]),
+    );
+
+    let mut recolored = Vec::new();
+    for island in islands {
+      let palindex = island[0].palindex;
+      let mut counts = HashMap::<u8, usize>::new();
+      for stitch in island.iter() {
+        for neighbour in grid.neighbours(cell_of(stitch), radius) {
+          if neighbour.palindex != palindex {
+            *counts.entry(neighbour.palindex).or_default() += 1;
+          }
+        }
+      }
+
+      let dominant = counts
+        .into_iter()
+        .max_by_key(|&(palindex, count)| (count, std::cmp::Reverse(palindex)))
+        .map(|(palindex, _)| palindex);
+      if let Some(dominant) = dominant {
+        recolored.extend(
+          island
+            .into_iter()
+            .map(|stitch| FullStitch { palindex: dominant, ..stitch }),
+        );
+      }
+    }
+    recolored.sort();
+    recolored
+  }
+}
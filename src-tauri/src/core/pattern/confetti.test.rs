@@ -0,0 +1,109 @@
+use ordered_float::NotNan;
+
+use super::*;
+
+fn fullstitch(x: f32, y: f32, palindex: u8) -> FullStitch {
+  FullStitch {
+    x: NotNan::new(x).unwrap(),
+    y: NotNan::new(y).unwrap(),
+    palindex,
+    kind: FullStitchKind::Full,
+  }
+}
+
+/// Creates a 5x5 pattern filled with the palette item `0` and the given stitches on top of it.
+fn create_pattern(stitches: &[FullStitch]) -> Pattern {
+  let mut pattern = Pattern::default();
+  for y in 0..5 {
+    for x in 0..5 {
+      pattern.fullstitches.insert(fullstitch(x as f32, y as f32, 0));
+    }
+  }
+  for &stitch in stitches {
+    pattern.fullstitches.insert(stitch);
+  }
+  pattern
+}
+
+const OPTIONS: ConfettiOptions = ConfettiOptions { radius: 1, min_island_size: 3 };
+
+#[test]
+fn finds_isolated_stitches() {
+  let pattern = create_pattern(&[fullstitch(2.0, 2.0, 1)]);
+  let analysis = pattern.analyze_confetti(&OPTIONS);
+  assert_eq!(analysis.isolated, vec![fullstitch(2.0, 2.0, 1)]);
+  assert_eq!(analysis.islands, vec![vec![fullstitch(2.0, 2.0, 1)]]);
+}
+
+#[test]
+fn respects_radius() {
+  let mut pattern = Pattern::default();
+  pattern.fullstitches = Stitches::from_iter([fullstitch(0.0, 0.0, 1), fullstitch(2.0, 0.0, 1)]);
+
+  let analysis = pattern.analyze_confetti(&OPTIONS);
+  assert_eq!(analysis.isolated.len(), 2);
+
+  let analysis = pattern.analyze_confetti(&ConfettiOptions { radius: 2, ..OPTIONS });
+  assert!(analysis.isolated.is_empty());
+}
+
+#[test]
+fn limits_radius() {
+  let mut pattern = Pattern::default();
+  let far = ConfettiOptions::MAX_RADIUS as f32 + 1.0;
+  pattern.fullstitches = Stitches::from_iter([fullstitch(0.0, 0.0, 1), fullstitch(far, 0.0, 1)]);
+
+  // The stitches farther than the maximum radius are isolated, however large the requested radius is.
+  let analysis = pattern.analyze_confetti(&ConfettiOptions { radius: u16::MAX, ..OPTIONS });
+  assert_eq!(analysis.isolated.len(), 2);
+}
+
+#[test]
+fn finds_tiny_islands() {
+  let pattern = create_pattern(&[
+    fullstitch(1.0, 1.0, 1),
+    fullstitch(2.0, 2.0, 1),
+    fullstitch(4.0, 4.0, 2),
+  ]);
+  let analysis = pattern.analyze_confetti(&OPTIONS);
+  assert_eq!(analysis.isolated, vec![fullstitch(4.0, 4.0, 2)]);
+  assert_eq!(
+    analysis.islands,
+    vec![
+      vec![fullstitch(1.0, 1.0, 1), fullstitch(2.0, 2.0, 1)],
+      vec![fullstitch(4.0, 4.0, 2)]
+    ]
+  );
+
+  let analysis = pattern.analyze_confetti(&ConfettiOptions { min_island_size: 2, ..OPTIONS });
+  assert_eq!(analysis.islands, vec![vec![fullstitch(4.0, 4.0, 2)]]);
+}
+
+#[test]
+fn recolors_confetti_to_dominant_neighbours() {
+  let pattern = create_pattern(&[
+    fullstitch(1.0, 1.0, 1),
+    fullstitch(2.0, 1.0, 1),
+    fullstitch(4.0, 3.0, 2),
+    fullstitch(3.0, 4.0, 2),
+    fullstitch(4.0, 4.0, 3),
+  ]);
+  // The stitch of the palette item `3` is surrounded by two stitches of `2` and one of `0`.
+  assert_eq!(
+    pattern.recolor_confetti(&OPTIONS),
+    vec![
+      fullstitch(1.0, 1.0, 0),
+      fullstitch(2.0, 1.0, 0),
+      fullstitch(4.0, 3.0, 0),
+      fullstitch(3.0, 4.0, 0),
+      fullstitch(4.0, 4.0, 2),
+    ]
+  );
+}
+
+#[test]
+fn keeps_confetti_without_neighbours() {
+  let mut pattern = Pattern::default();
+  pattern.fullstitches = Stitches::from_iter([fullstitch(0.0, 0.0, 1), fullstitch(5.0, 5.0, 2)]);
+  assert!(pattern.recolor_confetti(&OPTIONS).is_empty());
+}
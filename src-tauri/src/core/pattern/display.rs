@@ -57,6 +57,51 @@ impl DisplaySettings {
   }
 }
 
+/// A single display setting that can be updated independently of the others.
+#[derive(Debug, Clone, PartialEq, BorshSerialize, BorshDeserialize)]
+pub enum DisplaySetting {
+  ShowSymbols(bool),
+  ShowGrid(bool),
+  ShowRulers(bool),
+  ShowCenteringMarks(bool),
+  ShowFabricColorsWithSymbols(bool),
+  GapsBetweenStitches(bool),
+  OutlinedStitches(bool),
+  Zoom(u16),
+  StitchOutline(StitchOutline),
+  SymbolSettings(SymbolSettings),
+  StitchSettings(StitchSettings),
+}
+
+impl DisplaySetting {
+  /// Applies the setting to the display settings and returns the replaced value.
+  pub fn apply(&self, settings: &mut DisplaySettings) -> DisplaySetting {
+    use std::mem::replace;
+    match self.clone() {
+      Self::ShowSymbols(value) => Self::ShowSymbols(replace(&mut settings.show_symbols, value)),
+      Self::ShowGrid(value) => Self::ShowGrid(replace(&mut settings.show_grid, value)),
+      Self::ShowRulers(value) => Self::ShowRulers(replace(&mut settings.show_rulers, value)),
+      Self::ShowCenteringMarks(value) => Self::ShowCenteringMarks(replace(&mut settings.show_centering_marks, value)),
+      Self::ShowFabricColorsWithSymbols(value) => {
+        Self::ShowFabricColorsWithSymbols(replace(&mut settings.show_fabric_colors_with_symbols, value))
+      }
+      Self::GapsBetweenStitches(value) => {
+        Self::GapsBetweenStitches(replace(&mut settings.gaps_between_stitches, value))
+      }
+      Self::OutlinedStitches(value) => Self::OutlinedStitches(replace(&mut settings.outlined_stitches, value)),
+      Self::Zoom(value) => Self::Zoom(replace(&mut settings.zoom, value)),
+      Self::StitchOutline(value) => Self::StitchOutline(replace(&mut settings.stitch_outline, value)),
+      Self::SymbolSettings(value) => Self::SymbolSettings(replace(&mut settings.symbol_settings, value)),
+      Self::StitchSettings(value) => Self::StitchSettings(replace(&mut settings.stitch_settings, value)),
+    }
+  }
+
+  /// Whether both values are of the same setting.
+  pub fn is_same_setting(&self, other: &DisplaySetting) -> bool {
+    std::mem::discriminant(self) == std::mem::discriminant(other)
+  }
+}
+
 #[derive(Debug, Default, Clone, PartialEq, BorshSerialize, BorshDeserialize)]
 pub struct Symbols {
   pub full: Option<u16>,
@@ -5,8 +5,19 @@ pub use pattern::*;
 mod stitches;
 pub use stitches::*;
 
+mod backstitch;
+
+mod confetti;
+pub use confetti::*;
+
 pub mod display;
 pub mod print;
 
+mod symmetry;
+pub use symmetry::*;
+
+mod transform;
+pub use transform::*;
+
 mod project;
 pub use project::*;
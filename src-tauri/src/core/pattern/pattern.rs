@@ -259,6 +259,129 @@ impl Pattern {
     conflicts
   }
 
+  /// Returns the number of stitches of each palette item.
+  pub fn stitch_counts(&self) -> Vec<usize> {
+    (0..self.palette.len())
+      .map(|palindex| {
+        let palindex = palindex as u8;
+        self.fullstitches.count_by_palindex(palindex)
+          + self.partstitches.count_by_palindex(palindex)
+          + self.nodes.count_by_palindex(palindex)
+          + self.lines.count_by_palindex(palindex)
+          + self.specialstitches.count_by_palindex(palindex)
+      })
+      .collect()
+  }
+
+  /// Returns the stitches touching the area (see `Stitches::in_region` and `PatternRegion`).
+  pub fn stitches_in_region(&self, area: Rect) -> PatternRegion {
+    let Rect { x, y, width, height } = area;
+    PatternRegion {
+      area,
+      fullstitches: self.fullstitches.in_region(x, y, width, height),
+      partstitches: self.partstitches.in_region(x, y, width, height),
+      nodes: self.nodes.in_region(x, y, width, height),
+      lines: self.lines.in_region(x, y, width, height),
+      specialstitches: self.specialstitches.in_region(x, y, width, height),
+    }
+  }
+
+  /// Returns a copy of the pattern without any stitches.
+  /// It is used to send the pattern to the frontend before its stitches.
+  pub fn without_stitches(&self) -> Self {
+    Self {
+      info: self.info.clone(),
+      fabric: self.fabric.clone(),
+      palette: self.palette.clone(),
+      special_stitch_models: self.special_stitch_models.clone(),
+      ..Self::default()
+    }
+  }
+
+  /// Returns the smallest rectangle of cells that contains all the stitches, including the special ones.
+  /// Returns `None` if the pattern has no stitches.
+  ///
+  /// The rectangle is at least one cell wide and high, even if the content is a single horizontal or vertical line.
+  pub fn content_bounds(&self) -> Option<Rect> {
+    // Each item is the top-left and bottom-right corners of the area occupied by a stitch.
+    let fullstitches = self.fullstitches.iter().map(|stitch| {
+      let size = if stitch.kind == FullStitchKind::Full { 1.0 } else { 0.5 };
+      (
+        stitch.x.into_inner(),
+        stitch.y.into_inner(),
+        stitch.x + size,
+        stitch.y + size,
+      )
+    });
+    let partstitches = self.partstitches.iter().map(|stitch| {
+      let size = if stitch.kind == PartStitchKind::Half { 1.0 } else { 0.5 };
+      (
+        stitch.x.into_inner(),
+        stitch.y.into_inner(),
+        stitch.x + size,
+        stitch.y + size,
+      )
+    });
+    // Nodes and special stitches belong to the cell their position is in.
+    let nodes = self.nodes.iter().map(|node| {
+      let (x, y) = (node.x.floor(), node.y.floor());
+      (x, y, x + 1.0, y + 1.0)
+    });
+    let specialstitches = self.specialstitches.iter().map(|stitch| {
+      let (x, y) = (stitch.x.floor(), stitch.y.floor());
+      (x, y, x + 1.0, y + 1.0)
+    });
+    let lines = self.lines.iter().map(|line| {
+      (
+        line.x.0.min(line.x.1).into_inner(),
+        line.y.0.min(line.y.1).into_inner(),
+        line.x.0.max(line.x.1).into_inner(),
+        line.y.0.max(line.y.1).into_inner(),
+      )
+    });
+
+    let (left, top, right, bottom) = fullstitches
+      .chain(partstitches)
+      .chain(nodes)
+      .chain(specialstitches)
+      .chain(lines)
+      .reduce(|a, b| (a.0.min(b.0), a.1.min(b.1), a.2.max(b.2), a.3.max(b.3)))?;
+    let (left, top) = (left.floor().max(0.0) as u16, top.floor().max(0.0) as u16);
+    let (right, bottom) = (right.ceil() as u16, bottom.ceil() as u16);
+    Some(Rect {
+      x: left,
+      y: top,
+      width: right.saturating_sub(left).max(1),
+      height: bottom.saturating_sub(top).max(1),
+    })
+  }
+
+  /// Moves all the stitches, including the special ones, by the given number of cells.
+  pub fn translate_stitches(&mut self, dx: i32, dy: i32) {
+    let (dx, dy) = (dx as f32, dy as f32);
+    let translate = |coord: Coord, delta: f32| Coord::new(coord.into_inner() + delta).unwrap();
+    self.fullstitches.transform(|stitch| {
+      stitch.x = translate(stitch.x, dx);
+      stitch.y = translate(stitch.y, dy);
+    });
+    self.partstitches.transform(|stitch| {
+      stitch.x = translate(stitch.x, dx);
+      stitch.y = translate(stitch.y, dy);
+    });
+    self.nodes.transform(|stitch| {
+      stitch.x = translate(stitch.x, dx);
+      stitch.y = translate(stitch.y, dy);
+    });
+    self.lines.transform(|stitch| {
+      stitch.x = (translate(stitch.x.0, dx), translate(stitch.x.1, dx));
+      stitch.y = (translate(stitch.y.0, dy), translate(stitch.y.1, dy));
+    });
+    self.specialstitches.transform(|stitch| {
+      stitch.x = translate(stitch.x, dx);
+      stitch.y = translate(stitch.y, dy);
+    });
+  }
+
   pub fn restore_stitches(&mut self, stitches: Vec<Stitch>, palindexes: &[u8], palsize: u8) {
     let mut fullstitches = Vec::new();
     let mut partstitches = Vec::new();
@@ -287,6 +410,16 @@ pub struct PatternInfo {
   pub company: String,
   pub copyright: String,
   pub description: String,
+  /// Keywords to categorize and search the pattern.
+  /// They are stored as a comma-separated list in OXS, so they can't contain commas.
+  pub keywords: Vec<String>,
+  /// Where the pattern comes from (e.g., the original artwork or a website).
+  pub source: String,
+  pub license: String,
+  /// The creation date as a Unix timestamp (in seconds).
+  pub created_at: Option<u64>,
+  /// The date of the last save as a Unix timestamp (in seconds).
+  pub modified_at: Option<u64>,
 }
 
 impl Default for PatternInfo {
@@ -297,10 +430,22 @@ impl Default for PatternInfo {
       company: String::new(),
       copyright: String::new(),
       description: String::new(),
+      keywords: Vec::new(),
+      source: String::new(),
+      license: String::new(),
+      created_at: None,
+      modified_at: None,
     }
   }
 }
 
+/// Returns the current time as a Unix timestamp (in seconds).
+pub fn unix_timestamp() -> u64 {
+  std::time::SystemTime::now()
+    .duration_since(std::time::UNIX_EPOCH)
+    .map_or(0, |duration| duration.as_secs())
+}
+
 #[derive(Debug, Clone, PartialEq, BorshSerialize, BorshDeserialize)]
 pub struct PaletteItem {
   pub brand: String,
@@ -391,3 +536,97 @@ impl Default for Fabric {
 }
 
 pub type StitchesPerInch = (u16, u16);
+
+/// The point of the canvas that stays in place when the canvas is resized.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub enum CanvasAnchor {
+  #[default]
+  TopLeft,
+  Top,
+  TopRight,
+  Left,
+  Center,
+  Right,
+  BottomLeft,
+  Bottom,
+  BottomRight,
+}
+
+impl CanvasAnchor {
+  /// Returns the offset (in cells) by which the pattern content should be moved
+  /// to keep the anchor in place when the canvas is resized from `old_size` to `new_size`.
+  pub fn offset(&self, old_size: (u16, u16), new_size: (u16, u16)) -> (i32, i32) {
+    use CanvasAnchor::*;
+
+    let dx = new_size.0 as i32 - old_size.0 as i32;
+    let dy = new_size.1 as i32 - old_size.1 as i32;
+    let x = match self {
+      TopLeft | Left | BottomLeft => 0,
+      Top | Center | Bottom => dx.div_euclid(2),
+      TopRight | Right | BottomRight => dx,
+    };
+    let y = match self {
+      TopLeft | Top | TopRight => 0,
+      Left | Center | Right => dy.div_euclid(2),
+      BottomLeft | Bottom | BottomRight => dy,
+    };
+    (x, y)
+  }
+}
+
+/// Describes where the pattern content is placed on the resized canvas.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub enum CanvasPlacement {
+  Anchor(CanvasAnchor),
+  /// The explicit offset (in cells) of the content relative to the top-left corner of the old canvas.
+  Offset(i32, i32),
+}
+
+impl CanvasPlacement {
+  pub fn offset(&self, old_size: (u16, u16), new_size: (u16, u16)) -> (i32, i32) {
+    match self {
+      CanvasPlacement::Anchor(anchor) => anchor.offset(old_size, new_size),
+      CanvasPlacement::Offset(x, y) => (*x, *y),
+    }
+  }
+}
+
+/// The new canvas size and the placement of the pattern content on it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct CanvasResize {
+  pub width: u16,
+  pub height: u16,
+  pub placement: CanvasPlacement,
+}
+
+/// A rectangular area of the pattern, in cells.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct Rect {
+  pub x: u16,
+  pub y: u16,
+  pub width: u16,
+  pub height: u16,
+}
+
+/// The stitches lying in the area of the pattern.
+/// The stitch collections are serialized in the same way as in the `Pattern`.
+///
+/// When the pattern is requested by adjacent regions (tiles), every stitch is returned by the single tile its anchor point is in,
+/// except for the lines, which are returned by every tile they cross. So, the lines must be deduplicated by the caller.
+#[derive(Debug, Clone, PartialEq, BorshSerialize)]
+pub struct PatternRegion {
+  pub area: Rect,
+  pub fullstitches: Vec<FullStitch>,
+  pub partstitches: Vec<PartStitch>,
+  pub nodes: Vec<Node>,
+  pub lines: Vec<Line>,
+  pub specialstitches: Vec<SpecialStitch>,
+}
+
+/// The area to crop the canvas to, with the margin (in cells) to keep around it.
+/// If the area is not specified, the canvas is cropped to the pattern content.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct CanvasCrop {
+  pub area: Option<Rect>,
+  pub margin: u16,
+}
@@ -1,5 +1,11 @@
 use borsh::{BorshDeserialize, BorshSerialize};
 
+use super::PatternInfo;
+
+#[cfg(test)]
+#[path = "print.test.rs"]
+mod tests;
+
 #[derive(Debug, Clone, PartialEq, BorshSerialize, BorshDeserialize)]
 pub struct PrintSettings {
   pub font: Font,
@@ -25,6 +31,53 @@ impl Default for PrintSettings {
   }
 }
 
+impl PrintSettings {
+  /// Returns the header of the given page with the template variables substituted.
+  pub fn render_header(&self, info: &PatternInfo, page: usize, pages: usize) -> String {
+    render_template(&self.header, info, page, pages)
+  }
+
+  /// Returns the footer of the given page with the template variables substituted.
+  pub fn render_footer(&self, info: &PatternInfo, page: usize, pages: usize) -> String {
+    render_template(&self.footer, info, page, pages)
+  }
+}
+
+/// Substitutes the template variables in the header or footer template.
+///
+/// The supported variables are `{title}`, `{author}`, `{company}`, `{copyright}`, `{page}` and `{pages}`.
+/// Unknown variables are kept as is.
+pub fn render_template(template: &str, info: &PatternInfo, page: usize, pages: usize) -> String {
+  let mut result = String::with_capacity(template.len());
+  let mut rest = template;
+  while let Some(start) = rest.find('{') {
+    result.push_str(&rest[..start]);
+    rest = &rest[start..];
+
+    let Some(end) = rest.find('}') else {
+      break;
+    };
+    let value = match &rest[1..end] {
+      "title" => info.title.clone(),
+      "author" => info.author.clone(),
+      "company" => info.company.clone(),
+      "copyright" => info.copyright.clone(),
+      "page" => page.to_string(),
+      "pages" => pages.to_string(),
+      _ => {
+        // Keep the brace and continue right after it, so the nested variables are still substituted.
+        result.push('{');
+        rest = &rest[1..];
+        continue;
+      }
+    };
+    result.push_str(&value);
+    rest = &rest[(end + 1)..];
+  }
+  result.push_str(rest);
+  result
+}
+
 #[derive(Debug, Clone, PartialEq, BorshSerialize, BorshDeserialize)]
 pub struct Font {
   pub name: String,
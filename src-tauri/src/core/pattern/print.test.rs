@@ -0,0 +1,44 @@
+use super::*;
+
+fn pattern_info() -> PatternInfo {
+  PatternInfo {
+    title: String::from("Spring Flowers"),
+    author: String::from("Nazar Antoniuk"),
+    ..PatternInfo::default()
+  }
+}
+
+#[test]
+fn test_render_template() {
+  let info = pattern_info();
+
+  assert_eq!(render_template("", &info, 1, 4), "");
+  assert_eq!(render_template("No variables", &info, 1, 4), "No variables");
+  assert_eq!(
+    render_template("{title} by {author}", &info, 1, 4),
+    "Spring Flowers by Nazar Antoniuk"
+  );
+  assert_eq!(render_template("Page {page} of {pages}", &info, 2, 4), "Page 2 of 4");
+}
+
+#[test]
+fn test_render_template_keeps_unknown_variables() {
+  let info = pattern_info();
+
+  assert_eq!(render_template("{unknown} {page}", &info, 3, 4), "{unknown} 3");
+  assert_eq!(render_template("{{page}}", &info, 3, 4), "{3}");
+  assert_eq!(render_template("Unclosed {page", &info, 3, 4), "Unclosed {page");
+}
+
+#[test]
+fn test_render_header_and_footer() {
+  let info = pattern_info();
+  let print_settings = PrintSettings {
+    header: String::from("{title}"),
+    footer: String::from("{page}/{pages}"),
+    ..PrintSettings::default()
+  };
+
+  assert_eq!(print_settings.render_header(&info, 1, 2), "Spring Flowers");
+  assert_eq!(print_settings.render_footer(&info, 1, 2), "1/2");
+}
@@ -12,3 +12,15 @@ pub struct PatternProject {
   pub display_settings: DisplaySettings,
   pub print_settings: PrintSettings,
 }
+
+impl PatternProject {
+  /// Returns a copy of the project without the pattern stitches (see `Pattern::without_stitches`).
+  pub fn without_stitches(&self) -> Self {
+    Self {
+      file_path: self.file_path.clone(),
+      pattern: self.pattern.without_stitches(),
+      display_settings: self.display_settings.clone(),
+      print_settings: self.print_settings.clone(),
+    }
+  }
+}
@@ -1,6 +1,6 @@
 use borsh::{BorshDeserialize, BorshSerialize};
 
-use super::PaletteIndex;
+use super::{PaletteIndex, StitchPosition};
 use crate::core::pattern::Coord;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
@@ -33,6 +33,17 @@ impl PaletteIndex for Line {
   }
 }
 
+impl StitchPosition for Line {
+  fn bounds(&self) -> (f32, f32, f32, f32) {
+    (
+      self.x.0.min(self.x.1).into_inner(),
+      self.y.0.min(self.y.1).into_inner(),
+      self.x.0.max(self.x.1).into_inner(),
+      self.y.0.max(self.y.1).into_inner(),
+    )
+  }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, BorshSerialize, BorshDeserialize)]
 #[borsh(use_discriminant = true)]
 pub enum LineKind {
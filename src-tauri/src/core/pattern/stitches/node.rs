@@ -1,6 +1,6 @@
 use borsh::{BorshDeserialize, BorshSerialize};
 
-use super::PaletteIndex;
+use super::{PaletteIndex, StitchPosition};
 use crate::core::pattern::Coord;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
@@ -34,6 +34,12 @@ impl PaletteIndex for Node {
   }
 }
 
+impl StitchPosition for Node {
+  fn bounds(&self) -> (f32, f32, f32, f32) {
+    (*self.x, *self.y, *self.x, *self.y)
+  }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, BorshSerialize, BorshDeserialize)]
 #[borsh(use_discriminant = true)]
 pub enum NodeKind {
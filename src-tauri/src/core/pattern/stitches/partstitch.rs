@@ -1,7 +1,7 @@
 use borsh::{BorshDeserialize, BorshSerialize};
 
-use super::PaletteIndex;
 use super::fullstitch::*;
+use super::{PaletteIndex, StitchPosition};
 use crate::core::pattern::Coord;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
@@ -70,6 +70,12 @@ impl PaletteIndex for PartStitch {
   }
 }
 
+impl StitchPosition for PartStitch {
+  fn bounds(&self) -> (f32, f32, f32, f32) {
+    (*self.x, *self.y, *self.x, *self.y)
+  }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, BorshSerialize, BorshDeserialize)]
 #[borsh(use_discriminant = true)]
 pub enum PartStitchDirection {
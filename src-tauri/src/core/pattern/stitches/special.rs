@@ -1,6 +1,6 @@
 use borsh::{BorshDeserialize, BorshSerialize};
 
-use super::{Line, Node};
+use super::{Line, Node, PaletteIndex, StitchPosition};
 use crate::core::pattern::Coord;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
@@ -25,6 +25,22 @@ impl Ord for SpecialStitch {
   }
 }
 
+impl PaletteIndex for SpecialStitch {
+  fn palindex(&self) -> u8 {
+    self.palindex
+  }
+
+  fn set_palindex(&mut self, palindex: u8) {
+    self.palindex = palindex;
+  }
+}
+
+impl StitchPosition for SpecialStitch {
+  fn bounds(&self) -> (f32, f32, f32, f32) {
+    (*self.x, *self.y, *self.x, *self.y)
+  }
+}
+
 #[nutype::nutype(
   sanitize(with = |raw| raw.clamp(0, 360)),
   derive(Debug, Clone, Copy, PartialEq, Eq, FromStr, Display, BorshSerialize, BorshDeserialize)
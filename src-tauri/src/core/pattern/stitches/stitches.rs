@@ -1,4 +1,4 @@
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 
 use borsh::{BorshDeserialize, BorshSerialize};
 use ordered_float::NotNan;
@@ -43,16 +43,37 @@ impl From<Line> for Stitch {
   }
 }
 
+/// The size (in cells) of the square chunks the fabric is divided into to index the stitches.
+const CHUNK_SIZE: f32 = 32.0;
+
+type ChunkKey = (i32, i32);
+
+/// Provides the position of the stitch on the fabric, so it can be found by region.
+pub trait StitchPosition {
+  /// Returns the bounding box of the stitch anchor points as `(min_x, min_y, max_x, max_y)`.
+  fn bounds(&self) -> (f32, f32, f32, f32);
+}
+
 /// A set of stitches.
-#[derive(Debug, Clone, BorshSerialize, BorshDeserialize)]
+///
+/// The stitches are ordered by their position, which is used to iterate and serialize them.
+/// Additionally, they are indexed by the fabric chunks they touch and by their palette items.
+/// That allows to query the stitches in a region or of a palette item without scanning the whole set.
+#[derive(Clone)]
 pub struct Stitches<T: Ord> {
   inner: BTreeSet<T>,
+  chunks: HashMap<ChunkKey, BTreeSet<T>>,
+  palindexes: BTreeMap<u8, BTreeSet<T>>,
 }
 
-impl<T: Ord> Stitches<T> {
+impl<T: Ord + Copy + PaletteIndex + StitchPosition> Stitches<T> {
   #[allow(clippy::new_without_default)]
   pub fn new() -> Self {
-    Self { inner: BTreeSet::new() }
+    Self {
+      inner: BTreeSet::new(),
+      chunks: HashMap::new(),
+      palindexes: BTreeMap::new(),
+    }
   }
 
   pub fn iter(&self) -> impl Iterator<Item = &T> {
@@ -83,7 +104,12 @@ impl<T: Ord> Stitches<T> {
   pub fn insert(&mut self, stitch: T) -> Option<T> {
     // We need to use the `replace` method to get the replaced value from the set.
     // We need to return the previous value to pass it back to the caller, so it can be used to update the pattern on the frontend.
-    self.inner.replace(stitch)
+    let replaced = self.inner.replace(stitch);
+    if let Some(replaced) = replaced.as_ref() {
+      self.unindex(replaced);
+    }
+    self.index(stitch);
+    replaced
   }
 
   /// Removes and returns a stitch from the set.
@@ -91,30 +117,253 @@ impl<T: Ord> Stitches<T> {
     // We need to use the `take` method to get the actual value from the set.
     // The passed `stitch` contains only the fields that are used for ordering (coordinates, kind, etc.).
     // Hovewer, we need to return the actual stitch that contains all the other values (mainly, palindex), so it can be used to update the pattern on the frontend.
-    self.inner.take(stitch)
+    let removed = self.inner.take(stitch);
+    if let Some(removed) = removed.as_ref() {
+      self.unindex(removed);
+    }
+    removed
   }
 
   pub fn get(&self, stitch: &T) -> Option<&T> {
     self.inner.get(stitch)
   }
 
+  /// Adds the stitches to the set, keeping the existing ones in case of conflicts.
   pub fn extend(&mut self, stitches: Stitches<T>) {
-    self.inner.extend(stitches.inner);
+    self.insert_missing(stitches.inner);
+  }
+
+  /// Removes and returns all the stitches that match the predicate.
+  pub fn remove_where<F: FnMut(&T) -> bool>(&mut self, mut predicate: F) -> Vec<T> {
+    let removed: Vec<T> = self.inner.iter().filter(|stitch| predicate(stitch)).copied().collect();
+    for stitch in removed.iter() {
+      self.remove(stitch);
+    }
+    removed
+  }
+
+  /// Applies the transformation to every stitch in the set.
+  /// The set is rebuilt, so the transformation may change the fields used for ordering (e.g., coordinates).
+  pub fn transform<F: FnMut(&mut T)>(&mut self, mut f: F) {
+    let stitches = std::mem::take(&mut self.inner);
+    self.chunks.clear();
+    self.palindexes.clear();
+    for mut stitch in stitches.into_iter() {
+      f(&mut stitch);
+      self.insert(stitch);
+    }
+  }
+
+  /// Returns the stitches whose anchor points touch the region, in the order of the set.
+  /// The region includes its top and left edges, but not the bottom and right ones.
+  pub fn in_region(&self, x: u16, y: u16, width: u16, height: u16) -> Vec<T> {
+    if width == 0 || height == 0 {
+      return Vec::new();
+    }
+
+    let (left, top) = (x as f32, y as f32);
+    let (right, bottom) = (left + width as f32, top + height as f32);
+
+    let mut stitches = Vec::new();
+    for key in chunk_keys((left, top, right, bottom)) {
+      if let Some(chunk) = self.chunks.get(&key) {
+        stitches.extend(chunk.iter().filter(|stitch| {
+          let (min_x, min_y, max_x, max_y) = stitch.bounds();
+          min_x < right && max_x >= left && min_y < bottom && max_y >= top
+        }));
+      }
+    }
+    // The stitches spanning several chunks are collected more than once.
+    stitches.sort_unstable();
+    stitches.dedup();
+    stitches
+  }
+
+  /// Returns the number of stitches of the palette item.
+  pub fn count_by_palindex(&self, palindex: u8) -> usize {
+    self.palindexes.get(&palindex).map_or(0, |stitches| stitches.len())
+  }
+
+  /// Returns the stitches of the palette item in the order of the set.
+  pub fn iter_by_palindex(&self, palindex: u8) -> impl Iterator<Item = &T> {
+    self.palindexes.get(&palindex).into_iter().flatten()
+  }
+
+  pub fn remove_stitches_by_palindexes(&mut self, palindexes: &[u8]) -> Vec<T> {
+    // First, we need to remove the stitches of the removed palette items.
+    let mut removed_stitches = Vec::new();
+    for palindex in palindexes {
+      if let Some(stitches) = self.palindexes.get(palindex).cloned() {
+        for stitch in stitches.iter() {
+          self.remove(stitch);
+        }
+        removed_stitches.extend(stitches);
+      }
+    }
+    removed_stitches.sort_unstable();
+
+    // Then, we need to update the palette item indexes of the remaining stitches.
+    // Only the stitches of the palette items that follow the removed ones are affected.
+    self.remap_palindexes(|palindex| {
+      for (index, &removed) in palindexes.iter().enumerate().rev() {
+        if palindex > removed {
+          return palindex - (index as u8) - 1;
+        }
+      }
+      palindex
+    });
+
+    removed_stitches
+  }
+
+  pub fn restore_stitches(&mut self, stitches: Vec<T>, palindexes: &[u8], palsize: u8) {
+    // First, we need to create a map of the old palette item indexes to the new ones.
+    // We do this by iterating over the complete range of current palette item indexes
+    // and incrementing those that are greater than the removed ones.
+    let mut palindexes_map = std::collections::HashMap::new();
+    let mut counter = 0;
+    for palindex in 0..palsize {
+      while palindexes.contains(&(palindex + counter)) {
+        counter += 1;
+      }
+      let new_palindex = palindex + counter;
+      palindexes_map.insert(palindex, new_palindex);
+    }
+
+    // Then, we need to update the palette item indexes of the stitches.
+    self.remap_palindexes(|palindex| *palindexes_map.get(&palindex).unwrap());
+    self.insert_missing(stitches);
+  }
+
+  /// Changes the palette item indexes of the stitches according to the mapping.
+  /// The stitches keep their positions, so only the palette items that actually change are touched.
+  fn remap_palindexes<F: FnMut(u8) -> u8>(&mut self, mut map: F) {
+    for (palindex, stitches) in std::mem::take(&mut self.palindexes) {
+      let new_palindex = map(palindex);
+      if new_palindex == palindex {
+        self.palindexes.entry(palindex).or_default().extend(stitches);
+        continue;
+      }
+
+      let stitches = stitches.into_iter().map(|mut stitch| {
+        stitch.set_palindex(new_palindex);
+        // The palette item index isn't used for ordering, so the stitches are replaced in place.
+        self.inner.replace(stitch);
+        for key in chunk_keys(stitch.bounds()) {
+          if let Some(chunk) = self.chunks.get_mut(&key) {
+            chunk.replace(stitch);
+          }
+        }
+        stitch
+      });
+      self.palindexes.entry(new_palindex).or_default().extend(stitches);
+    }
+  }
+
+  fn insert_missing<I: IntoIterator<Item = T>>(&mut self, stitches: I) {
+    for stitch in stitches {
+      if self.inner.insert(stitch) {
+        self.index(stitch);
+      }
+    }
+  }
+
+  /// Removes and returns the stitches for which `inside` returns `false`.
+  /// The chunks lying entirely within the bounds are skipped.
+  fn remove_stitches_outside<F: Fn(&T) -> bool>(
+    &mut self,
+    x: u16,
+    y: u16,
+    width: u16,
+    height: u16,
+    inside: F,
+  ) -> Vec<T> {
+    let (left, top, right, bottom) = edges(x, y, width, height);
+    let mut outside = Vec::new();
+    for (&(chunk_x, chunk_y), chunk) in self.chunks.iter() {
+      let chunk_left = chunk_x as f32 * CHUNK_SIZE;
+      let chunk_top = chunk_y as f32 * CHUNK_SIZE;
+      if chunk_left >= left && chunk_left + CHUNK_SIZE <= right && chunk_top >= top && chunk_top + CHUNK_SIZE <= bottom
+      {
+        continue;
+      }
+      outside.extend(chunk.iter().filter(|stitch| !inside(stitch)));
+    }
+    outside.sort_unstable();
+    outside.dedup();
+    for stitch in outside.iter() {
+      self.remove(stitch);
+    }
+    outside
+  }
+
+  fn index(&mut self, stitch: T) {
+    for key in chunk_keys(stitch.bounds()) {
+      self.chunks.entry(key).or_default().insert(stitch);
+    }
+    self.palindexes.entry(stitch.palindex()).or_default().insert(stitch);
+  }
+
+  fn unindex(&mut self, stitch: &T) {
+    for key in chunk_keys(stitch.bounds()) {
+      if let Some(chunk) = self.chunks.get_mut(&key) {
+        chunk.remove(stitch);
+        if chunk.is_empty() {
+          self.chunks.remove(&key);
+        }
+      }
+    }
+    if let Some(stitches) = self.palindexes.get_mut(&stitch.palindex()) {
+      stitches.remove(stitch);
+      if stitches.is_empty() {
+        self.palindexes.remove(&stitch.palindex());
+      }
+    }
   }
 }
 
-impl<T: Ord> FromIterator<T> for Stitches<T> {
+/// Returns the keys of all the chunks touched by the bounding box.
+fn chunk_keys((min_x, min_y, max_x, max_y): (f32, f32, f32, f32)) -> impl Iterator<Item = ChunkKey> {
+  let chunk = |value: f32| (value / CHUNK_SIZE).floor() as i32;
+  (chunk(min_y)..=chunk(max_y))
+    .flat_map(move |chunk_y| (chunk(min_x)..=chunk(max_x)).map(move |chunk_x| (chunk_x, chunk_y)))
+}
+
+impl<T: Ord + Copy + PaletteIndex + StitchPosition> FromIterator<T> for Stitches<T> {
   fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
-    Self { inner: BTreeSet::from_iter(iter) }
+    let mut stitches = Self::new();
+    for stitch in iter {
+      stitches.insert(stitch);
+    }
+    stitches
   }
 }
 
-impl<T: Ord> Default for Stitches<T> {
+impl<T: Ord + Copy + PaletteIndex + StitchPosition> Default for Stitches<T> {
   fn default() -> Self {
     Self::new()
   }
 }
 
+impl<T: Ord + std::fmt::Debug> std::fmt::Debug for Stitches<T> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("Stitches").field("inner", &self.inner).finish()
+  }
+}
+
+// The indexes are not serialized, since they are rebuilt from the stitches.
+impl<T: Ord + BorshSerialize> BorshSerialize for Stitches<T> {
+  fn serialize<W: std::io::Write>(&self, writer: &mut W) -> std::io::Result<()> {
+    self.inner.serialize(writer)
+  }
+}
+
+impl<T: Ord + Copy + PaletteIndex + StitchPosition + BorshDeserialize> BorshDeserialize for Stitches<T> {
+  fn deserialize_reader<R: std::io::Read>(reader: &mut R) -> std::io::Result<Self> {
+    Ok(BTreeSet::<T>::deserialize_reader(reader)?.into_iter().collect())
+  }
+}
+
 impl Stitches<FullStitch> {
   /// Removes and returns all the conflicts with a given full stitch.
   /// It looks for any petite stitches that overlap with the full stitch.
@@ -212,19 +461,10 @@ impl Stitches<FullStitch> {
   }
 
   pub fn remove_stitches_outside_bounds(&mut self, x: u16, y: u16, width: u16, height: u16) -> Vec<FullStitch> {
-    let mut conflicts = Vec::new();
-    for fullstitch in std::mem::take(&mut self.inner).into_iter() {
-      if fullstitch.x < x.into()
-        || fullstitch.x >= (x + width).into()
-        || fullstitch.y < y.into()
-        || fullstitch.y >= (y + height).into()
-      {
-        conflicts.push(fullstitch);
-      } else {
-        self.inner.insert(fullstitch);
-      }
-    }
-    conflicts
+    let (left, top, right, bottom) = edges(x, y, width, height);
+    self.remove_stitches_outside(x, y, width, height, |fullstitch| {
+      *fullstitch.x >= left && *fullstitch.x < right && *fullstitch.y >= top && *fullstitch.y < bottom
+    })
   }
 }
 
@@ -382,58 +622,54 @@ impl Stitches<PartStitch> {
   }
 
   pub fn remove_stitches_outside_bounds(&mut self, x: u16, y: u16, width: u16, height: u16) -> Vec<PartStitch> {
-    let mut conflicts = Vec::new();
-    for fullstitch in std::mem::take(&mut self.inner).into_iter() {
-      if fullstitch.x < x.into()
-        || fullstitch.x >= (x + width).into()
-        || fullstitch.y < y.into()
-        || fullstitch.y >= (y + height).into()
-      {
-        conflicts.push(fullstitch);
-      } else {
-        self.inner.insert(fullstitch);
-      }
-    }
-    conflicts
+    let (left, top, right, bottom) = edges(x, y, width, height);
+    self.remove_stitches_outside(x, y, width, height, |partstitch| {
+      *partstitch.x >= left && *partstitch.x < right && *partstitch.y >= top && *partstitch.y < bottom
+    })
   }
 }
 
 impl Stitches<Line> {
   pub fn remove_stitches_outside_bounds(&mut self, x: u16, y: u16, width: u16, height: u16) -> Vec<Line> {
-    let mut conflicts = Vec::new();
-    for line in std::mem::take(&mut self.inner).into_iter() {
-      if line.x.0 < x.into()
-        || line.x.1 < x.into()
-        || line.x.0 > (x + width).into()
-        || line.x.1 > (x + width).into()
-        || line.y.0 < y.into()
-        || line.y.1 < y.into()
-        || line.y.0 > (y + height).into()
-        || line.y.1 > (y + height).into()
-      {
-        conflicts.push(line);
-      } else {
-        self.inner.insert(line);
-      }
-    }
-    conflicts
+    let (left, top, right, bottom) = edges(x, y, width, height);
+    self.remove_stitches_outside(x, y, width, height, |line| {
+      *line.x.0 >= left
+        && *line.x.1 >= left
+        && *line.x.0 <= right
+        && *line.x.1 <= right
+        && *line.y.0 >= top
+        && *line.y.1 >= top
+        && *line.y.0 <= bottom
+        && *line.y.1 <= bottom
+    })
   }
 }
 
 impl Stitches<Node> {
   pub fn remove_stitches_outside_bounds(&mut self, x: u16, y: u16, width: u16, height: u16) -> Vec<Node> {
-    let mut conflicts = Vec::new();
-    for node in std::mem::take(&mut self.inner).into_iter() {
-      if node.x < x.into() || node.x >= (x + width).into() || node.y < y.into() || node.y >= (y + height).into() {
-        conflicts.push(node);
-      } else {
-        self.inner.insert(node);
-      }
-    }
-    conflicts
+    let (left, top, right, bottom) = edges(x, y, width, height);
+    self.remove_stitches_outside(x, y, width, height, |node| {
+      *node.x >= left && *node.x < right && *node.y >= top && *node.y < bottom
+    })
+  }
+}
+
+impl Stitches<SpecialStitch> {
+  pub fn remove_stitches_outside_bounds(&mut self, x: u16, y: u16, width: u16, height: u16) -> Vec<SpecialStitch> {
+    let (left, top, right, bottom) = edges(x, y, width, height);
+    self.remove_stitches_outside(x, y, width, height, |special| {
+      *special.x >= left && *special.x < right && *special.y >= top && *special.y < bottom
+    })
   }
 }
 
+/// Returns the left, top, right and bottom edges of the bounds.
+/// They are computed in `f32`, since the right and bottom edges may not fit into `u16`.
+fn edges(x: u16, y: u16, width: u16, height: u16) -> (f32, f32, f32, f32) {
+  let (left, top) = (x as f32, y as f32);
+  (left, top, left + width as f32, top + height as f32)
+}
+
 // TODO: rewrite
 // Just defines some common methods to work with the palette item indexes.
 // That allows to share some logic across different stitch types.
@@ -441,67 +677,3 @@ pub trait PaletteIndex {
   fn palindex(&self) -> u8;
   fn set_palindex(&mut self, palindex: u8);
 }
-
-impl<T: Ord + PaletteIndex> Stitches<T> {
-  pub fn remove_stitches_by_palindexes(&mut self, palindexes: &[u8]) -> Vec<T> {
-    let mut remaining_stitches = Vec::new();
-    let mut removed_stitches = Vec::new();
-
-    // First, we need to separate the stitches into two groups: the ones that should be removed and the ones that should remain.
-    for stitch in std::mem::take(&mut self.inner).into_iter() {
-      if palindexes.contains(&stitch.palindex()) {
-        removed_stitches.push(stitch);
-      } else {
-        remaining_stitches.push(stitch);
-      }
-    }
-
-    // Then, we need to reinsert the remaining stitches into the set with the new palette item indexes.
-    let mut palindexes_map = std::collections::HashMap::new();
-    'outer: for mut stitch in remaining_stitches.into_iter() {
-      match palindexes_map.get(&stitch.palindex()) {
-        Some(&new_palindex) => {
-          stitch.set_palindex(new_palindex);
-        }
-        None => {
-          for (index, &palindex) in palindexes.iter().enumerate().rev() {
-            if stitch.palindex() > palindex {
-              let new_palindex = stitch.palindex() - (index as u8) - 1;
-              palindexes_map.insert(stitch.palindex(), new_palindex);
-              stitch.set_palindex(new_palindex);
-              self.inner.insert(stitch);
-              continue 'outer;
-            }
-          }
-          palindexes_map.insert(stitch.palindex(), stitch.palindex());
-        }
-      }
-      self.inner.insert(stitch);
-    }
-
-    removed_stitches
-  }
-
-  pub fn restore_stitches(&mut self, stitches: Vec<T>, palindexes: &[u8], palsize: u8) {
-    // First, we need to create a map of the old palette item indexes to the new ones.
-    // We do this by iterating over the complete range of current palette item indexes
-    // and incrementing those that are greater than the removed ones.
-    let mut palindexes_map = std::collections::HashMap::new();
-    let mut counter = 0;
-    for palindex in 0..palsize {
-      while palindexes.contains(&(palindex + counter)) {
-        counter += 1;
-      }
-      let new_palindex = palindex + counter;
-      palindexes_map.insert(palindex, new_palindex);
-    }
-
-    // Then, we need to update the palette item indexes of the stitches.
-    for mut stitch in std::mem::take(&mut self.inner).into_iter() {
-      let new_palindex = palindexes_map.get(&stitch.palindex()).unwrap();
-      stitch.set_palindex(*new_palindex);
-      self.inner.insert(stitch);
-    }
-    self.inner.extend(stitches);
-  }
-}
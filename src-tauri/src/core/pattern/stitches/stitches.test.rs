@@ -522,3 +522,127 @@ fn node_conflicts_with_node() {
   let bead = node(NotNan::new(1.0).unwrap(), NodeKind::Bead);
   assert!(TEST_NODES.get(&bead).is_some());
 }
+
+fn fullstitch(x: f32, y: f32, palindex: u8) -> FullStitch {
+  FullStitch {
+    x: NotNan::new(x).unwrap(),
+    y: NotNan::new(y).unwrap(),
+    palindex,
+    kind: FullStitchKind::Full,
+  }
+}
+
+#[test]
+fn finds_stitches_in_region() {
+  let stitches = Stitches::from_iter([
+    fullstitch(0.0, 0.0, 0),
+    fullstitch(31.0, 31.0, 0),
+    fullstitch(32.0, 31.0, 0),
+    fullstitch(40.0, 70.0, 0),
+  ]);
+  assert_eq!(
+    stitches.in_region(30, 30, 10, 10),
+    vec![fullstitch(31.0, 31.0, 0), fullstitch(32.0, 31.0, 0)]
+  );
+  assert_eq!(stitches.in_region(0, 0, 1, 1), vec![fullstitch(0.0, 0.0, 0)]);
+  assert!(stitches.in_region(1, 0, 30, 30).is_empty());
+  assert!(stitches.in_region(0, 0, 0, 100).is_empty());
+
+  // The line spanning several chunks is found from any of them, but only once.
+  let lines = Stitches::from_iter([Line {
+    x: (NotNan::new(10.0).unwrap(), NotNan::new(100.0).unwrap()),
+    y: (NotNan::new(10.0).unwrap(), NotNan::new(10.0).unwrap()),
+    palindex: 0,
+    kind: LineKind::Back,
+  }]);
+  assert_eq!(lines.in_region(60, 0, 10, 20).len(), 1);
+  assert_eq!(lines.in_region(0, 0, 200, 20).len(), 1);
+  assert!(lines.in_region(0, 11, 200, 20).is_empty());
+}
+
+#[test]
+fn keeps_indexes_up_to_date() {
+  let mut stitches = Stitches::from_iter([fullstitch(0.0, 0.0, 0), fullstitch(1.0, 0.0, 1)]);
+  assert_eq!(stitches.count_by_palindex(0), 1);
+  assert_eq!(stitches.count_by_palindex(1), 1);
+
+  assert_eq!(stitches.insert(fullstitch(0.0, 0.0, 1)), Some(fullstitch(0.0, 0.0, 0)));
+  assert_eq!(stitches.count_by_palindex(0), 0);
+  assert_eq!(stitches.count_by_palindex(1), 2);
+  assert_eq!(stitches.in_region(0, 0, 1, 1), vec![fullstitch(0.0, 0.0, 1)]);
+
+  stitches.remove(&fullstitch(1.0, 0.0, 0));
+  assert_eq!(
+    stitches.iter_by_palindex(1).copied().collect::<Vec<_>>(),
+    vec![fullstitch(0.0, 0.0, 1)]
+  );
+  assert!(stitches.in_region(1, 0, 1, 1).is_empty());
+}
+
+#[test]
+fn removes_and_restores_stitches_by_palindexes() {
+  let original = [
+    fullstitch(0.0, 0.0, 0),
+    fullstitch(1.0, 0.0, 1),
+    fullstitch(2.0, 0.0, 2),
+    fullstitch(3.0, 0.0, 3),
+  ];
+  let mut stitches = Stitches::from_iter(original);
+
+  let removed = stitches.remove_stitches_by_palindexes(&[0, 2]);
+  assert_eq!(removed, vec![fullstitch(0.0, 0.0, 0), fullstitch(2.0, 0.0, 2)]);
+  assert_eq!(
+    stitches.iter().copied().collect::<Vec<_>>(),
+    vec![fullstitch(1.0, 0.0, 0), fullstitch(3.0, 0.0, 1)]
+  );
+  assert_eq!(stitches.count_by_palindex(0), 1);
+  assert_eq!(stitches.count_by_palindex(1), 1);
+  assert_eq!(stitches.count_by_palindex(2), 0);
+  assert_eq!(stitches.in_region(3, 0, 1, 1), vec![fullstitch(3.0, 0.0, 1)]);
+
+  stitches.restore_stitches(removed, &[0, 2], 2);
+  assert_eq!(stitches.iter().copied().collect::<Vec<_>>(), original.to_vec());
+  for palindex in 0..4 {
+    assert_eq!(stitches.count_by_palindex(palindex), 1);
+  }
+}
+
+#[test]
+fn removes_stitches_outside_bounds_across_chunks() {
+  let mut stitches = Stitches::from_iter([
+    fullstitch(0.0, 0.0, 0),
+    fullstitch(50.0, 10.0, 0),
+    fullstitch(70.0, 10.0, 0),
+    fullstitch(10.0, 90.0, 0),
+  ]);
+  let removed = stitches.remove_stitches_outside_bounds(0, 0, 64, 64);
+  assert_eq!(removed, vec![fullstitch(70.0, 10.0, 0), fullstitch(10.0, 90.0, 0)]);
+  assert_eq!(stitches.len(), 2);
+  assert_eq!(stitches.in_region(0, 0, 100, 100).len(), 2);
+}
+
+#[test]
+fn removes_stitches_outside_bounds_beyond_u16() {
+  let mut stitches = Stitches::from_iter([fullstitch(10.0, 10.0, 0), fullstitch(60000.0, 10.0, 0)]);
+  // The right edge of the bounds doesn't fit into `u16`.
+  let removed = stitches.remove_stitches_outside_bounds(100, 0, u16::MAX, 64);
+  assert_eq!(removed, vec![fullstitch(10.0, 10.0, 0)]);
+  assert_eq!(
+    stitches.iter().copied().collect::<Vec<_>>(),
+    vec![fullstitch(60000.0, 10.0, 0)]
+  );
+}
+
+#[test]
+fn serializes_only_stitches() {
+  let stitches = Stitches::from_iter([fullstitch(0.0, 0.0, 0), fullstitch(40.0, 40.0, 1)]);
+  let bytes = borsh::to_vec(&stitches).unwrap();
+  assert_eq!(
+    bytes,
+    borsh::to_vec(&vec![fullstitch(0.0, 0.0, 0), fullstitch(40.0, 40.0, 1)]).unwrap()
+  );
+
+  let stitches: Stitches<FullStitch> = borsh::from_slice(&bytes).unwrap();
+  assert_eq!(stitches.count_by_palindex(1), 1);
+  assert_eq!(stitches.in_region(40, 40, 1, 1), vec![fullstitch(40.0, 40.0, 1)]);
+}
@@ -0,0 +1,202 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use super::*;
+
+#[cfg(test)]
+#[path = "symmetry.test.rs"]
+mod tests;
+
+/// The maximum number of stitches produced by the repeat mode for a single stitch.
+/// Otherwise, a small interval on a large fabric would produce millions of stitches at once.
+const MAX_REPEATED_STITCHES: usize = 10_000;
+
+/// The way the stitches are drawn (or erased) on the pattern.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub enum DrawingMode {
+  /// Every stitch is drawn as is.
+  #[default]
+  Normal,
+  /// Every stitch is mirrored across the vertical axis of the fabric.
+  MirrorVertical,
+  /// Every stitch is mirrored across the horizontal axis of the fabric.
+  MirrorHorizontal,
+  /// Every stitch is mirrored across both axes of the fabric.
+  MirrorBoth,
+  /// Every stitch is repeated 4 times, rotating it by 90 degrees around the fabric center.
+  Rotational4,
+  /// Every stitch is repeated 8 times, rotating it by 90 degrees around the fabric center and mirroring each copy.
+  Rotational8,
+  /// Every stitch is repeated across the whole fabric at the given interval (in cells).
+  /// A zero interval disables repeating along that axis.
+  /// The number of copies is limited by `MAX_REPEATED_STITCHES`.
+  Repeat { x: u16, y: u16 },
+}
+
+impl DrawingMode {
+  /// Returns the stitch along with all its counterparts that fit the fabric.
+  /// The original stitch always comes first, and there are no duplicates.
+  pub fn expand(&self, stitch: Stitch, fabric: &Fabric) -> Vec<Stitch> {
+    let fabric_frame = Frame::from(Rect {
+      x: 0,
+      y: 0,
+      width: fabric.width,
+      height: fabric.height,
+    });
+
+    let candidates = match *self {
+      DrawingMode::Normal => vec![stitch],
+      DrawingMode::MirrorVertical => vec![
+        stitch,
+        transform_stitch(stitch, Transformation::FlipHorizontal, fabric_frame),
+      ],
+      DrawingMode::MirrorHorizontal => vec![
+        stitch,
+        transform_stitch(stitch, Transformation::FlipVertical, fabric_frame),
+      ],
+      DrawingMode::MirrorBoth => [
+        Transformation::FlipHorizontal,
+        Transformation::FlipVertical,
+        Transformation::Rotate180,
+      ]
+      .into_iter()
+      .fold(vec![stitch], |mut stitches, transformation| {
+        stitches.push(transform_stitch(stitch, transformation, fabric_frame));
+        stitches
+      }),
+      DrawingMode::Rotational4 | DrawingMode::Rotational8 => {
+        let frame = rotation_frame(fabric);
+        let rotations = [
+          stitch,
+          transform_stitch(stitch, Transformation::Rotate90, frame),
+          transform_stitch(stitch, Transformation::Rotate180, frame),
+          transform_stitch(stitch, Transformation::Rotate270, frame),
+        ];
+        if *self == DrawingMode::Rotational8 {
+          rotations
+            .into_iter()
+            .flat_map(|stitch| [stitch, transform_stitch(stitch, Transformation::FlipHorizontal, frame)])
+            .collect()
+        } else {
+          rotations.to_vec()
+        }
+      }
+      DrawingMode::Repeat { x, y } => return repeat_stitch(stitch, x, y, fabric),
+    };
+
+    let mut stitches: Vec<Stitch> = Vec::with_capacity(candidates.len());
+    for candidate in candidates {
+      if is_within_fabric(&candidate, fabric) && !stitches.contains(&candidate) {
+        stitches.push(candidate);
+      }
+    }
+    stitches
+  }
+}
+
+/// Repeats the stitch across the fabric at the given interval (in cells).
+///
+/// Only the offsets that may keep the stitch on the fabric are tried.
+/// Distinct offsets always produce distinct stitches, so the copies don't need to be deduplicated.
+/// The number of the stitches is capped at `MAX_REPEATED_STITCHES`, the farthest copies are dropped.
+fn repeat_stitch(stitch: Stitch, interval_x: u16, interval_y: u16, fabric: &Fabric) -> Vec<Stitch> {
+  let (min_x, min_y, max_x, max_y) = match stitch {
+    Stitch::Full(fullstitch) => fullstitch.bounds(),
+    Stitch::Part(partstitch) => partstitch.bounds(),
+    Stitch::Node(node) => node.bounds(),
+    Stitch::Line(line) => line.bounds(),
+  };
+  let offsets = |interval: u16, min: f32, max: f32, size: u16| {
+    if interval == 0 {
+      return 0..=0;
+    }
+    let interval = interval as f32;
+    ((-min / interval).ceil() as i32)..=(((size as f32 - max) / interval).floor() as i32)
+  };
+  let offsets_x = offsets(interval_x, min_x, max_x, fabric.width);
+  let offsets_y = offsets(interval_y, min_y, max_y, fabric.height);
+
+  let mut stitches = Vec::new();
+  if is_within_fabric(&stitch, fabric) {
+    stitches.push(stitch);
+  }
+  let copies = offsets_y
+    .flat_map(|j| offsets_x.clone().map(move |i| (i, j)))
+    .filter(|&offset| offset != (0, 0))
+    .map(|(i, j)| translate_stitch(stitch, i * interval_x as i32, j * interval_y as i32))
+    .filter(|copy| is_within_fabric(copy, fabric));
+  for copy in copies {
+    if stitches.len() >= MAX_REPEATED_STITCHES {
+      break;
+    }
+    stitches.push(copy);
+  }
+  stitches
+}
+
+/// Returns the square frame centered on the fabric to rotate the stitches around its center.
+///
+/// The rotated cells must stay aligned to the grid.
+/// So, if the fabric width and height have different parity, the center is shifted up by half a cell.
+fn rotation_frame(fabric: &Fabric) -> Frame {
+  let center_x = fabric.width as f32 / 2.0;
+  let center_y = if (fabric.width + fabric.height) % 2 == 0 {
+    fabric.height as f32 / 2.0
+  } else {
+    (fabric.height as f32 - 1.0) / 2.0
+  };
+  let size = fabric.width.max(fabric.height) as f32;
+  Frame {
+    x: center_x - size / 2.0,
+    y: center_y - size / 2.0,
+    width: size,
+    height: size,
+  }
+}
+
+fn transform_stitch(stitch: Stitch, transformation: Transformation, frame: Frame) -> Stitch {
+  match stitch {
+    Stitch::Full(fullstitch) => Stitch::Full(transformation.transform_fullstitch(fullstitch, frame)),
+    Stitch::Part(partstitch) => Stitch::Part(transformation.transform_partstitch(partstitch, frame)),
+    Stitch::Node(node) => Stitch::Node(transformation.transform_node(node, frame)),
+    Stitch::Line(line) => Stitch::Line(transformation.transform_line(line, frame)),
+  }
+}
+
+fn translate_stitch(stitch: Stitch, dx: i32, dy: i32) -> Stitch {
+  let translate = |coord: Coord, delta: i32| Coord::new(coord.into_inner() + delta as f32).unwrap();
+  match stitch {
+    Stitch::Full(fullstitch) => Stitch::Full(FullStitch {
+      x: translate(fullstitch.x, dx),
+      y: translate(fullstitch.y, dy),
+      ..fullstitch
+    }),
+    Stitch::Part(partstitch) => Stitch::Part(PartStitch {
+      x: translate(partstitch.x, dx),
+      y: translate(partstitch.y, dy),
+      ..partstitch
+    }),
+    Stitch::Node(node) => Stitch::Node(Node {
+      x: translate(node.x, dx),
+      y: translate(node.y, dy),
+      ..node
+    }),
+    Stitch::Line(line) => Stitch::Line(Line {
+      x: (translate(line.x.0, dx), translate(line.x.1, dx)),
+      y: (translate(line.y.0, dy), translate(line.y.1, dy)),
+      ..line
+    }),
+  }
+}
+
+/// Checks whether the stitch fits the fabric in the same way as `Pattern::remove_stitches_outside_bounds` does.
+fn is_within_fabric(stitch: &Stitch, fabric: &Fabric) -> bool {
+  let (width, height) = (fabric.width as f32, fabric.height as f32);
+  let contains_cell = |x: Coord, y: Coord| *x >= 0.0 && *x < width && *y >= 0.0 && *y < height;
+  let contains_point = |x: Coord, y: Coord| *x >= 0.0 && *x <= width && *y >= 0.0 && *y <= height;
+  match stitch {
+    Stitch::Full(fullstitch) => contains_cell(fullstitch.x, fullstitch.y),
+    Stitch::Part(partstitch) => contains_cell(partstitch.x, partstitch.y),
+    Stitch::Node(node) => contains_cell(node.x, node.y),
+    Stitch::Line(line) => contains_point(line.x.0, line.y.0) && contains_point(line.x.1, line.y.1),
+  }
+}
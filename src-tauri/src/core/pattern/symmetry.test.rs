@@ -0,0 +1,136 @@
+use ordered_float::NotNan;
+
+use super::*;
+
+fn fabric(width: u16, height: u16) -> Fabric {
+  Fabric {
+    width,
+    height,
+    ..Fabric::default()
+  }
+}
+
+fn full(x: f32, y: f32) -> Stitch {
+  Stitch::Full(FullStitch {
+    x: NotNan::new(x).unwrap(),
+    y: NotNan::new(y).unwrap(),
+    palindex: 0,
+    kind: FullStitchKind::Full,
+  })
+}
+
+fn half(x: f32, y: f32, direction: PartStitchDirection) -> Stitch {
+  Stitch::Part(PartStitch {
+    x: NotNan::new(x).unwrap(),
+    y: NotNan::new(y).unwrap(),
+    palindex: 0,
+    direction,
+    kind: PartStitchKind::Half,
+  })
+}
+
+#[test]
+fn test_normal_mode() {
+  assert_eq!(
+    DrawingMode::Normal.expand(full(1.0, 2.0), &fabric(10, 10)),
+    vec![full(1.0, 2.0)]
+  );
+}
+
+#[test]
+fn test_mirror_modes() {
+  let fabric = fabric(10, 10);
+  assert_eq!(
+    DrawingMode::MirrorVertical.expand(full(1.0, 2.0), &fabric),
+    vec![full(1.0, 2.0), full(8.0, 2.0)]
+  );
+  assert_eq!(
+    DrawingMode::MirrorHorizontal.expand(full(1.0, 2.0), &fabric),
+    vec![full(1.0, 2.0), full(1.0, 7.0)]
+  );
+  assert_eq!(
+    DrawingMode::MirrorBoth.expand(full(1.0, 2.0), &fabric),
+    vec![full(1.0, 2.0), full(8.0, 2.0), full(1.0, 7.0), full(8.0, 7.0)]
+  );
+
+  // The diagonal of the mirrored half stitch is flipped too.
+  assert_eq!(
+    DrawingMode::MirrorVertical.expand(half(1.0, 2.0, PartStitchDirection::Forward), &fabric),
+    vec![
+      half(1.0, 2.0, PartStitchDirection::Forward),
+      half(8.0, 2.0, PartStitchDirection::Backward)
+    ]
+  );
+
+  // The stitch on the axis is its own counterpart.
+  assert_eq!(
+    DrawingMode::MirrorVertical.expand(full(4.0, 2.0), &fabric(9, 9)),
+    vec![full(4.0, 2.0)]
+  );
+}
+
+#[test]
+fn test_rotational_modes() {
+  let fabric = fabric(10, 10);
+  assert_eq!(
+    DrawingMode::Rotational4.expand(full(1.0, 2.0), &fabric),
+    vec![full(1.0, 2.0), full(7.0, 1.0), full(8.0, 7.0), full(2.0, 8.0)]
+  );
+
+  let stitches = DrawingMode::Rotational8.expand(full(1.0, 2.0), &fabric);
+  assert_eq!(stitches.len(), 8);
+  assert_eq!(stitches[0], full(1.0, 2.0));
+  assert!(stitches.contains(&full(8.0, 2.0)));
+  assert!(stitches.contains(&full(2.0, 1.0)));
+}
+
+#[test]
+fn test_rotational_mode_on_non_square_fabric() {
+  // The center of the 5x4 fabric is shifted to the center of the cell (2, 1) to stay aligned to the grid.
+  let fabric = fabric(5, 4);
+  assert_eq!(
+    DrawingMode::Rotational4.expand(full(2.0, 1.0), &fabric),
+    vec![full(2.0, 1.0)]
+  );
+  assert_eq!(
+    DrawingMode::Rotational4.expand(full(1.0, 1.0), &fabric),
+    vec![full(1.0, 1.0), full(2.0, 0.0), full(3.0, 1.0), full(2.0, 2.0)]
+  );
+  // The counterparts outside the fabric are dropped.
+  assert_eq!(
+    DrawingMode::Rotational4.expand(full(0.0, 3.0), &fabric),
+    vec![full(0.0, 3.0), full(4.0, 3.0)]
+  );
+}
+
+#[test]
+fn test_repeat_mode() {
+  let fabric = fabric(10, 10);
+  assert_eq!(
+    DrawingMode::Repeat { x: 4, y: 0 }.expand(full(1.0, 2.0), &fabric),
+    vec![full(1.0, 2.0), full(5.0, 2.0), full(9.0, 2.0)]
+  );
+
+  let stitches = DrawingMode::Repeat { x: 5, y: 5 }.expand(full(6.0, 7.0), &fabric);
+  assert_eq!(
+    stitches,
+    vec![full(6.0, 7.0), full(1.0, 2.0), full(6.0, 2.0), full(1.0, 7.0)]
+  );
+
+  assert_eq!(
+    DrawingMode::Repeat { x: 0, y: 0 }.expand(full(1.0, 2.0), &fabric),
+    vec![full(1.0, 2.0)]
+  );
+}
+
+#[test]
+fn test_repeat_mode_is_limited() {
+  let fabric = fabric(1000, 1000);
+  let stitches = DrawingMode::Repeat { x: 1, y: 1 }.expand(full(500.0, 500.0), &fabric);
+  assert_eq!(stitches.len(), MAX_REPEATED_STITCHES);
+  assert_eq!(stitches[0], full(500.0, 500.0));
+
+  // Without the vertical interval, the stitch is only repeated along its row.
+  let stitches = DrawingMode::Repeat { x: 1, y: 0 }.expand(full(500.0, 500.0), &fabric);
+  assert_eq!(stitches.len(), 1000);
+}
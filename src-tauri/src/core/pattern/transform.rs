@@ -0,0 +1,302 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use super::*;
+
+#[cfg(test)]
+#[path = "transform.test.rs"]
+mod tests;
+
+/// A geometric transformation of the pattern or its area.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub enum Transformation {
+  /// Rotates by 90 degrees clockwise.
+  Rotate90,
+  Rotate180,
+  /// Rotates by 270 degrees clockwise (i.e., 90 degrees counterclockwise).
+  Rotate270,
+  /// Mirrors from left to right.
+  FlipHorizontal,
+  /// Mirrors from top to bottom.
+  FlipVertical,
+}
+
+impl Transformation {
+  /// Returns `true` if the transformation swaps the width and height of the transformed area.
+  pub fn swaps_axes(&self) -> bool {
+    matches!(self, Transformation::Rotate90 | Transformation::Rotate270)
+  }
+
+  /// Returns `true` if the transformation turns the `/` diagonal into `\` and vice versa.
+  pub fn swaps_diagonals(&self) -> bool {
+    !matches!(self, Transformation::Rotate180)
+  }
+
+  /// Returns the size of the area of the given size after the transformation.
+  pub fn transform_size(&self, (width, height): (u16, u16)) -> (u16, u16) {
+    if self.swaps_axes() {
+      (height, width)
+    } else {
+      (width, height)
+    }
+  }
+
+  /// Maps a point relative to the top-left corner of the area of the given size.
+  /// The result is relative to the top-left corner of the transformed area.
+  pub fn map_point(&self, (x, y): (f32, f32), (width, height): (f32, f32)) -> (f32, f32) {
+    match self {
+      Transformation::Rotate90 => (height - y, x),
+      Transformation::Rotate180 => (width - x, height - y),
+      Transformation::Rotate270 => (y, width - x),
+      Transformation::FlipHorizontal => (width - x, y),
+      Transformation::FlipVertical => (x, height - y),
+    }
+  }
+
+  /// Maps a point of the pattern lying in the area.
+  fn map_coords(&self, x: Coord, y: Coord, area: Frame) -> (Coord, Coord) {
+    let (x, y) = self.map_point(
+      (x.into_inner() - area.x, y.into_inner() - area.y),
+      (area.width, area.height),
+    );
+    (Coord::new(x + area.x).unwrap(), Coord::new(y + area.y).unwrap())
+  }
+
+  /// Maps a square of the given size (a cell or its quarter) defined by its top-left corner.
+  /// Returns the top-left corner of the transformed square.
+  fn map_square(&self, x: Coord, y: Coord, size: f32, area: Frame) -> (Coord, Coord) {
+    let (x1, y1) = self.map_coords(x, y, area);
+    let (x2, y2) = self.map_coords(Coord::new(x + size).unwrap(), Coord::new(y + size).unwrap(), area);
+    (x1.min(x2), y1.min(y2))
+  }
+
+  fn swap_direction(&self, direction: PartStitchDirection) -> PartStitchDirection {
+    match (self.swaps_diagonals(), direction) {
+      (false, direction) => direction,
+      (true, PartStitchDirection::Forward) => PartStitchDirection::Backward,
+      (true, PartStitchDirection::Backward) => PartStitchDirection::Forward,
+    }
+  }
+
+  pub fn transform_fullstitch(&self, fullstitch: FullStitch, area: impl Into<Frame>) -> FullStitch {
+    let size = if fullstitch.kind == FullStitchKind::Full {
+      1.0
+    } else {
+      0.5
+    };
+    let (x, y) = self.map_square(fullstitch.x, fullstitch.y, size, area.into());
+    FullStitch { x, y, ..fullstitch }
+  }
+
+  /// Transforms the part stitch.
+  /// The position of the quarter stitch within the cell is remapped along with its diagonal.
+  pub fn transform_partstitch(&self, partstitch: PartStitch, area: impl Into<Frame>) -> PartStitch {
+    let size = if partstitch.kind == PartStitchKind::Half {
+      1.0
+    } else {
+      0.5
+    };
+    let (x, y) = self.map_square(partstitch.x, partstitch.y, size, area.into());
+    PartStitch {
+      x,
+      y,
+      direction: self.swap_direction(partstitch.direction),
+      ..partstitch
+    }
+  }
+
+  pub fn transform_node(&self, node: Node, area: impl Into<Frame>) -> Node {
+    let (x, y) = self.map_coords(node.x, node.y, area.into());
+    Node {
+      x,
+      y,
+      rotated: node.rotated ^ self.swaps_axes(),
+      ..node
+    }
+  }
+
+  pub fn transform_line(&self, line: Line, area: impl Into<Frame>) -> Line {
+    let area = area.into();
+    let (x1, y1) = self.map_coords(line.x.0, line.y.0, area);
+    let (x2, y2) = self.map_coords(line.x.1, line.y.1, area);
+    Line { x: (x1, x2), y: (y1, y2), ..line }
+  }
+
+  /// Transforms the special stitch.
+  /// Its position is mapped as a point, since the special stitch model is rotated and flipped around it.
+  pub fn transform_specialstitch(&self, specialstitch: SpecialStitch, area: impl Into<Frame>) -> SpecialStitch {
+    let (x, y) = self.map_coords(specialstitch.x, specialstitch.y, area.into());
+    let rotation = specialstitch.rotation.into_inner() % 360;
+    let (rotation, flip) = match self {
+      Transformation::Rotate90 => (rotation + 90, specialstitch.flip),
+      Transformation::Rotate180 => (rotation + 180, specialstitch.flip),
+      Transformation::Rotate270 => (rotation + 270, specialstitch.flip),
+      // Mirroring the rotated model is the same as mirroring it first and then rotating in the opposite direction.
+      Transformation::FlipHorizontal => (360 - rotation, (!specialstitch.flip.0, specialstitch.flip.1)),
+      Transformation::FlipVertical => (360 - rotation, (specialstitch.flip.0, !specialstitch.flip.1)),
+    };
+    SpecialStitch {
+      x,
+      y,
+      rotation: Degree::new(rotation % 360),
+      flip,
+      ..specialstitch
+    }
+  }
+}
+
+/// A rectangular area to transform the stitches within.
+/// Unlike `Rect`, it doesn't need to be aligned to the cells, e.g., to be centered on the fabric.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Frame {
+  pub x: f32,
+  pub y: f32,
+  pub width: f32,
+  pub height: f32,
+}
+
+impl From<Rect> for Frame {
+  fn from(rect: Rect) -> Self {
+    Self {
+      x: rect.x as f32,
+      y: rect.y as f32,
+      width: rect.width as f32,
+      height: rect.height as f32,
+    }
+  }
+}
+
+/// The transformation to apply to the pattern area or, if it isn't specified, to the whole pattern.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct PatternTransform {
+  pub transformation: Transformation,
+  pub area: Option<Rect>,
+}
+
+/// The stitches replaced by an operation that affects many stitches at once.
+/// It is used to revert the operation.
+#[derive(Debug, Default, Clone, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct StitchChanges {
+  pub removed: Vec<Stitch>,
+  pub removed_specialstitches: Vec<SpecialStitch>,
+  pub added: Vec<Stitch>,
+  pub added_specialstitches: Vec<SpecialStitch>,
+}
+
+impl Pattern {
+  /// Transforms the stitches in the area or, if it isn't specified, the whole pattern.
+  ///
+  /// The transformed area keeps its top-left corner.
+  /// The transformed stitches replace the ones they overlap and are dropped if they get outside the fabric.
+  /// When the whole pattern is rotated by 90 or 270 degrees, the fabric dimensions are swapped.
+  ///
+  /// Returns the changes needed to revert the transformation.
+  pub fn transform(&mut self, transformation: Transformation, area: Option<Rect>) -> StitchChanges {
+    let whole_pattern = area.is_none();
+    let area = area.unwrap_or(Rect {
+      x: 0,
+      y: 0,
+      width: self.fabric.width,
+      height: self.fabric.height,
+    });
+    if whole_pattern {
+      (self.fabric.width, self.fabric.height) = transformation.transform_size((self.fabric.width, self.fabric.height));
+    }
+
+    let (left, top) = (area.x as f32, area.y as f32);
+    let (right, bottom) = (left + area.width as f32, top + area.height as f32);
+    // Cells are included by their top-left corner, while points can lie on the right and bottom edges of the area.
+    let contains_cell = |x: Coord, y: Coord| *x >= left && *x < right && *y >= top && *y < bottom;
+    let contains_point = |x: Coord, y: Coord| *x >= left && *x <= right && *y >= top && *y <= bottom;
+
+    let fullstitches = self
+      .fullstitches
+      .remove_where(|stitch| contains_cell(stitch.x, stitch.y));
+    let partstitches = self
+      .partstitches
+      .remove_where(|stitch| contains_cell(stitch.x, stitch.y));
+    let nodes = self.nodes.remove_where(|node| contains_point(node.x, node.y));
+    let lines = self
+      .lines
+      .remove_where(|line| contains_point(line.x.0, line.y.0) && contains_point(line.x.1, line.y.1));
+    let specialstitches = self
+      .specialstitches
+      .remove_where(|stitch| contains_point(stitch.x, stitch.y));
+
+    let mut transformed = Pattern::new(self.fabric.clone());
+    transformed.fullstitches = fullstitches
+      .iter()
+      .map(|&stitch| transformation.transform_fullstitch(stitch, area))
+      .collect();
+    transformed.partstitches = partstitches
+      .iter()
+      .map(|&stitch| transformation.transform_partstitch(stitch, area))
+      .collect();
+    transformed.nodes = nodes
+      .iter()
+      .map(|&node| transformation.transform_node(node, area))
+      .collect();
+    transformed.lines = lines
+      .iter()
+      .map(|&line| transformation.transform_line(line, area))
+      .collect();
+    transformed.specialstitches = specialstitches
+      .iter()
+      .map(|&stitch| transformation.transform_specialstitch(stitch, area))
+      .collect();
+    // The whole pattern always fits the transformed fabric, so we don't clip it.
+    // Otherwise, the stitches on the right and bottom edges of the fabric would be dropped.
+    if !whole_pattern {
+      transformed.remove_stitches_outside_bounds(0, 0, self.fabric.width, self.fabric.height);
+      transformed
+        .specialstitches
+        .remove_stitches_outside_bounds(0, 0, self.fabric.width, self.fabric.height);
+    }
+
+    let mut changes = StitchChanges {
+      removed: fullstitches
+        .into_iter()
+        .map(Stitch::Full)
+        .chain(partstitches.into_iter().map(Stitch::Part))
+        .chain(nodes.into_iter().map(Stitch::Node))
+        .chain(lines.into_iter().map(Stitch::Line))
+        .collect(),
+      removed_specialstitches: specialstitches,
+      ..StitchChanges::default()
+    };
+
+    let added = transformed
+      .fullstitches
+      .iter()
+      .map(|&stitch| Stitch::Full(stitch))
+      .chain(transformed.partstitches.iter().map(|&stitch| Stitch::Part(stitch)))
+      .chain(transformed.nodes.iter().map(|&node| Stitch::Node(node)))
+      .chain(transformed.lines.iter().map(|&line| Stitch::Line(line)));
+    for stitch in added {
+      changes.removed.extend(self.add_stitch(stitch));
+      changes.added.push(stitch);
+    }
+    for &stitch in transformed.specialstitches.iter() {
+      changes
+        .removed_specialstitches
+        .extend(self.specialstitches.insert(stitch));
+      changes.added_specialstitches.push(stitch);
+    }
+
+    changes
+  }
+
+  /// Reverts the changes made by an operation that affects many stitches at once.
+  /// The fabric is not restored, since such operations may change it in different ways.
+  pub fn revert_stitch_changes(&mut self, changes: &StitchChanges) {
+    for &stitch in changes.added.iter() {
+      self.remove_stitch(stitch);
+    }
+    for stitch in changes.added_specialstitches.iter() {
+      self.specialstitches.remove(stitch);
+    }
+    self.add_stitches(changes.removed.clone());
+    for &stitch in changes.removed_specialstitches.iter() {
+      self.specialstitches.insert(stitch);
+    }
+  }
+}
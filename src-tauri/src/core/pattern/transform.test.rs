@@ -0,0 +1,184 @@
+use ordered_float::NotNan;
+
+use super::*;
+
+const AREA: Rect = Rect {
+  x: 0,
+  y: 0,
+  width: 10,
+  height: 10,
+};
+
+fn coord(value: f32) -> Coord {
+  NotNan::new(value).unwrap()
+}
+
+fn fullstitch(x: f32, y: f32, palindex: u8, kind: FullStitchKind) -> FullStitch {
+  FullStitch {
+    x: coord(x),
+    y: coord(y),
+    palindex,
+    kind,
+  }
+}
+
+fn partstitch(x: f32, y: f32, direction: PartStitchDirection, kind: PartStitchKind) -> PartStitch {
+  PartStitch {
+    x: coord(x),
+    y: coord(y),
+    palindex: 0,
+    direction,
+    kind,
+  }
+}
+
+#[test]
+fn test_transform_size() {
+  assert_eq!(Transformation::Rotate90.transform_size((10, 6)), (6, 10));
+  assert_eq!(Transformation::Rotate180.transform_size((10, 6)), (10, 6));
+  assert_eq!(Transformation::Rotate270.transform_size((10, 6)), (6, 10));
+  assert_eq!(Transformation::FlipHorizontal.transform_size((10, 6)), (10, 6));
+}
+
+#[test]
+fn test_transform_fullstitches() {
+  let full = fullstitch(0.0, 0.0, 0, FullStitchKind::Full);
+  assert_eq!(
+    Transformation::Rotate90.transform_fullstitch(full, AREA),
+    fullstitch(9.0, 0.0, 0, FullStitchKind::Full)
+  );
+  assert_eq!(
+    Transformation::Rotate180.transform_fullstitch(full, AREA),
+    fullstitch(9.0, 9.0, 0, FullStitchKind::Full)
+  );
+  assert_eq!(
+    Transformation::Rotate270.transform_fullstitch(full, AREA),
+    fullstitch(0.0, 9.0, 0, FullStitchKind::Full)
+  );
+
+  let petite = fullstitch(0.0, 0.0, 0, FullStitchKind::Petite);
+  assert_eq!(
+    Transformation::FlipHorizontal.transform_fullstitch(petite, AREA),
+    fullstitch(9.5, 0.0, 0, FullStitchKind::Petite)
+  );
+  assert_eq!(
+    Transformation::FlipVertical.transform_fullstitch(petite, AREA),
+    fullstitch(0.0, 9.5, 0, FullStitchKind::Petite)
+  );
+}
+
+#[test]
+fn test_transform_partstitches() {
+  let half = partstitch(2.0, 3.0, PartStitchDirection::Forward, PartStitchKind::Half);
+  assert_eq!(
+    Transformation::FlipVertical.transform_partstitch(half, AREA),
+    partstitch(2.0, 6.0, PartStitchDirection::Backward, PartStitchKind::Half)
+  );
+  assert_eq!(
+    Transformation::Rotate180.transform_partstitch(half, AREA),
+    partstitch(7.0, 6.0, PartStitchDirection::Forward, PartStitchKind::Half)
+  );
+
+  // The top-right quarter becomes the bottom-right one.
+  let quarter = partstitch(0.5, 0.0, PartStitchDirection::Forward, PartStitchKind::Quarter);
+  let rotated = Transformation::Rotate90.transform_partstitch(quarter, AREA);
+  assert_eq!(
+    rotated,
+    partstitch(9.5, 0.5, PartStitchDirection::Backward, PartStitchKind::Quarter)
+  );
+  assert!(rotated.is_on_bottom_right());
+  assert_eq!(rotated.direction, PartStitchDirection::from((rotated.x, rotated.y)));
+}
+
+#[test]
+fn test_transform_nodes_and_lines() {
+  let node = Node {
+    x: coord(1.0),
+    y: coord(2.0),
+    rotated: false,
+    palindex: 0,
+    kind: NodeKind::Bead,
+  };
+  let rotated = Transformation::Rotate90.transform_node(node, AREA);
+  assert_eq!((rotated.x, rotated.y, rotated.rotated), (coord(8.0), coord(1.0), true));
+
+  let line = Line {
+    x: (coord(1.0), coord(3.0)),
+    y: (coord(1.0), coord(1.0)),
+    palindex: 0,
+    kind: LineKind::Back,
+  };
+  let rotated = Transformation::Rotate180.transform_line(line, AREA);
+  assert_eq!(rotated.x, (coord(9.0), coord(7.0)));
+  assert_eq!(rotated.y, (coord(9.0), coord(9.0)));
+}
+
+#[test]
+fn test_transform_specialstitches() {
+  let specialstitch = SpecialStitch {
+    x: coord(2.0),
+    y: coord(2.0),
+    rotation: Degree::new(0),
+    flip: (false, false),
+    palindex: 0,
+    modindex: 0,
+  };
+
+  let rotated = Transformation::Rotate90.transform_specialstitch(specialstitch, AREA);
+  assert_eq!((rotated.x, rotated.y), (coord(8.0), coord(2.0)));
+  assert_eq!(rotated.rotation, Degree::new(90));
+
+  let mirrored = Transformation::FlipHorizontal.transform_specialstitch(rotated, AREA);
+  assert_eq!(mirrored.rotation, Degree::new(270));
+  assert_eq!(mirrored.flip, (true, false));
+}
+
+#[test]
+fn test_transform_whole_pattern() {
+  let mut pattern = Pattern::new(Fabric {
+    width: 10,
+    height: 6,
+    ..Fabric::default()
+  });
+  pattern
+    .fullstitches
+    .insert(fullstitch(0.0, 0.0, 0, FullStitchKind::Full));
+
+  let changes = pattern.transform(Transformation::Rotate90, None);
+  assert_eq!((pattern.fabric.width, pattern.fabric.height), (6, 10));
+  assert_eq!(
+    pattern.fullstitches.iter().collect::<Vec<_>>(),
+    vec![&fullstitch(5.0, 0.0, 0, FullStitchKind::Full)]
+  );
+  assert_eq!(changes.added.len(), 1);
+  assert_eq!(changes.removed.len(), 1);
+}
+
+#[test]
+fn test_transform_area_replaces_overlapped_stitches() {
+  let mut pattern = Pattern::default();
+  pattern
+    .fullstitches
+    .insert(fullstitch(0.0, 0.0, 0, FullStitchKind::Full));
+  pattern
+    .fullstitches
+    .insert(fullstitch(1.0, 0.0, 1, FullStitchKind::Full));
+  pattern
+    .fullstitches
+    .insert(fullstitch(0.0, 1.0, 2, FullStitchKind::Full));
+  let original = pattern.fullstitches.iter().copied().collect::<Vec<_>>();
+
+  let area = Rect { x: 0, y: 0, width: 2, height: 1 };
+  let changes = pattern.transform(Transformation::Rotate90, Some(area));
+  assert_eq!(
+    pattern.fullstitches.iter().copied().collect::<Vec<_>>(),
+    vec![
+      fullstitch(0.0, 0.0, 0, FullStitchKind::Full),
+      fullstitch(0.0, 1.0, 1, FullStitchKind::Full),
+    ]
+  );
+  assert_eq!(changes.removed.len(), 3);
+
+  pattern.revert_stitch_changes(&changes);
+  assert_eq!(pattern.fullstitches.iter().copied().collect::<Vec<_>>(), original);
+}
@@ -0,0 +1,106 @@
+//! This module contains the algorithm of the automatic symbol assignment.
+
+use std::collections::HashSet;
+
+use anyhow::Result;
+
+use super::fonts::StitchFont;
+use super::pattern::display::Symbols;
+
+#[cfg(test)]
+#[path = "symbols.test.rs"]
+mod tests;
+
+/// Glyphs with less ink than this are too faint (dots, commas, etc.) to be recognized on a chart.
+const MIN_INK_DENSITY: f32 = 0.04;
+
+/// A glyph that can be used as a symbol.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SymbolCandidate {
+  pub code_point: u16,
+  pub glyph_id: u32,
+  /// See `StitchFont::ink_density`.
+  pub density: f32,
+}
+
+/// Collects the glyphs of the font that are legible enough to be used as symbols.
+/// The candidates are sorted from the most to the least contrasting ones.
+pub fn symbol_candidates(font: &StitchFont) -> Result<Vec<SymbolCandidate>> {
+  let mut seen_glyphs = HashSet::new();
+  let mut candidates = Vec::new();
+  for (code_point, glyph_id) in font.code_points() {
+    // Fonts often map several code points to the same glyph (e.g., the symbol fonts duplicate the ASCII range).
+    if !seen_glyphs.insert(glyph_id) {
+      continue;
+    }
+    let density = font.ink_density(glyph_id)?;
+    if density >= MIN_INK_DENSITY {
+      candidates.push(SymbolCandidate { code_point, glyph_id, density });
+    }
+  }
+  sort_candidates(&mut candidates);
+  Ok(candidates)
+}
+
+fn sort_candidates(candidates: &mut [SymbolCandidate]) {
+  candidates.sort_by(|a, b| b.density.total_cmp(&a.density).then(a.code_point.cmp(&b.code_point)));
+}
+
+/// Assigns distinct symbols to the palette items.
+///
+/// - `candidates` are the available glyphs sorted by their contrast (see `symbol_candidates`).
+/// - `symbols` are the current symbols of all the palette items.
+/// - `usage` is the number of stitches of each palette item.
+/// - `palindexes` are the palette items to assign the symbols to.
+///
+/// The most used colors get the most contrasting glyphs.
+/// The symbols of the other palette items are kept untouched and are never reused.
+/// Returns the new symbols of the palette items that could be assigned.
+pub fn assign_symbols(
+  candidates: &[SymbolCandidate],
+  symbols: &[Symbols],
+  usage: &[usize],
+  palindexes: &[u8],
+) -> Vec<(u8, Symbols)> {
+  let reserved = symbols
+    .iter()
+    .enumerate()
+    .filter(|(palindex, _)| !palindexes.contains(&(*palindex as u8)))
+    .flat_map(|(_, symbols)| {
+      [
+        symbols.full,
+        symbols.petite,
+        symbols.half,
+        symbols.quarter,
+        symbols.french_knot,
+        symbols.bead,
+      ]
+    })
+    .flatten()
+    .collect::<HashSet<_>>();
+
+  let mut palindexes = palindexes.to_vec();
+  palindexes.sort_unstable();
+  palindexes.dedup();
+  palindexes.sort_by_key(|&palindex| std::cmp::Reverse(usage.get(palindex as usize).copied().unwrap_or(0)));
+
+  let available = candidates
+    .iter()
+    .filter(|candidate| !reserved.contains(&candidate.code_point));
+  palindexes
+    .into_iter()
+    .zip(available)
+    .map(|(palindex, candidate)| {
+      let code_point = Some(candidate.code_point);
+      let symbols = Symbols {
+        full: code_point,
+        petite: code_point,
+        half: code_point,
+        quarter: code_point,
+        french_knot: code_point,
+        bead: code_point,
+      };
+      (palindex, symbols)
+    })
+    .collect()
+}
@@ -0,0 +1,92 @@
+use super::*;
+
+fn candidate(code_point: u16, density: f32) -> SymbolCandidate {
+  SymbolCandidate {
+    code_point,
+    glyph_id: code_point as u32,
+    density,
+  }
+}
+
+fn symbol(code_point: u16) -> Symbols {
+  let code_point = Some(code_point);
+  Symbols {
+    full: code_point,
+    petite: code_point,
+    half: code_point,
+    quarter: code_point,
+    french_knot: code_point,
+    bead: code_point,
+  }
+}
+
+#[test]
+fn test_sort_candidates() {
+  let mut candidates = vec![candidate(3, 0.1), candidate(2, 0.5), candidate(1, 0.1)];
+  sort_candidates(&mut candidates);
+  assert_eq!(
+    candidates,
+    vec![candidate(2, 0.5), candidate(1, 0.1), candidate(3, 0.1)]
+  );
+}
+
+#[test]
+fn test_assign_symbols_by_usage() {
+  let candidates = [candidate(65, 0.5), candidate(66, 0.3), candidate(67, 0.1)];
+  let symbols = vec![Symbols::default(); 3];
+  let usage = [10, 50, 30];
+
+  let assigned = assign_symbols(&candidates, &symbols, &usage, &[0, 1, 2]);
+  assert_eq!(assigned, vec![(1, symbol(65)), (2, symbol(66)), (0, symbol(67))]);
+}
+
+#[test]
+fn test_assign_symbols_avoids_duplicates() {
+  let candidates = [candidate(65, 0.5), candidate(66, 0.3), candidate(67, 0.1)];
+  let symbols = vec![
+    symbol(65),
+    Symbols::default(),
+    Symbols {
+      half: Some(66),
+      ..Symbols::default()
+    },
+  ];
+  let usage = [10, 50, 30];
+
+  // The symbols of the other palette items are reserved.
+  let assigned = assign_symbols(&candidates, &symbols, &usage, &[1]);
+  assert_eq!(assigned, vec![(1, symbol(67))]);
+
+  // The current symbols of the reassigned palette items can be reused.
+  let assigned = assign_symbols(&candidates, &symbols, &usage, &[0, 1, 1]);
+  assert_eq!(assigned, vec![(1, symbol(65)), (0, symbol(67))]);
+}
+
+#[test]
+fn test_assign_symbols_runs_out_of_candidates() {
+  let candidates = [candidate(65, 0.5)];
+  let symbols = vec![Symbols::default(); 2];
+  let usage = [10, 50];
+
+  let assigned = assign_symbols(&candidates, &symbols, &usage, &[0, 1]);
+  assert_eq!(assigned, vec![(1, symbol(65))]);
+}
+
+#[test]
+fn test_symbol_candidates() {
+  let font_path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("resources/fonts/ursasoftware.ttf");
+  let font = StitchFont::from_path(font_path).unwrap();
+  let candidates = symbol_candidates(&font).unwrap();
+
+  assert!(!candidates.is_empty());
+  assert!(candidates.windows(2).all(|pair| pair[0].density >= pair[1].density));
+  assert!(candidates.iter().all(|candidate| candidate.density >= MIN_INK_DENSITY));
+
+  let mut glyph_ids = candidates
+    .iter()
+    .map(|candidate| candidate.glyph_id)
+    .collect::<Vec<_>>();
+  glyph_ids.sort_unstable();
+  glyph_ids.dedup();
+  assert_eq!(glyph_ids.len(), candidates.len());
+}
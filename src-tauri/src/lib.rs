@@ -12,6 +12,7 @@ pub use core::pattern::*;
 
 mod error;
 mod logger;
+mod recovery;
 mod utils;
 
 pub fn setup_app<R: tauri::Runtime>(builder: tauri::Builder<R>) -> tauri::App<R> {
@@ -57,12 +58,24 @@ pub fn setup_app<R: tauri::Runtime>(builder: tauri::Builder<R>) -> tauri::App<R>
         }
       }
 
+      let thread_catalog = core::catalog::ThreadCatalog::load(
+        utils::path::thread_catalogs_dir(app.handle())?,
+        utils::path::custom_thread_catalogs_dir(app.handle())?,
+      )?;
+      app.manage(RwLock::new(thread_catalog));
+
+      recovery::spawn_autosave(app.handle().clone());
+
       Ok(())
     })
     .manage(RwLock::new(
       HashMap::<state::PatternKey, core::pattern::PatternProject>::new(),
     ))
     .manage(RwLock::new(HistoryStateInner::<R>::default()))
+    .manage(RwLock::new(HashMap::<state::PatternKey, Option<u64>>::new()))
+    .manage(RwLock::new(
+      HashMap::<state::PatternKey, core::pattern::DrawingMode>::new(),
+    ))
     .plugin(logger::setup_logger().build())
     .plugin(tauri_plugin_dialog::init())
     .plugin(tauri_plugin_fs::init())
@@ -72,21 +85,50 @@ pub fn setup_app<R: tauri::Runtime>(builder: tauri::Builder<R>) -> tauri::App<R>
       commands::pattern::load_pattern,
       commands::pattern::create_pattern,
       commands::pattern::save_pattern,
+      commands::pattern::update_pattern_info,
       commands::pattern::close_pattern,
       commands::pattern::get_pattern_file_path,
+      commands::pattern::is_pattern_dirty,
+      commands::convert::import_image,
+      commands::recovery::list_recoverable_patterns,
+      commands::recovery::restore_pattern,
+      commands::recovery::discard_recoverable_pattern,
       commands::display::set_display_mode,
       commands::display::show_symbols,
+      commands::display::update_display_setting,
       commands::fabric::update_fabric,
+      commands::fabric::resize_canvas,
+      commands::fabric::crop_canvas,
       commands::grid::update_grid,
+      commands::print::update_print_settings,
       commands::palette::add_palette_item,
       commands::palette::remove_palette_items,
       commands::palette::update_palette_display_settings,
+      commands::palette::update_palette_item_symbols,
+      commands::palette::update_palette_item_formats,
+      commands::palette::auto_assign_symbols,
+      commands::stitches::set_drawing_mode,
       commands::stitches::add_stitch,
       commands::stitches::remove_stitch,
+      commands::stitches::transform_pattern,
+      commands::stitches::optimize_lines,
+      commands::stitches::analyze_confetti,
+      commands::stitches::cleanup_confetti,
+      commands::stitches::get_stitches_in_region,
       commands::history::undo,
       commands::history::redo,
+      commands::history::get_history,
+      commands::history::jump_to_history,
+      commands::history::begin_transaction,
+      commands::history::commit_transaction,
+      commands::history::set_history_limits,
       commands::fonts::get_all_text_font_families,
       commands::fonts::load_stitch_font,
+      commands::fonts::get_stitch_font_glyphs,
+      commands::catalog::get_thread_catalogs,
+      commands::catalog::get_catalog,
+      commands::catalog::search_threads,
+      commands::catalog::reload_thread_catalogs,
     ])
     .build(tauri::generate_context!())
     .expect("Failed to build Embroidery Studio")
@@ -0,0 +1,218 @@
+//! Autosave and crash recovery.
+//!
+//! All the edits live only in memory until the user explicitly saves the pattern.
+//! To not lose hours of work on a crash of the webview or the whole process,
+//! the modified patterns are periodically written as EMBPROJ snapshots into the recovery directory.
+//!
+//! Every snapshot is accompanied by a JSON file with the information needed to restore the pattern.
+//! Both files are named after a hash of the pattern key, so each open pattern has at most one snapshot.
+
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::Result;
+use borsh::{BorshDeserialize, BorshSerialize};
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+
+use crate::core::parser::embproj;
+use crate::core::pattern::PatternProject;
+use crate::state::{PatternKey, PatternsState, RecoveryState};
+use crate::utils::fs::write_atomically;
+use crate::utils::path::app_recovery_dir;
+
+#[cfg(test)]
+#[path = "recovery.test.rs"]
+mod tests;
+
+/// How often the open patterns are checked for changes.
+const AUTOSAVE_INTERVAL: Duration = Duration::from_secs(60);
+
+const SNAPSHOT_EXTENSION: &str = "embproj";
+const METADATA_EXTENSION: &str = "json";
+
+/// Information about an autosaved snapshot of a pattern.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, BorshSerialize, BorshDeserialize)]
+pub struct RecoverablePattern {
+  pub pattern_key: PatternKey,
+  /// The path the pattern should be saved to after the recovery.
+  pub file_path: String,
+  pub title: String,
+  /// The time of the snapshot in seconds since the Unix epoch.
+  pub saved_at: u64,
+}
+
+/// Spawns a background thread that periodically writes snapshots of the modified patterns.
+pub fn spawn_autosave<R: tauri::Runtime>(app_handle: tauri::AppHandle<R>) {
+  std::thread::spawn(move || {
+    loop {
+      std::thread::sleep(AUTOSAVE_INTERVAL);
+      if let Err(err) = autosave(&app_handle) {
+        log::error!("Failed to autosave the patterns: {err:?}");
+      }
+    }
+  });
+}
+
+/// Writes the snapshots of the open patterns that have been modified since they were persisted.
+pub fn autosave<R: tauri::Runtime>(app_handle: &tauri::AppHandle<R>) -> Result<()> {
+  let recovery_dir = app_recovery_dir(app_handle)?;
+  let patterns = app_handle.state::<PatternsState>();
+  let recovery = app_handle.state::<RecoveryState>();
+
+  // The locks are never held at the same time, so they can't deadlock with the commands.
+  // Each pattern is only locked to copy it, so the user can continue editing while the snapshots are serialized and written.
+  let pattern_keys = patterns.read().unwrap().keys().cloned().collect::<Vec<_>>();
+  for pattern_key in pattern_keys {
+    // The pattern without an entry is being closed, so there is nothing to autosave.
+    let Some(persisted) = recovery.read().unwrap().get(&pattern_key).copied() else {
+      continue;
+    };
+    let Some(patproj) = patterns.read().unwrap().get(&pattern_key).cloned() else {
+      continue;
+    };
+    let fingerprint = fingerprint(&patproj)?;
+    if persisted == Some(fingerprint) {
+      continue;
+    }
+
+    // The recovery state is kept locked while writing the snapshot.
+    // If the pattern has been saved or closed since it was copied, the copy is stale, so it is skipped.
+    let mut recovery = recovery.write().unwrap();
+    if recovery.get(&pattern_key) != Some(&persisted) {
+      continue;
+    }
+
+    log::debug!("Autosaving the pattern {}", pattern_key.as_ref());
+    match write_snapshot(&recovery_dir, &pattern_key, &patproj, app_handle.package_info()) {
+      Ok(()) => {
+        recovery.insert(pattern_key, Some(fingerprint));
+      }
+      Err(err) => log::error!("Failed to autosave the pattern {}: {err:?}", pattern_key.as_ref()),
+    }
+  }
+
+  Ok(())
+}
+
+/// Remembers the current state of the pattern as the persisted one, so it isn't autosaved until it is modified.
+pub fn mark_persisted(recovery: &RecoveryState, pattern_key: &PatternKey, patproj: &PatternProject) -> Result<()> {
+  recovery
+    .write()
+    .unwrap()
+    .insert(pattern_key.clone(), Some(fingerprint(patproj)?));
+  Ok(())
+}
+
+/// Starts tracking the restored pattern, which only exists in its snapshot, so it is autosaved until it is saved.
+pub fn mark_restored(recovery: &RecoveryState, pattern_key: &PatternKey) {
+  recovery.write().unwrap().insert(pattern_key.clone(), None);
+}
+
+/// Removes the pattern from the recovery state and deletes its snapshot.
+pub fn forget<R: tauri::Runtime>(
+  app_handle: &tauri::AppHandle<R>,
+  recovery: &RecoveryState,
+  pattern_key: &PatternKey,
+) -> Result<()> {
+  recovery.write().unwrap().remove(pattern_key);
+  remove_snapshot(&app_recovery_dir(app_handle)?, pattern_key)
+}
+
+/// Returns all the snapshots that are left in the recovery directory.
+/// The most recent snapshots come first.
+pub fn list_snapshots(recovery_dir: &Path) -> Result<Vec<RecoverablePattern>> {
+  let mut snapshots = Vec::new();
+  if !recovery_dir.exists() {
+    return Ok(snapshots);
+  }
+
+  for entry in std::fs::read_dir(recovery_dir)? {
+    let path = entry?.path();
+    if path.extension().is_none_or(|ext| ext != METADATA_EXTENSION) || !path.with_extension(SNAPSHOT_EXTENSION).exists()
+    {
+      continue;
+    }
+
+    match serde_json::from_slice::<RecoverablePattern>(&std::fs::read(&path)?) {
+      Ok(snapshot) => snapshots.push(snapshot),
+      Err(err) => log::warn!("Skipping the corrupted snapshot metadata {path:?}: {err:?}"),
+    }
+  }
+
+  snapshots.sort_by(|a, b| b.saved_at.cmp(&a.saved_at));
+  Ok(snapshots)
+}
+
+/// Loads the pattern from its snapshot.
+/// The file path of the restored pattern is set to the one it had before the crash.
+pub fn read_snapshot(recovery_dir: &Path, pattern_key: &PatternKey) -> Result<PatternProject> {
+  let (snapshot_path, metadata_path) = snapshot_paths(recovery_dir, pattern_key);
+  let metadata: RecoverablePattern = serde_json::from_slice(&std::fs::read(metadata_path)?)?;
+  let mut patproj = embproj::parse_pattern_from_reader(std::fs::File::open(snapshot_path)?)?;
+  patproj.file_path = PathBuf::from(metadata.file_path);
+  Ok(patproj)
+}
+
+/// Writes the snapshot of the pattern and its metadata into the recovery directory.
+pub fn write_snapshot(
+  recovery_dir: &Path,
+  pattern_key: &PatternKey,
+  patproj: &PatternProject,
+  package_info: &tauri::PackageInfo,
+) -> Result<()> {
+  std::fs::create_dir_all(recovery_dir)?;
+  let (snapshot_path, metadata_path) = snapshot_paths(recovery_dir, pattern_key);
+
+  write_atomically(&snapshot_path, 0, |file| {
+    embproj::save_pattern_to_writer(file, patproj, None, package_info)
+  })?;
+
+  let metadata = RecoverablePattern {
+    pattern_key: pattern_key.clone(),
+    file_path: patproj.file_path.to_string_lossy().to_string(),
+    title: patproj.pattern.info.title.clone(),
+    saved_at: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+  };
+  write_atomically(&metadata_path, 0, |file| {
+    serde_json::to_writer(file, &metadata)?;
+    Ok(())
+  })?;
+
+  Ok(())
+}
+
+/// Deletes the snapshot of the pattern if it exists.
+pub fn remove_snapshot(recovery_dir: &Path, pattern_key: &PatternKey) -> Result<()> {
+  let (snapshot_path, metadata_path) = snapshot_paths(recovery_dir, pattern_key);
+  for path in [metadata_path, snapshot_path] {
+    match std::fs::remove_file(path) {
+      Err(err) if err.kind() != std::io::ErrorKind::NotFound => return Err(err.into()),
+      _ => {}
+    }
+  }
+  Ok(())
+}
+
+fn snapshot_paths(recovery_dir: &Path, pattern_key: &PatternKey) -> (PathBuf, PathBuf) {
+  let stem = format!("{:016x}", fnv1a(pattern_key.as_ref().as_bytes()));
+  (
+    recovery_dir.join(&stem).with_extension(SNAPSHOT_EXTENSION),
+    recovery_dir.join(&stem).with_extension(METADATA_EXTENSION),
+  )
+}
+
+/// Calculates a cheap fingerprint of the pattern content to detect its changes.
+fn fingerprint(patproj: &PatternProject) -> Result<u64> {
+  Ok(fnv1a(&borsh::to_vec(patproj)?))
+}
+
+/// The FNV-1a hash function.
+/// Unlike the `DefaultHasher`, it is guaranteed to be stable between the application runs and Rust releases.
+fn fnv1a(bytes: &[u8]) -> u64 {
+  const OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+  const PRIME: u64 = 0x100000001b3;
+  bytes
+    .iter()
+    .fold(OFFSET_BASIS, |hash, &byte| (hash ^ byte as u64).wrapping_mul(PRIME))
+}
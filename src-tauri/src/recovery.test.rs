@@ -0,0 +1,38 @@
+use tauri::generate_context;
+use tauri::test::mock_builder;
+
+use super::*;
+use crate::core::parser::oxs;
+
+fn create_pattern_project() -> PatternProject {
+  let file_path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("testdata/patterns/rainbow.oxs");
+  oxs::parse_pattern(file_path).unwrap()
+}
+
+#[test]
+fn writes_lists_and_removes_snapshots() {
+  let app = mock_builder().build(generate_context!()).unwrap();
+  let recovery_dir = tempfile::tempdir().unwrap();
+  let recovery_dir = recovery_dir.path();
+
+  let patproj = create_pattern_project();
+  let pattern_key = PatternKey::from(&patproj.file_path);
+
+  assert!(list_snapshots(recovery_dir).unwrap().is_empty());
+
+  write_snapshot(recovery_dir, &pattern_key, &patproj, app.package_info()).unwrap();
+  let snapshots = list_snapshots(recovery_dir).unwrap();
+  assert_eq!(snapshots.len(), 1);
+  assert_eq!(snapshots[0].pattern_key, pattern_key);
+  assert_eq!(snapshots[0].title, patproj.pattern.info.title);
+
+  let restored = read_snapshot(recovery_dir, &pattern_key).unwrap();
+  assert_eq!(restored.file_path, patproj.file_path);
+  assert_eq!(restored.pattern.palette, patproj.pattern.palette);
+
+  remove_snapshot(recovery_dir, &pattern_key).unwrap();
+  assert!(list_snapshots(recovery_dir).unwrap().is_empty());
+
+  // Removing a missing snapshot is not an error.
+  remove_snapshot(recovery_dir, &pattern_key).unwrap();
+}
@@ -4,8 +4,9 @@ use std::path::PathBuf;
 use borsh::{BorshDeserialize, BorshSerialize};
 use serde::{Deserialize, Serialize};
 
-use crate::core::history::History;
-use crate::core::pattern::PatternProject;
+use crate::core::catalog::ThreadCatalog;
+use crate::core::history::{History, HistoryLimits};
+use crate::core::pattern::{DrawingMode, PatternProject};
 
 #[derive(Debug, Hash, PartialEq, Eq, Clone, Serialize, Deserialize, BorshSerialize, BorshDeserialize)]
 #[repr(transparent)]
@@ -31,6 +32,8 @@ impl From<&PathBuf> for PatternKey {
 
 pub struct HistoryStateInner<R: tauri::Runtime> {
   inner: HashMap<PatternKey, History<R>>,
+  /// The limits applied to the histories of all the patterns.
+  limits: HistoryLimits,
 }
 
 impl<R: tauri::Runtime> HistoryStateInner<R> {
@@ -39,15 +42,45 @@ impl<R: tauri::Runtime> HistoryStateInner<R> {
   }
 
   pub fn get_mut(&mut self, key: &PatternKey) -> &mut History<R> {
-    self.inner.entry(key.clone()).or_default()
+    let limits = self.limits;
+    self.inner.entry(key.clone()).or_insert_with(|| History::new(limits))
+  }
+
+  pub fn insert(&mut self, key: PatternKey, history: History<R>) {
+    self.inner.insert(key, history);
+  }
+
+  pub fn remove(&mut self, key: &PatternKey) -> Option<History<R>> {
+    self.inner.remove(key)
+  }
+
+  pub fn limits(&self) -> HistoryLimits {
+    self.limits
+  }
+
+  pub fn set_limits(&mut self, limits: HistoryLimits) {
+    self.limits = limits;
+    for history in self.inner.values_mut() {
+      history.set_limits(limits);
+    }
   }
 }
 
 impl<R: tauri::Runtime> Default for HistoryStateInner<R> {
   fn default() -> Self {
-    Self { inner: HashMap::new() }
+    Self {
+      inner: HashMap::new(),
+      limits: HistoryLimits::default(),
+    }
   }
 }
 
 pub type PatternsState = std::sync::RwLock<HashMap<PatternKey, PatternProject>>;
 pub type HistoryState<R> = std::sync::RwLock<HistoryStateInner<R>>;
+/// Fingerprints of the last persisted (saved or autosaved) state of every open pattern.
+/// It is `None` for a restored pattern that hasn't been persisted yet. Closed patterns have no entry.
+pub type RecoveryState = std::sync::RwLock<HashMap<PatternKey, Option<u64>>>;
+/// The drawing modes of the open patterns. Patterns without an entry are drawn in the normal mode.
+pub type DrawingModesState = std::sync::RwLock<HashMap<PatternKey, DrawingMode>>;
+/// The bundled and custom thread catalogs.
+pub type ThreadCatalogState = std::sync::RwLock<ThreadCatalog>;
@@ -0,0 +1,73 @@
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+
+#[cfg(test)]
+#[path = "fs.test.rs"]
+mod tests;
+
+/// Writes the file atomically.
+///
+/// The content is written into a temporary file in the same directory, flushed to the disk and then renamed over the target file.
+/// This way, the target file is either left untouched or completely replaced, even if the writing fails in the middle or the process crashes.
+///
+/// If `backups` is greater than zero, that many previous versions of the file are kept as `<file>.1.bak`, `<file>.2.bak`, etc.
+/// The lower the number, the more recent the version is.
+pub fn write_atomically<F>(file_path: &Path, backups: usize, write: F) -> Result<()>
+where
+  F: FnOnce(&mut File) -> Result<()>,
+{
+  let dir_path = match file_path.parent() {
+    Some(parent) if !parent.as_os_str().is_empty() => parent,
+    _ => Path::new("."),
+  };
+
+  // The temporary file is removed automatically if anything goes wrong before it is persisted.
+  let mut temp_file = tempfile::NamedTempFile::new_in(dir_path)?;
+  write(temp_file.as_file_mut())?;
+  temp_file.as_file().sync_all()?;
+
+  if let Ok(metadata) = std::fs::metadata(file_path) {
+    // The temporary files are created with restricted permissions, so we preserve the permissions of the original file.
+    temp_file.as_file().set_permissions(metadata.permissions())?;
+    if backups > 0 {
+      rotate_backups(file_path, backups)?;
+    }
+  }
+
+  temp_file.persist(file_path)?;
+
+  // Make sure the rename itself is durable.
+  #[cfg(unix)]
+  File::open(dir_path)?.sync_all()?;
+
+  Ok(())
+}
+
+/// Returns the path of the backup with the given index.
+pub fn backup_path(file_path: &Path, index: usize) -> PathBuf {
+  let mut file_name = file_path.file_name().unwrap_or_default().to_os_string();
+  file_name.push(format!(".{index}.bak"));
+  file_path.with_file_name(file_name)
+}
+
+/// Shifts the existing backups by one, dropping the oldest one, and backs up the current version of the file.
+fn rotate_backups(file_path: &Path, backups: usize) -> Result<()> {
+  let oldest_backup = backup_path(file_path, backups);
+  if oldest_backup.exists() {
+    std::fs::remove_file(oldest_backup)?;
+  }
+
+  for index in (1..backups).rev() {
+    let backup = backup_path(file_path, index);
+    if backup.exists() {
+      std::fs::rename(backup, backup_path(file_path, index + 1))?;
+    }
+  }
+
+  // Copy instead of renaming, so the original file stays in place until it is replaced.
+  std::fs::copy(file_path, backup_path(file_path, 1))?;
+
+  Ok(())
+}
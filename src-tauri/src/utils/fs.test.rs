@@ -0,0 +1,53 @@
+use std::io::Write;
+
+use super::*;
+
+#[test]
+fn writes_file_atomically() {
+  let dir = tempfile::tempdir().unwrap();
+  let file_path = dir.path().join("pattern.oxs");
+
+  write_atomically(&file_path, 0, |file| Ok(file.write_all(b"first")?)).unwrap();
+  assert_eq!(std::fs::read(&file_path).unwrap(), b"first");
+
+  write_atomically(&file_path, 0, |file| Ok(file.write_all(b"second")?)).unwrap();
+  assert_eq!(std::fs::read(&file_path).unwrap(), b"second");
+
+  // No backups or temporary files are left.
+  assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+}
+
+#[test]
+fn keeps_original_file_on_failure() {
+  let dir = tempfile::tempdir().unwrap();
+  let file_path = dir.path().join("pattern.oxs");
+  std::fs::write(&file_path, b"original").unwrap();
+
+  let result = write_atomically(&file_path, 1, |file| {
+    file.write_all(b"corrupted")?;
+    anyhow::bail!("Serialization error")
+  });
+  assert!(result.is_err());
+
+  assert_eq!(std::fs::read(&file_path).unwrap(), b"original");
+  assert!(!backup_path(&file_path, 1).exists());
+  assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+}
+
+#[test]
+fn rotates_backups() {
+  let dir = tempfile::tempdir().unwrap();
+  let file_path = dir.path().join("pattern.oxs");
+
+  for version in 1..=4 {
+    write_atomically(&file_path, 2, |file| {
+      Ok(file.write_all(version.to_string().as_bytes())?)
+    })
+    .unwrap();
+  }
+
+  assert_eq!(std::fs::read(&file_path).unwrap(), b"4");
+  assert_eq!(std::fs::read(backup_path(&file_path, 1)).unwrap(), b"3");
+  assert_eq!(std::fs::read(backup_path(&file_path, 2)).unwrap(), b"2");
+  assert!(!backup_path(&file_path, 3).exists());
+}
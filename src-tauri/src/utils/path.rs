@@ -1,5 +1,6 @@
 use std::path::PathBuf;
 
+use convert_case::{Case, Casing};
 use tauri::Manager;
 
 pub fn app_document_dir<R: tauri::Runtime>(app_handle: &tauri::AppHandle<R>) -> anyhow::Result<PathBuf> {
@@ -15,3 +16,39 @@ pub fn app_document_dir<R: tauri::Runtime>(app_handle: &tauri::AppHandle<R>) ->
   };
   Ok(dir_path.join(app_name))
 }
+
+/// Returns the directory where autosaved snapshots of the open patterns are stored.
+pub fn app_recovery_dir<R: tauri::Runtime>(app_handle: &tauri::AppHandle<R>) -> anyhow::Result<PathBuf> {
+  let dir_path = if cfg!(test) {
+    std::env::temp_dir().join(app_handle.config().identifier.clone())
+  } else {
+    app_handle.path().app_data_dir()?
+  };
+  Ok(dir_path.join("recovery"))
+}
+
+/// Returns the path to the bundled stitch font of the given family.
+pub fn stitch_font_path<R: tauri::Runtime>(
+  app_handle: &tauri::AppHandle<R>,
+  font_family: &str,
+) -> anyhow::Result<PathBuf> {
+  let font_family = font_family.to_case(Case::Snake);
+  let font_path = app_handle.path().resolve(
+    format!("resources/fonts/{font_family}.ttf"),
+    tauri::path::BaseDirectory::Resource,
+  )?;
+  Ok(font_path)
+}
+
+/// Returns the directory of the bundled thread catalogs.
+pub fn thread_catalogs_dir<R: tauri::Runtime>(app_handle: &tauri::AppHandle<R>) -> anyhow::Result<PathBuf> {
+  let dir_path = app_handle
+    .path()
+    .resolve("resources/palettes/", tauri::path::BaseDirectory::Resource)?;
+  Ok(dir_path)
+}
+
+/// Returns the directory where users can put their own thread catalogs.
+pub fn custom_thread_catalogs_dir<R: tauri::Runtime>(app_handle: &tauri::AppHandle<R>) -> anyhow::Result<PathBuf> {
+  Ok(app_document_dir(app_handle)?.join("palettes"))
+}
@@ -1,5 +1,6 @@
 use embroidery_studio::state::{PatternKey, PatternsState};
-use embroidery_studio::{Fabric, setup_app};
+use embroidery_studio::{Fabric, FullStitch, FullStitchKind, Stitch, setup_app};
+use ordered_float::NotNan;
 use tauri::Manager;
 use tauri::http::{HeaderMap, HeaderValue};
 use tauri::test::{INVOKE_KEY, MockRuntime, get_ipc_response, mock_builder};
@@ -214,3 +215,77 @@ fn closes_pattern() {
   );
   assert!(patterns_state.read().unwrap().is_empty());
 }
+
+#[test]
+fn does_not_close_pattern_with_unsaved_changes() {
+  let app = setup_app::<MockRuntime>(mock_builder());
+  let webview = tauri::WebviewWindowBuilder::new(&app, "main", Default::default())
+    .build()
+    .unwrap();
+  let patterns_state = app.handle().state::<PatternsState>();
+
+  assert!(
+    get_ipc_response(
+      &webview,
+      tauri::webview::InvokeRequest {
+        cmd: "create_pattern".to_string(),
+        callback: tauri::ipc::CallbackFn(0),
+        error: tauri::ipc::CallbackFn(1),
+        url: "http://tauri.localhost".parse().unwrap(),
+        body: tauri::ipc::InvokeBody::Raw(borsh::to_vec(&Fabric::default()).unwrap()),
+        headers: HeaderMap::default(),
+        invoke_key: INVOKE_KEY.to_string(),
+      },
+    )
+    .is_ok()
+  );
+
+  let pattern_key = patterns_state.read().unwrap().keys().next().unwrap().to_owned();
+  let stitch = Stitch::Full(FullStitch {
+    x: NotNan::new(0.0).unwrap(),
+    y: NotNan::new(0.0).unwrap(),
+    palindex: 0,
+    kind: FullStitchKind::Full,
+  });
+  assert!(
+    get_ipc_response(
+      &webview,
+      tauri::webview::InvokeRequest {
+        cmd: "add_stitch".to_string(),
+        callback: tauri::ipc::CallbackFn(0),
+        error: tauri::ipc::CallbackFn(1),
+        url: "http://tauri.localhost".parse().unwrap(),
+        body: tauri::ipc::InvokeBody::Raw(borsh::to_vec(&stitch).unwrap()),
+        headers: {
+          let mut headers = HeaderMap::new();
+          headers.insert("patternKey", HeaderValue::from_str(pattern_key.as_ref()).unwrap());
+          headers
+        },
+        invoke_key: INVOKE_KEY.to_string(),
+      },
+    )
+    .is_ok()
+  );
+
+  for (force, closed) in [(false, false), (true, true)] {
+    let response = get_ipc_response(
+      &webview,
+      tauri::webview::InvokeRequest {
+        cmd: "close_pattern".to_string(),
+        callback: tauri::ipc::CallbackFn(0),
+        error: tauri::ipc::CallbackFn(1),
+        url: "http://tauri.localhost".parse().unwrap(),
+        body: tauri::ipc::InvokeBody::default(),
+        headers: {
+          let mut headers = HeaderMap::new();
+          headers.insert("patternKey", HeaderValue::from_str(pattern_key.as_ref()).unwrap());
+          headers.insert("force", HeaderValue::from_str(&force.to_string()).unwrap());
+          headers
+        },
+        invoke_key: INVOKE_KEY.to_string(),
+      },
+    );
+    assert_eq!(response.unwrap().deserialize::<bool>().unwrap(), closed);
+    assert_eq!(patterns_state.read().unwrap().contains_key(&pattern_key), !closed);
+  }
+}
@@ -2,8 +2,12 @@ import { invoke } from "@tauri-apps/api/core";
 import { serialize } from "@dao-xyz/borsh";
 import { type PatternKey, Fabric, deserializePatternProject } from "#/schemas/pattern";
 
-export async function loadPattern(filePath: string) {
-  const bytes = await invoke<number[]>("load_pattern", undefined, { headers: { filePath } });
+/**
+ * Loads the pattern from the file.
+ * If `lazy` is `true`, the pattern is loaded without stitches, so they can be requested by regions via `getStitchesInRegion`.
+ */
+export async function loadPattern(filePath: string, lazy = false) {
+  const bytes = await invoke<number[]>("load_pattern", undefined, { headers: { filePath, lazy: String(lazy) } });
   return deserializePatternProject(new Uint8Array(bytes));
 }
 
@@ -16,8 +20,12 @@ export function savePattern(patternKey: PatternKey, filePath: string) {
   return invoke<void>("save_pattern", undefined, { headers: { patternKey, filePath } });
 }
 
-export function closePattern(patternKey: PatternKey) {
-  return invoke<void>("close_pattern", undefined, { headers: { patternKey } });
+/**
+ * Closes the pattern.
+ * Returns `false` without closing the pattern if it has unsaved changes, unless `force` is `true`.
+ */
+export function closePattern(patternKey: PatternKey, force = false) {
+  return invoke<boolean>("close_pattern", undefined, { headers: { patternKey, force: String(force) } });
 }
 
 export function getPatternFilePath(patternKey: PatternKey) {
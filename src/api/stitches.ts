@@ -1,5 +1,6 @@
 import { invoke } from "@tauri-apps/api/core";
-import { serializeStitch, type PatternKey, type Stitch } from "#/schemas/pattern";
+import { deserialize, serialize } from "@dao-xyz/borsh";
+import { serializeStitch, PatternRegion, Rect, type PatternKey, type Stitch } from "#/schemas/pattern";
 
 export function addStitch(patternKey: PatternKey, stitch: Stitch) {
   return invoke<boolean>("add_stitch", serializeStitch(stitch), { headers: { patternKey } });
@@ -8,3 +9,8 @@ export function addStitch(patternKey: PatternKey, stitch: Stitch) {
 export function removeStitch(patternKey: PatternKey, stitch: Stitch) {
   return invoke<boolean>("remove_stitch", serializeStitch(stitch), { headers: { patternKey } });
 }
+
+export async function getStitchesInRegion(patternKey: PatternKey, area: Rect) {
+  const bytes = await invoke<ArrayBuffer>("get_stitches_in_region", serialize(area), { headers: { patternKey } });
+  return deserialize(new Uint8Array(bytes), PatternRegion);
+}
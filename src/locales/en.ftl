@@ -13,6 +13,7 @@ label-save-changes = Save Changes
 label-close = Close
 label-export = Export
 label-cancel = Cancel
+label-discard = Discard
 
 ## Names of the stitches and other instruments.
 
@@ -143,3 +144,8 @@ label-minor-lines = Minor Lines
 
 label-color = Color
 label-thickness = Thickness
+
+## Titles, labels and messages related to closing the patterns.
+
+title-unsaved-changes = Unsaved Changes
+message-unsaved-changes = The pattern "{ $title }" has unsaved changes. Do you want to save them before closing it?
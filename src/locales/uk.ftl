@@ -13,6 +13,7 @@ label-save-changes = Зберегти зміни
 label-close = Закрити
 label-export = Експортувати
 label-cancel = Скасувати
+label-discard = Не зберігати
 
 ## Names of the stitches and other instruments.
 
@@ -146,3 +147,8 @@ label-minor-lines = Малі лінії
 
 label-color = Колір
 label-thickness = Товщина
+
+## Titles, labels and messages related to closing the patterns.
+
+title-unsaved-changes = Незбережені зміни
+message-unsaved-changes = Схема "{ $title }" має незбережені зміни. Зберегти їх перед закриттям?
@@ -2,5 +2,8 @@ import type { StrokeInput } from "pixi.js";
 
 export const STITCH_SCALE_FACTOR = 1 / 100;
 
+/** The size (in cells) of the square tiles by which the stitches of lazily loaded patterns are requested. */
+export const REGION_TILE_SIZE = 64;
+
 export const GRAPHICS_STROKE: StrokeInput = { pixelLine: true, alignment: 0.5, color: 0x000000 };
 export const TEXTURE_STROKE: StrokeInput = { width: 2, alignment: 0.5, color: 0x000000 };
@@ -3,7 +3,7 @@ import type { ApplicationOptions, ColorSource, FederatedPointerEvent, Point } fr
 import { Viewport } from "pixi-viewport";
 import { PatternView } from "./pattern-view";
 import { TextureManager, StitchGraphics, STITCH_SCALE_FACTOR, StitchParticleContainer } from "#/plugins/pixi";
-import type { Bead, LineStitch, NodeStitch, Stitch, StitchKind } from "#/schemas/pattern";
+import type { Bead, LineStitch, NodeStitch, PatternKey, Stitch, StitchKind } from "#/schemas/pattern";
 
 const DEFAULT_INIT_OPTIONS: Partial<ApplicationOptions> = {
   eventFeatures: { globalMove: false },
@@ -14,6 +14,7 @@ const DEFAULT_INIT_OPTIONS: Partial<ApplicationOptions> = {
 export class PatternCanvas extends EventTarget {
   #pixi = new Application();
   #viewport!: Viewport;
+  #patternKey: PatternKey | undefined = undefined;
 
   #startPoint: Point | undefined = undefined;
   #hint = new Graphics();
@@ -41,6 +42,8 @@ export class PatternCanvas extends EventTarget {
     this.#viewport.on("pointerdown", this.#onPointerDown, this);
     this.#viewport.on("pointermove", this.#onPointerMove, this);
     this.#viewport.on("pointerup", this.#onPointerUp, this);
+    this.#viewport.on("moved", this.#fireVisibleAreaChangeEvent, this);
+    this.#viewport.on("zoomed", this.#fireVisibleAreaChangeEvent, this);
   }
 
   setPatternView(pattern: PatternView) {
@@ -53,8 +56,13 @@ export class PatternCanvas extends EventTarget {
     this.#viewport.worldWidth = width;
     this.#viewport.worldHeight = height;
 
-    this.#viewport.fitHeight();
-    this.#viewport.moveCenter(width / 2, height / 2);
+    // Keep the current viewport when the same pattern is reloaded (e.g., after it has been transformed).
+    if (this.#patternKey !== pattern.key) {
+      this.#viewport.fitHeight();
+      this.#viewport.moveCenter(width / 2, height / 2);
+      this.#patternKey = pattern.key;
+    }
+    this.#fireVisibleAreaChangeEvent();
   }
 
   clear() {
@@ -64,6 +72,7 @@ export class PatternCanvas extends EventTarget {
   resize({ width, height }: CanvasSize) {
     this.#pixi.renderer.resize(width, height);
     this.#viewport.resize(width, height);
+    this.#fireVisibleAreaChangeEvent();
   }
 
   drawLineHint(line: LineStitch, color: ColorSource) {
@@ -103,6 +112,12 @@ export class PatternCanvas extends EventTarget {
     return this.#viewport.addChild(this.#hint);
   }
 
+  #fireVisibleAreaChangeEvent() {
+    const { x, y, width, height } = this.#viewport.getVisibleBounds();
+    const detail: VisibleArea = { x, y, width, height };
+    this.dispatchEvent(new CustomEvent(EventType.VisibleAreaChange, { detail }));
+  }
+
   #fireAddStitchEvent(e: FederatedPointerEvent, stage: AddStitchEventStage) {
     const point = this.#viewport.toWorld(e.global);
     if (this.#pointIsOutside(point)) return;
@@ -173,6 +188,7 @@ export interface CanvasSize {
 export const enum EventType {
   AddStitch = "add_stitch",
   RemoveStitch = "remove_stitch",
+  VisibleAreaChange = "visible_area_change",
 }
 
 export const enum AddStitchEventStage {
@@ -205,4 +221,12 @@ export interface AddStitchData {
   fixed: boolean;
 }
 
+/** Represents the data for the `VisibleAreaChange` event, i.e. the area of the pattern (in cells) shown by the canvas. */
+export interface VisibleArea {
+  x: number;
+  y: number;
+  width: number;
+  height: number;
+}
+
 export type RemoveStitchData = { stitch: Stitch } | { point: Point; kind: StitchKind };
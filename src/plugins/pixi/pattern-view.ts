@@ -1,5 +1,13 @@
 import { Container, Graphics, Particle } from "pixi.js";
-import { TextureManager, StitchGraphics, STITCH_SCALE_FACTOR, StitchParticleContainer, Symbol } from "#/plugins/pixi";
+import {
+  TextureManager,
+  StitchGraphics,
+  STITCH_SCALE_FACTOR,
+  REGION_TILE_SIZE,
+  StitchParticleContainer,
+  Symbol,
+} from "#/plugins/pixi";
+import type { VisibleArea } from "#/plugins/pixi";
 import { ObjectedMap } from "#/utils/map";
 import {
   AddedPaletteItemData,
@@ -12,17 +20,24 @@ import {
   PartStitch,
   NodeStitch,
   DisplayMode,
+  Rect,
 } from "#/schemas/pattern";
 import type {
+  DisplaySetting,
+  DisplaySettings,
   Fabric,
+  Formats,
   Grid,
   PatternInfo,
   PatternKey,
   PatternProject,
+  PatternRegion,
   PaletteSettings,
+  PrintSettings,
   SpecialStitch,
   SpecialStitchModel,
   Stitch,
+  Symbols,
 } from "#/schemas/pattern";
 
 /**
@@ -36,6 +51,9 @@ export class PatternView {
   #palette: CompletePaletteItem[];
   paletteDisplaySettings: PaletteSettings;
 
+  #displaySettings: DisplaySettings;
+  printSettings: PrintSettings;
+
   #fabric: Fabric;
   #grid: Grid;
 
@@ -54,14 +72,19 @@ export class PatternView {
   // They are more complex and require more control over their rendering.
   #lines: ObjectedMap<LineStitch, StitchGraphics | undefined>;
   #nodes: ObjectedMap<NodeStitch, StitchGraphics | undefined>;
+  #specialstitches: ObjectedMap<SpecialStitch, Graphics | undefined>;
 
   readonly defaultStitchFont: string;
 
   #symbols = new ObjectedMap<Stitch, Symbol>();
 
-  #specialstitches: SpecialStitch[];
   #specialStitchModels: SpecialStitchModel[];
 
+  // If the pattern is loaded without stitches, they are requested by tiles as the canvas shows them.
+  // The tiles are stored as `x:y` keys of their indexes.
+  #lazy: boolean;
+  #requestedTiles = new Set<string>();
+
   #stages = {
     // lowest
     fabric: new Graphics(),
@@ -77,7 +100,7 @@ export class PatternView {
     // highest
   };
 
-  constructor({ key, pattern, displaySettings }: PatternProject) {
+  constructor({ key, pattern, displaySettings, printSettings }: PatternProject, lazy = false) {
     this.#key = key;
     this.#info = pattern.info;
 
@@ -89,6 +112,9 @@ export class PatternView {
     });
     this.paletteDisplaySettings = displaySettings.paletteSettings;
 
+    this.#displaySettings = displaySettings;
+    this.printSettings = printSettings;
+
     this.#fabric = pattern.fabric;
     this.#grid = displaySettings.grid;
 
@@ -104,10 +130,11 @@ export class PatternView {
     this.#lines = ObjectedMap.withKeys(pattern.lines);
     this.#nodes = ObjectedMap.withKeys(pattern.nodes);
 
-    this.#specialstitches = pattern.specialstitches;
+    this.#specialstitches = ObjectedMap.withKeys(pattern.specialstitches);
     this.#specialStitchModels = pattern.specialStitchModels;
 
     this.defaultStitchFont = displaySettings.defaultStitchFont;
+    this.#lazy = lazy;
   }
 
   render() {
@@ -120,7 +147,48 @@ export class PatternView {
     for (const partstitch of this.#partstitches.keys()) this.addStitch(partstitch);
     for (const line of this.#lines.keys()) this.addStitch(line);
     for (const node of this.#nodes.keys()) this.addStitch(node);
-    for (const specialstitch of this.#specialstitches) this.addSpecialStitch(specialstitch);
+    for (const specialstitch of this.#specialstitches.keys()) this.addSpecialStitch(specialstitch);
+  }
+
+  /**
+   * Returns the tiles touching the area whose stitches haven't been requested yet, and marks them as requested.
+   * It returns nothing if the pattern is loaded along with all its stitches.
+   */
+  takeUnrequestedTiles(area: VisibleArea): Rect[] {
+    if (!this.#lazy) return [];
+    const size = REGION_TILE_SIZE;
+    const { width, height } = this.#fabric;
+    const left = Math.max(0, Math.floor(area.x / size));
+    const top = Math.max(0, Math.floor(area.y / size));
+    const right = Math.min(Math.ceil(width / size), Math.ceil((area.x + area.width) / size));
+    const bottom = Math.min(Math.ceil(height / size), Math.ceil((area.y + area.height) / size));
+
+    const tiles: Rect[] = [];
+    for (let tileY = top; tileY < bottom; tileY++) {
+      for (let tileX = left; tileX < right; tileX++) {
+        const key = `${tileX}:${tileY}`;
+        if (this.#requestedTiles.has(key)) continue;
+        this.#requestedTiles.add(key);
+
+        const [x, y] = [tileX * size, tileY * size];
+        tiles.push(new Rect({ x, y, width: Math.min(size, width - x), height: Math.min(size, height - y) }));
+      }
+    }
+    return tiles;
+  }
+
+  /**
+   * Adds the stitches of the region that are not displayed yet.
+   * It skips the lines crossing the already loaded tiles and the stitches that were added while the tile was loading.
+   */
+  addRegion(region: PatternRegion) {
+    for (const full of region.fullstitches) if (!this.#fullstitches.has(full)) this.addStitch(full);
+    for (const part of region.partstitches) if (!this.#partstitches.has(part)) this.addStitch(part);
+    for (const line of region.lines) if (!this.#lines.has(line)) this.addStitch(line);
+    for (const node of region.nodes) if (!this.#nodes.has(node)) this.addStitch(node);
+    for (const special of region.specialstitches) {
+      if (!this.#specialstitches.has(special)) this.addSpecialStitch(special);
+    }
   }
 
   setDisplayMode(displayMode: DisplayMode | undefined) {
@@ -154,6 +222,19 @@ export class PatternView {
     return this.#info;
   }
 
+  setInfo(info: PatternInfo) {
+    this.#info = info;
+  }
+
+  get displaySettings() {
+    return this.#displaySettings;
+  }
+
+  updateDisplaySetting(setting: DisplaySetting) {
+    Object.assign(this.#displaySettings, setting);
+    if (setting.showSymbols !== undefined) this.setShowSymbols(setting.showSymbols);
+  }
+
   get stages() {
     return Object.values(this.#stages);
   }
@@ -230,6 +311,29 @@ export class PatternView {
     this.#palette.splice(palindex, 1);
   }
 
+  setPaletteItemSymbols(palindex: number, symbols: Symbols) {
+    const { palitem, formats } = this.#palette[palindex]!;
+    this.#palette[palindex] = new CompletePaletteItem(palitem, symbols, formats);
+    this.#redrawSymbols(palindex);
+  }
+
+  setPaletteItemFormats(palindex: number, formats: Formats) {
+    const { palitem, symbols } = this.#palette[palindex]!;
+    this.#palette[palindex] = new CompletePaletteItem(palitem, symbols, formats);
+    // The formats contain the font of the symbols.
+    this.#redrawSymbols(palindex);
+  }
+
+  /** Redraws the symbols of the stitches of the palette item. */
+  #redrawSymbols(palindex: number) {
+    for (const { key: stitch, value: symbol } of this.#symbols.entries()) {
+      if (stitch.palindex === palindex) {
+        this.#stages.symbols.removeChild(symbol);
+        this.addSymbol(stitch);
+      } else this.#symbols.set(stitch, symbol);
+    }
+  }
+
   get allStitchFonts() {
     const fonts = new Set<string>();
     fonts.add(this.defaultStitchFont);
@@ -272,8 +376,8 @@ export class PatternView {
   }
 
   removeSymbol(stitch: Stitch) {
-    const symbol = this.#symbols.delete(stitch)!;
-    this.#stages.symbols.removeChild(symbol);
+    const symbol = this.#symbols.delete(stitch);
+    if (symbol) this.#stages.symbols.removeChild(symbol);
   }
 
   addFullStitch(full: FullStitch) {
@@ -292,7 +396,9 @@ export class PatternView {
   }
 
   removeFullStitch(fullstitch: FullStitch) {
-    const particle = this.#fullstitches.delete(fullstitch)!;
+    // The stitch may be not loaded yet if the pattern is loaded lazily.
+    const particle = this.#fullstitches.delete(fullstitch);
+    if (!particle) return;
     if (fullstitch.kind === FullStitchKind.Full) this.#stages.fullstitches.removeParticle(particle);
     else this.#stages.petites.removeParticle(particle);
   }
@@ -314,7 +420,8 @@ export class PatternView {
   }
 
   removePartStitch(partstitch: PartStitch) {
-    const particle = this.#partstitches.delete(partstitch)!;
+    const particle = this.#partstitches.delete(partstitch);
+    if (!particle) return;
     if (partstitch.kind === PartStitchKind.Half) this.#stages.halfstitches.removeParticle(particle);
     else this.#stages.quarters.removeParticle(particle);
   }
@@ -338,8 +445,8 @@ export class PatternView {
   }
 
   removeLineStitch(line: LineStitch) {
-    const graphics = this.#lines.delete(line)!;
-    this.#stages.lines.removeChild(graphics);
+    const graphics = this.#lines.delete(line);
+    if (graphics) this.#stages.lines.removeChild(graphics);
   }
 
   addNodeStitch(node: NodeStitch) {
@@ -357,8 +464,8 @@ export class PatternView {
   }
 
   removeNodeStitch(node: NodeStitch) {
-    const graphics = this.#nodes.delete(node)!;
-    this.#stages.nodes.removeChild(graphics);
+    const graphics = this.#nodes.delete(node);
+    if (graphics) this.#stages.nodes.removeChild(graphics);
   }
 
   addSpecialStitch(specialStitch: SpecialStitch) {
@@ -406,6 +513,7 @@ export class PatternView {
     if (flip[0]) graphics.scale.x = -1;
     if (flip[1]) graphics.scale.y = -1;
 
+    this.#specialstitches.set(specialStitch, graphics);
     this.#stages.specialstitches.addChild(graphics);
   }
 }
@@ -17,12 +17,35 @@ export class PatternInfo {
   @field({ type: "string" })
   description: string;
 
+  /** Keywords to categorize and search the pattern. They can't contain commas. */
+  @field({ type: vec("string") })
+  keywords: string[];
+
+  @field({ type: "string" })
+  source: string;
+
+  @field({ type: "string" })
+  license: string;
+
+  /** The creation date as a Unix timestamp (in seconds). */
+  @field({ type: option("u64") })
+  createdAt?: bigint;
+
+  /** The date of the last save as a Unix timestamp (in seconds). */
+  @field({ type: option("u64") })
+  modifiedAt?: bigint;
+
   constructor(data: PatternInfo) {
     this.title = data.title;
     this.author = data.author;
     this.company = data.company;
     this.copyright = data.copyright;
     this.description = data.description;
+    this.keywords = data.keywords;
+    this.source = data.source;
+    this.license = data.license;
+    this.createdAt = data.createdAt;
+    this.modifiedAt = data.modifiedAt;
   }
 }
 
@@ -413,5 +436,56 @@ export class Pattern {
   }
 }
 
+export class Rect {
+  @field({ type: "u16" })
+  x: number;
+
+  @field({ type: "u16" })
+  y: number;
+
+  @field({ type: "u16" })
+  width: number;
+
+  @field({ type: "u16" })
+  height: number;
+
+  constructor(data: Rect) {
+    this.x = data.x;
+    this.y = data.y;
+    this.width = data.width;
+    this.height = data.height;
+  }
+}
+
+/** The stitches lying in the area of the pattern. */
+export class PatternRegion {
+  @field({ type: Rect })
+  area: Rect;
+
+  @field({ type: vec(FullStitch) })
+  fullstitches: FullStitch[];
+
+  @field({ type: vec(PartStitch) })
+  partstitches: PartStitch[];
+
+  @field({ type: vec(NodeStitch) })
+  nodes: NodeStitch[];
+
+  @field({ type: vec(LineStitch) })
+  lines: LineStitch[];
+
+  @field({ type: vec(SpecialStitch) })
+  specialstitches: SpecialStitch[];
+
+  constructor(data: PatternRegion) {
+    this.area = data.area;
+    this.fullstitches = data.fullstitches;
+    this.partstitches = data.partstitches;
+    this.nodes = data.nodes;
+    this.lines = data.lines;
+    this.specialstitches = data.specialstitches;
+  }
+}
+
 export type Stitch = FullStitch | PartStitch | NodeStitch | LineStitch;
 export type StitchKind = FullStitchKind | PartStitchKind | NodeStitchKind | LineStitchKind;
@@ -1,5 +1,6 @@
 import { BinaryReader, BinaryWriter, deserialize, deserializeStruct, field, serialize } from "@dao-xyz/borsh";
 import { PatternProject } from "../project";
+import { type DisplaySettings, StitchOutline, StitchSettings, SymbolSettings } from "../display";
 import { FullStitch, LineStitch, NodeStitch, PartStitch, type Stitch } from "../pattern";
 
 class PatternKey {
@@ -52,3 +53,65 @@ export function serializeStitch(stitch: Stitch) {
   serialize(stitch, writer);
   return writer.finalize();
 }
+
+/** A single display setting as it is emitted by the `display:update_setting` event. */
+export type DisplaySetting = Partial<
+  Pick<
+    DisplaySettings,
+    | "showSymbols"
+    | "showGrid"
+    | "showRulers"
+    | "showCenteringMarks"
+    | "showFabricColorsWithSymbols"
+    | "gapsBetweenStitches"
+    | "outlinedStitches"
+    | "zoom"
+    | "stitchOutline"
+    | "symbolSettings"
+    | "stitchSettings"
+  >
+>;
+
+// TODO: remove this function together with the stitches ones, for the same reason.
+export function deserializeDisplaySetting(buffer: Uint8Array): DisplaySetting {
+  const reader = new BinaryReader(buffer);
+  const variant = reader.u8();
+  switch (variant) {
+    case 0: {
+      return { showSymbols: reader.bool() };
+    }
+    case 1: {
+      return { showGrid: reader.bool() };
+    }
+    case 2: {
+      return { showRulers: reader.bool() };
+    }
+    case 3: {
+      return { showCenteringMarks: reader.bool() };
+    }
+    case 4: {
+      return { showFabricColorsWithSymbols: reader.bool() };
+    }
+    case 5: {
+      return { gapsBetweenStitches: reader.bool() };
+    }
+    case 6: {
+      return { outlinedStitches: reader.bool() };
+    }
+    case 7: {
+      return { zoom: reader.u16() };
+    }
+    case 8: {
+      return { stitchOutline: deserializeStruct(StitchOutline, false)(reader) };
+    }
+    case 9: {
+      return { symbolSettings: deserializeStruct(SymbolSettings, false)(reader) };
+    }
+    case 10: {
+      return { stitchSettings: deserializeStruct(StitchSettings, false)(reader) };
+    }
+    default: {
+      throw new Error(`Unknown display setting variant: ${variant}`);
+    }
+  }
+}
@@ -0,0 +1,66 @@
+import { field, option } from "@dao-xyz/borsh";
+
+export class ActionDescription {
+  /** The action type. It can be used to display a localized label. */
+  @field({ type: "string" })
+  kind: string;
+
+  /** A human-readable label of the action. */
+  @field({ type: "string" })
+  label: string;
+
+  /** The number of stitches affected by the action. */
+  @field({ type: "u32" })
+  stitches: number;
+
+  /** The number of palette items affected by the action. */
+  @field({ type: "u32" })
+  paletteItems: number;
+
+  constructor(data: ActionDescription) {
+    this.kind = data.kind;
+    this.label = data.label;
+    this.stitches = data.stitches;
+    this.paletteItems = data.paletteItems;
+  }
+}
+
+export class HistoryChangedData {
+  @field({ type: "string" })
+  patternKey: string;
+
+  @field({ type: "bool" })
+  canUndo: boolean;
+
+  @field({ type: "bool" })
+  canRedo: boolean;
+
+  /** The description of the action that will be undone next. */
+  @field({ type: option(ActionDescription) })
+  undoAction?: ActionDescription;
+
+  /** The description of the action that will be redone next. */
+  @field({ type: option(ActionDescription) })
+  redoAction?: ActionDescription;
+
+  constructor(data: HistoryChangedData) {
+    this.patternKey = data.patternKey;
+    this.canUndo = data.canUndo;
+    this.canRedo = data.canRedo;
+    this.undoAction = data.undoAction;
+    this.redoAction = data.redoAction;
+  }
+}
+
+export class DirtyChangedData {
+  @field({ type: "string" })
+  patternKey: string;
+
+  @field({ type: "bool" })
+  isDirty: boolean;
+
+  constructor(data: DirtyChangedData) {
+    this.patternKey = data.patternKey;
+    this.isDirty = data.isDirty;
+  }
+}
@@ -1,2 +1,3 @@
 export * from "./borsh";
+export * from "./history";
 export * from "./palette";
@@ -22,3 +22,29 @@ export class AddedPaletteItemData {
     this.formats = data.formats;
   }
 }
+
+export class PaletteItemSymbols {
+  @field({ type: "u8" })
+  palindex: number;
+
+  @field({ type: Symbols })
+  symbols: Symbols;
+
+  constructor(data: PaletteItemSymbols) {
+    this.palindex = data.palindex;
+    this.symbols = data.symbols;
+  }
+}
+
+export class PaletteItemFormats {
+  @field({ type: "u8" })
+  palindex: number;
+
+  @field({ type: Formats })
+  formats: Formats;
+
+  constructor(data: PaletteItemFormats) {
+    this.palindex = data.palindex;
+    this.formats = data.formats;
+  }
+}
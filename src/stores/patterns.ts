@@ -3,19 +3,26 @@ import { open, save, type DialogFilter } from "@tauri-apps/plugin-dialog";
 import { defineAsyncComponent, ref, shallowRef, triggerRef } from "vue";
 import { useMagicKeys, whenever } from "@vueuse/core";
 import { useFluent } from "fluent-vue";
-import { useDialog } from "primevue";
+import { useConfirm, useDialog } from "primevue";
 import { defineStore } from "pinia";
 import { deserialize } from "@dao-xyz/borsh";
 import { toByteArray } from "base64-js";
 import { useAppStateStore } from "./state";
 import { DisplayApi, FabricApi, GridApi, HistoryApi, PaletteApi, PathApi, PatternApi, StitchesApi } from "#/api";
-import { PatternView } from "#/plugins/pixi";
+import { PatternView, type VisibleArea } from "#/plugins/pixi";
 import {
   AddedPaletteItemData,
+  deserializeDisplaySetting,
   deserializeStitch,
   deserializeStitches,
+  DirtyChangedData,
   DisplayMode,
+  HistoryChangedData,
+  PaletteItemFormats,
+  PaletteItemSymbols,
   PaletteSettings,
+  PatternInfo,
+  PrintSettings,
 } from "#/schemas/pattern";
 import { PaletteItem, Fabric, Grid, type Stitch } from "#/schemas/pattern";
 
@@ -29,6 +36,7 @@ export const usePatternsStore = defineStore("pattern-project", () => {
 
   const fluent = useFluent();
   const dialog = useDialog();
+  const confirm = useConfirm();
   const FabricProperties = defineAsyncComponent(() => import("#/components/dialogs/FabricProperties.vue"));
   const GridProperties = defineAsyncComponent(() => import("#/components/dialogs/GridProperties.vue"));
 
@@ -37,6 +45,8 @@ export const usePatternsStore = defineStore("pattern-project", () => {
   const blocked = ref(false);
   const loading = ref(false);
   const pattern = shallowRef<PatternView>();
+  /** The undo/redo state of the current pattern. */
+  const history = ref<HistoryChangedData>();
 
   async function loadPattern() {
     const path = await open({
@@ -54,13 +64,26 @@ export const usePatternsStore = defineStore("pattern-project", () => {
   async function openPattern(pathOrKey: string) {
     try {
       loading.value = true;
-      pattern.value = new PatternView(await PatternApi.loadPattern(pathOrKey));
+      // The stitches are loaded separately, by the tiles the canvas shows (see `loadVisibleStitches`).
+      pattern.value = new PatternView(await PatternApi.loadPattern(pathOrKey, true), true);
+      history.value = undefined;
       appStateStore.addOpenedPattern(pattern.value.info.title, pattern.value.key);
     } finally {
       loading.value = false;
     }
   }
 
+  // The pattern has been changed as a whole (e.g., transformed, resized or jumped in the history),
+  // so it is reloaded and its stitches are requested by the visible tiles again.
+  appWindow.listen("pattern:changed", async () => {
+    if (!pattern.value) return;
+    const { key } = pattern.value;
+    const reloaded = new PatternView(await PatternApi.loadPattern(key, true), true);
+    // Another pattern may have been opened while this one was reloading.
+    if (pattern.value?.key !== key) return;
+    pattern.value = reloaded;
+  });
+
   function createPattern() {
     dialog.open(FabricProperties, {
       props: { header: fluent.$t("title-fabric-properties"), modal: true },
@@ -70,6 +93,7 @@ export const usePatternsStore = defineStore("pattern-project", () => {
         try {
           loading.value = true;
           pattern.value = new PatternView(await PatternApi.createPattern(fabric));
+          history.value = undefined;
           appStateStore.addOpenedPattern(pattern.value.info.title, pattern.value.key);
         } finally {
           loading.value = false;
@@ -109,17 +133,65 @@ export const usePatternsStore = defineStore("pattern-project", () => {
 
   async function closePattern() {
     if (!pattern.value) return;
+    const { key, info } = pattern.value;
     try {
       loading.value = true;
-      await PatternApi.closePattern(pattern.value.key);
+      let closed = await PatternApi.closePattern(key);
+      if (!closed) {
+        switch (await askToSaveChanges(info.title)) {
+          case "save": {
+            await savePattern();
+            // The pattern is still dirty if the saving has failed, so it is kept open then.
+            closed = await PatternApi.closePattern(key);
+            break;
+          }
+          case "discard": {
+            closed = await PatternApi.closePattern(key, true);
+            break;
+          }
+        }
+      }
+      if (!closed) return;
+
       appStateStore.removeCurrentPattern();
-      if (!appStateStore.currentPattern) pattern.value = undefined;
+      if (!appStateStore.currentPattern) {
+        pattern.value = undefined;
+        history.value = undefined;
+      }
       else await openPattern(appStateStore.currentPattern.key);
     } finally {
       loading.value = false;
     }
   }
 
+  appWindow.listen<string>("pattern:dirty_changed", ({ payload }) => {
+    const { patternKey, isDirty } = deserialize(toByteArray(payload), DirtyChangedData);
+    appStateStore.updateOpenedPattern(patternKey, { isDirty });
+  });
+  appWindow.listen<string>("pattern:update_info", ({ payload }) => {
+    if (!pattern.value) return;
+    const info = deserialize(toByteArray(payload), PatternInfo);
+    pattern.value.setInfo(info);
+    appStateStore.updateOpenedPattern(pattern.value.key, { title: info.title });
+    triggerRef(pattern);
+  });
+
+  /** Asks the user whether to save the unsaved changes of the pattern before closing it. */
+  function askToSaveChanges(title: string) {
+    return new Promise<"save" | "discard" | "cancel">((resolve) => {
+      confirm.require({
+        header: fluent.$t("title-unsaved-changes"),
+        message: fluent.$t("message-unsaved-changes", { title }),
+        acceptProps: { label: fluent.$t("label-save") },
+        rejectProps: { label: fluent.$t("label-discard"), severity: "secondary", outlined: true },
+        accept: () => resolve("save"),
+        reject: () => resolve("discard"),
+        // The dialog is closed without choosing any option.
+        onHide: () => resolve("cancel"),
+      });
+    });
+  }
+
   function updateFabric() {
     if (!pattern.value) return;
     dialog.open(FabricProperties, {
@@ -190,6 +262,31 @@ export const usePatternsStore = defineStore("pattern-project", () => {
     triggerRef(pattern);
   });
 
+  appWindow.listen<string>("palette:update_symbols", ({ payload }) => {
+    if (!pattern.value) return;
+    const { palindex, symbols } = deserialize(toByteArray(payload), PaletteItemSymbols);
+    pattern.value.setPaletteItemSymbols(palindex, symbols);
+    triggerRef(pattern);
+  });
+  appWindow.listen<string>("palette:update_formats", ({ payload }) => {
+    if (!pattern.value) return;
+    const { palindex, formats } = deserialize(toByteArray(payload), PaletteItemFormats);
+    pattern.value.setPaletteItemFormats(palindex, formats);
+    triggerRef(pattern);
+  });
+
+  /** Loads the stitches of the visible area tile by tile, so the canvas renders them progressively. */
+  async function loadVisibleStitches(area: VisibleArea) {
+    const view = pattern.value;
+    if (!view) return;
+    for (const tile of view.takeUnrequestedTiles(area)) {
+      const region = await StitchesApi.getStitchesInRegion(view.key, tile);
+      // Another pattern may have been opened while the tile was loading.
+      if (pattern.value !== view) return;
+      view.addRegion(region);
+    }
+  }
+
   function addStitch(stitch: Stitch) {
     if (!pattern.value) return;
     return StitchesApi.addStitch(pattern.value.key, stitch);
@@ -239,6 +336,24 @@ export const usePatternsStore = defineStore("pattern-project", () => {
     triggerRef(pattern);
   });
 
+  appWindow.listen<string>("display:update_setting", ({ payload }) => {
+    if (!pattern.value) return;
+    pattern.value.updateDisplaySetting(deserializeDisplaySetting(toByteArray(payload)));
+    triggerRef(pattern);
+  });
+
+  appWindow.listen<string>("print:update", ({ payload }) => {
+    if (!pattern.value) return;
+    pattern.value.printSettings = deserialize(toByteArray(payload), PrintSettings);
+    triggerRef(pattern);
+  });
+
+  appWindow.listen<string>("history:changed", ({ payload }) => {
+    const data = deserialize(toByteArray(payload), HistoryChangedData);
+    // Only the history of the current pattern is tracked.
+    if (pattern.value?.key === data.patternKey) history.value = data;
+  });
+
   const keys = useMagicKeys();
 
   whenever(keys["Ctrl+KeyO"]!, loadPattern);
@@ -260,6 +375,7 @@ export const usePatternsStore = defineStore("pattern-project", () => {
     blocked,
     loading,
     pattern,
+    history,
     loadPattern,
     openPattern,
     createPattern,
@@ -271,6 +387,7 @@ export const usePatternsStore = defineStore("pattern-project", () => {
     addPaletteItem,
     removePaletteItem,
     updatePaletteDisplaySettings,
+    loadVisibleStitches,
     addStitch,
     removeStitch,
     setDisplayMode,
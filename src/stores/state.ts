@@ -5,6 +5,8 @@ import { FullStitchKind, type StitchKind, type PatternKey } from "#/schemas/patt
 interface OpenedPattern {
   title: string;
   key: PatternKey;
+  /** Whether the pattern has unsaved changes. */
+  isDirty?: boolean;
 }
 
 export const useAppStateStore = defineStore(
@@ -38,6 +40,13 @@ export const useAppStateStore = defineStore(
       else currentPattern.value = undefined;
     }
 
+    /** Updates the opened pattern with the given key, if it is opened. */
+    function updateOpenedPattern(key: PatternKey, data: Partial<Omit<OpenedPattern, "key">>) {
+      const openedPattern = openedPatterns.value.find((p) => p.key === key);
+      if (openedPattern) Object.assign(openedPattern, data);
+      if (currentPattern.value?.key === key) Object.assign(currentPattern.value, data);
+    }
+
     return {
       selectedStitchTool,
       selectedPaletteItemIndexes,
@@ -45,6 +54,7 @@ export const useAppStateStore = defineStore(
       currentPattern,
       addOpenedPattern,
       removeCurrentPattern,
+      updateOpenedPattern,
     };
   },
   { persist: { storage: sessionStorage } },
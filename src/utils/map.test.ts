@@ -16,6 +16,13 @@ describe("ObjectedMap", () => {
     expect(map.get(key)).toBeUndefined();
   });
 
+  it("should check whether the key exists", () => {
+    const map = new ObjectedMap<object, string | undefined>();
+    map.set({ id: 1 }, undefined);
+    expect(map.has({ id: 1 })).toBe(true);
+    expect(map.has({ id: 2 })).toBe(false);
+  });
+
   it("should delete a key-value pair by key", () => {
     const map = new ObjectedMap<object, string>();
     const key = { id: 1 };
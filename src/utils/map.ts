@@ -35,6 +35,12 @@ export class ObjectedMap<K extends object, V> {
     return existingIndex >= 0 ? bucket[existingIndex]!.value : undefined;
   }
 
+  /** Check whether the map contains the key. */
+  has(key: K): boolean {
+    const bucket = this.#buckets[this.#getBucketIndex(key)]!;
+    return this.#findEntry(bucket, key) >= 0;
+  }
+
   /** Delete a key-value pair by key. */
   delete(key: K): V | undefined {
     const bucket = this.#buckets[this.#getBucketIndex(key)]!;